@@ -74,6 +74,11 @@ const ir_frame_value = (decorded_frame: DecordedInfraredRemoteFrame): IrFrameVal
         { item_label: '(Logical Inverse) Command', value: decorded_frame.Nec.slice(24, 32) },
       ],
     };
+  } else if ('NecRepeatSequence' in decorded_frame) {
+    return {
+      frame_label: 'NEC (Repeat x' + decorded_frame.NecRepeatSequence.count + ')',
+      items: [],
+    };
   } else if ('NecRepeat' in decorded_frame) {
     return {
       frame_label: 'NEC (Repeat)',
@@ -87,6 +92,52 @@ const ir_frame_value = (decorded_frame: DecordedInfraredRemoteFrame): IrFrameVal
         { item_label: 'address', value: decorded_frame.Sirc.slice(5) },
       ],
     };
+  } else if ('SircRepeat' in decorded_frame) {
+    return {
+      frame_label: 'SIRC (x' + decorded_frame.SircRepeat.count + ')',
+      items: [
+        { item_label: 'command', value: decorded_frame.SircRepeat.frame.slice(0, 5) },
+        { item_label: 'address', value: decorded_frame.SircRepeat.frame.slice(5) },
+      ],
+    };
+  } else if ('Rc5' in decorded_frame) {
+    return {
+      frame_label: 'RC5',
+      items: [
+        { item_label: 'toggle', value: decorded_frame.Rc5.slice(2, 3) },
+        { item_label: 'address', value: decorded_frame.Rc5.slice(3, 8) },
+        { item_label: 'command', value: decorded_frame.Rc5.slice(8, 14) },
+      ],
+    };
+  } else if ('Rc6' in decorded_frame) {
+    return {
+      frame_label: 'RC6',
+      items: [
+        { item_label: 'mode', value: decorded_frame.Rc6.slice(1, 4) },
+        { item_label: 'toggle', value: decorded_frame.Rc6.slice(4, 5) },
+        { item_label: 'address', value: decorded_frame.Rc6.slice(5, 13) },
+        { item_label: 'command', value: decorded_frame.Rc6.slice(13, 21) },
+      ],
+    };
+  } else if ('Samsung' in decorded_frame) {
+    return {
+      frame_label: 'SAMSUNG',
+      items: [
+        { item_label: 'Address', value: decorded_frame.Samsung.slice(0, 8) },
+        { item_label: 'Address', value: decorded_frame.Samsung.slice(8, 16) },
+        { item_label: 'Command', value: decorded_frame.Samsung.slice(16, 24) },
+        { item_label: '(Logical Inverse) Command', value: decorded_frame.Samsung.slice(24, 32) },
+      ],
+    };
+  } else if ('Kaseikyo' in decorded_frame) {
+    return {
+      frame_label: 'KASEIKYO',
+      items: [
+        { item_label: 'OEM code', value: decorded_frame.Kaseikyo.slice(0, 16) },
+        { item_label: 'Payload', value: decorded_frame.Kaseikyo.slice(16, 48) },
+        { item_label: 'Checksum', value: decorded_frame.Kaseikyo.slice(48, 56) },
+      ],
+    };
   } else if ('Unknown' in decorded_frame) {
     return {
       frame_label: 'UNKNOWN',
@@ -0,0 +1,321 @@
+// Copyright (c) 2023 Akihiro Yamamoto.
+// Licensed under the MIT License <https://spdx.org/licenses/MIT.html>
+// See LICENSE file in the project root for full license information.
+//
+use crate::infrared_remote::{
+    decord_receiving_data, panasonic_hvac, toshiba_tv, DecordedInfraredRemoteFrame,
+    InfraredRemoteControlCode, LsbFirst, MarkAndSpaceIrCarrier, MarkAndSpaceMicros, Microseconds,
+};
+use base64::{engine::general_purpose, Engine as _};
+
+// Pronto形式の基準クロックの周期(マイクロ秒)
+pub const PRONTO_CLOCK_PERIOD_MICROS: f64 = 0.241246;
+
+// 搬送波周波数からPronto形式の周波数ワードを得る
+fn pronto_frequency_word(carrier_hz: u32) -> u16 {
+    (1_000_000.0 / (carrier_hz.max(1) as f64 * PRONTO_CLOCK_PERIOD_MICROS)).round() as u16
+}
+
+// ヘッダに続けてON/OFFの時間を並べたPronto形式の16進数文字列にする
+fn pronto_words(
+    signals: &[MarkAndSpaceMicros],
+    carrier_hz: u32,
+    burst_pairs: impl Fn(u16) -> (u16, u16),
+) -> String {
+    let frequency = pronto_frequency_word(carrier_hz);
+    let carrier_period = frequency as f64 * PRONTO_CLOCK_PERIOD_MICROS;
+    let to_word = |Microseconds(t): Microseconds| (t as f64 / carrier_period).round() as u16;
+    let (once, repeat) = burst_pairs(signals.len() as u16);
+    let header = [0x0000, frequency, once, repeat];
+    header
+        .into_iter()
+        .chain(
+            signals
+                .iter()
+                .flat_map(|ms| [to_word(ms.mark), to_word(ms.space)]),
+        )
+        .map(|word| format!("{:04X}", word))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// Pronto形式の16進数文字列にする
+//
+// 0000 <周波数> <1回目のペア数> <繰り返しのペア数> <ON> <OFF>...
+// ON/OFFの時間は搬送波の周期数で表す
+pub fn to_pronto_hex(signals: &[MarkAndSpaceMicros], carrier_hz: u16) -> String {
+    pronto_words(signals, carrier_hz as u32, |pairs| (pairs, 0x0000))
+}
+
+// 繰り返し部分に全てのペアを置いたPronto形式の16進数文字列にする
+//
+// 0000 <周波数> 0000 <繰り返しのペア数> <ON> <OFF>...
+pub fn to_pronto_code(signals: &[MarkAndSpaceMicros], carrier_hz: u32) -> String {
+    pronto_words(signals, carrier_hz, |pairs| (0x0000, pairs))
+}
+
+// マイクロ秒をBroadlinkの時間単位 269/8192 ms = 約32.84us にする
+fn to_broadlink_ticks(Microseconds(t): Microseconds) -> u16 {
+    ((t as u64 * 8192 + 269_000 / 2) / 269_000).min(u16::MAX as u64) as u16
+}
+
+// Broadlink形式(base64)の文字列にする
+//
+// 0x26(赤外線) 0x00(リピート回数) <長さ下位> <長さ上位> <時間>...
+// 時間は1バイトで表せる場合はそのまま, 256以上は 0x00 <上位> <下位> の3バイトで表す
+pub fn to_broadlink_base64(signals: &[MarkAndSpaceMicros]) -> String {
+    let body = signals
+        .iter()
+        .flat_map(|ms| [ms.mark, ms.space])
+        .flat_map(|t| match to_broadlink_ticks(t) {
+            ticks @ 1..=0xff => vec![ticks as u8],
+            ticks => vec![0x00, (ticks >> 8) as u8, (ticks & 0xff) as u8],
+        })
+        .collect::<Vec<u8>>();
+    let length = body.len() as u16;
+    let header = [0x26, 0x00, (length & 0xff) as u8, (length >> 8) as u8];
+    let packet = header.into_iter().chain(body).collect::<Vec<u8>>();
+    general_purpose::STANDARD.encode(packet)
+}
+
+// 搬送波の周期数を16ビットリトルエンディアンで並べた16進数文字列にする
+//
+// 5601A900... の形式(parse_infrared_code_textの入力形式)
+pub fn to_carrier_hex(signals: &[MarkAndSpaceMicros]) -> String {
+    signals
+        .iter()
+        .map(|&ms| MarkAndSpaceIrCarrier::from(ms).to_string_little_endian_u16())
+        .collect()
+}
+
+// Home Assistantに送るJSONにする
+//
+// NEC: { "protocol": "nec", "data": <送信順の4バイトを上位から並べた32ビット値> }
+// AEHA: { "protocol": "aeha", "data": [[<第1フレームのバイト列>], ...] }
+// 復号結果から送信データを再構成できない機器はNone
+pub fn to_home_assistant_format(code: &InfraredRemoteControlCode) -> Option<serde_json::Value> {
+    let get = |name: &str| code.0.get(name).map(|s| s.as_str());
+    match code.manufacturer()? {
+        "toshiba" => {
+            let payload = toshiba_tv::encode_payload(get("address")?, get("command")?).ok()?;
+            Some(serde_json::json!({
+                "protocol": "nec",
+                "data": u32::from_be_bytes(payload),
+            }))
+        }
+        "panasonic" if code.hvac_mode().is_some() => {
+            // タイマーも含めて符号化し直す
+            let signals = panasonic_hvac::PanasonicHvac::try_from(code.clone())
+                .ok()?
+                .encode()
+                .ok()?;
+            let data = decord_receiving_data(&signals, None)
+                .ok()?
+                .iter()
+                .filter_map(|frame| match frame {
+                    DecordedInfraredRemoteFrame::Aeha(bits) => Some(
+                        bits.chunks_exact(8)
+                            .filter_map(|x| LsbFirst::try_from(x).ok())
+                            .map(u8::from)
+                            .collect::<Vec<u8>>(),
+                    ),
+                    _ => None,
+                })
+                .collect::<Vec<Vec<u8>>>();
+            Some(serde_json::json!({ "protocol": "aeha", "data": data }))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod format_tests {
+    use crate::format::*;
+    use crate::infrared_remote::{decord_receiving_data, protocol_aeha};
+    use crate::parsing::{parse_broadlink_format, parse_infrared_code_text};
+
+    #[test]
+    fn test1_to_pronto_hex() {
+        let pronto = "0000 006D 0012 0000 0156 00AB 0015 0015 0015 0040 0015 0015 0015 0040 0015 0015 0015 0040 0015 0015 0015 0040 0015 0015 0015 0040 0015 0015 0015 0040 0015 0015 0015 0040 0015 0015 0015 0040 0015 05F1";
+        let signals = parse_infrared_code_text(pronto).unwrap();
+        let x = to_pronto_hex(&signals, 38000);
+        assert_eq!(
+            x.split_whitespace().collect::<Vec<&str>>(),
+            pronto.split_whitespace().collect::<Vec<&str>>()
+        );
+    }
+
+    #[test]
+    fn test2_to_pronto_hex() {
+        let signals = vec![
+            MarkAndSpaceMicros::from((Microseconds(9000), Microseconds(4500))),
+            MarkAndSpaceMicros::from((Microseconds(560), Microseconds(40000))),
+        ];
+        let x = to_pronto_hex(&signals, 38000);
+        assert_eq!(x, "0000 006D 0002 0000 0156 00AB 0015 05F1");
+    }
+
+    #[test]
+    fn test5_to_pronto_code() {
+        // NEC 38kHz
+        let pronto = "0000 006D 0000 0012 0156 00AB 0015 0015 0015 0040 0015 0015 0015 0040 0015 0015 0015 0040 0015 0015 0015 0040 0015 0015 0015 0040 0015 0015 0015 0040 0015 0015 0015 0040 0015 0015 0015 0040 0015 05F1";
+        let signals = parse_infrared_code_text(pronto).unwrap();
+        let x = to_pronto_code(&signals, 38000);
+        assert_eq!(x, pronto);
+    }
+
+    #[test]
+    fn test9_to_pronto_code() {
+        // toshiba_tv decode_tests test1 の受信データ(東芝テレビ 電源)
+        let rxdata = "5601A900180015001800140018001400190013001900140019001400170040001700150018003F0019003E0018003E0019003F0019003E00170040001800140019003E001800150018003F00180014001800140019003F0018001400170016001700150018003F001800140018003F0018003F001800140019003F0018003F0018003E0019004F03";
+        let signals = parse_infrared_code_text(rxdata).unwrap();
+        let expected = "0000 006D 0000 0022 0156 00A9 0018 0015 0018 0014 0018 0014 0019 0013 0019 0014 0019 0014 0017 0040 0017 0015 0018 003F 0019 003E 0018 003E 0019 003F 0019 003E 0017 0040 0018 0014 0019 003E 0018 0015 0018 003F 0018 0014 0018 0014 0019 003F 0018 0014 0017 0016 0017 0015 0018 003F 0018 0014 0018 003F 0018 003F 0018 0014 0019 003F 0018 003F 0018 003E 0019 0350";
+        assert_eq!(to_pronto_code(&signals, 38000), expected);
+    }
+
+    #[test]
+    fn test3_to_broadlink_base64() {
+        let signals = vec![
+            MarkAndSpaceMicros::from((Microseconds(558), Microseconds(1116))),
+            MarkAndSpaceMicros::from((Microseconds(8406), Microseconds(35000))),
+        ];
+        let x = to_broadlink_base64(&signals);
+        // 0x26 0x00 0x08 0x00 | 0x11 0x22 0x00 0x01 0x00 0x00 0x04 0x2a
+        assert_eq!(x, "JgAIABEiAAEAAAQq");
+    }
+
+    #[test]
+    fn test4_to_broadlink_base64() {
+        let onoff_pair = "5601A900180015001800140018001400190013001900140019001400170040001700150018003F0019003E0018003E0019003F0019003E00170040001800140019003E001800150018003F00180014001800140019003F0018001400170016001700150018003F001800140018003F0018003F001800140019003F0018003F0018003E0019004F03";
+        let signals = parse_infrared_code_text(onoff_pair).unwrap();
+        let x = parse_broadlink_format(&to_broadlink_base64(&signals)).unwrap();
+        assert_eq!(x.len(), signals.len());
+        // 量子化誤差(1カウント 約33us)の範囲で一致する
+        for (x, y) in x.iter().zip(signals.iter()) {
+            assert!(x.mark.0.abs_diff(y.mark.0) <= 33);
+            assert!(x.space.0.abs_diff(y.space.0) <= 33);
+        }
+    }
+
+    #[test]
+    fn test6_to_carrier_hex() {
+        // toshiba_tv::decode_tests::test1
+        let rxdata = "5601A900180015001800140018001400190013001900140019001400170040001700150018003F0019003E0018003E0019003F0019003E00170040001800140019003E001800150018003F00180014001800140019003F0018001400170016001700150018003F001800140018003F0018003F001800140019003F0018003F0018003E0019004F03";
+        let signals = parse_infrared_code_text(rxdata).unwrap();
+        let hex = to_carrier_hex(&signals);
+        assert_eq!(hex.len(), rxdata.len());
+        // マイクロ秒との相互変換は切り捨てなので各カウントは1までずれる
+        let counts = |s: &str| {
+            s.as_bytes()
+                .chunks(4)
+                .map(|x| {
+                    let x = std::str::from_utf8(x).unwrap();
+                    u16::from_str_radix(&format!("{}{}", &x[2..4], &x[0..2]), 16).unwrap()
+                })
+                .collect::<Vec<u16>>()
+        };
+        counts(&hex)
+            .into_iter()
+            .zip(counts(rxdata))
+            .for_each(|(a, b)| assert!(a.abs_diff(b) <= 1));
+        assert!(hex.starts_with("5601A800"));
+        // 同じ信号として復号できる
+        let reparsed = parse_infrared_code_text(&hex).unwrap();
+        assert_eq!(
+            decord_receiving_data(&reparsed, None).unwrap(),
+            decord_receiving_data(&signals, None).unwrap()
+        );
+        assert_eq!(to_carrier_hex(&[]), "");
+    }
+
+    #[test]
+    fn test7_to_home_assistant_format() {
+        // toshiba_tv decode_tests test1
+        let rxdata = "5601A900180015001800140018001400190013001900140019001400170040001700150018003F0019003E0018003E0019003F0019003E00170040001800140019003E001800150018003F00180014001800140019003F0018001400170016001700150018003F001800140018003F0018003F001800140019003F0018003F0018003E0019004F03";
+        let signals = parse_infrared_code_text(rxdata).unwrap();
+        let frames = decord_receiving_data(&signals, None).unwrap();
+        let code = &toshiba_tv::decode(&frames)[0];
+        let result = to_home_assistant_format(code).unwrap();
+        assert_eq!(
+            serde_json::to_string(&result).unwrap(),
+            r#"{"data":1086264045,"protocol":"nec"}"#
+        );
+    }
+
+    #[test]
+    fn test8_to_home_assistant_format() {
+        let signals =
+            panasonic_hvac::encode("hvac_mode_cool", "power_on", 26, "auto", "auto", "normal")
+                .unwrap();
+        let frames = decord_receiving_data(&signals, None).unwrap();
+        let code = &panasonic_hvac::decode(&frames)[0];
+        let result = to_home_assistant_format(code).unwrap();
+        assert_eq!(result["protocol"], "aeha");
+        let data = result["data"].as_array().unwrap();
+        assert_eq!(data.len(), 2);
+        assert_eq!(data[1].as_array().unwrap().len(), 19);
+        assert_eq!(data[1][0], 0x02);
+        assert_eq!(data[1][1], 0x20);
+        // 再構成できない機器
+        let mut unknown = code.clone();
+        unknown
+            .0
+            .insert("manufacturer".to_owned(), "unknown".to_owned());
+        assert_eq!(to_home_assistant_format(&unknown), None);
+    }
+
+    #[test]
+    fn test9_to_home_assistant_format() {
+        // panasonic_hvac decode_panasonic_tests test1
+        let rxdata = "8800410014001000130032001300100013001000130010001300100014001000130010001300100013001000130010001400100013001000130032001300100013001000130010001400100013001000130010001300100014003100130032001300320013001000130010001300320013001000130010001400100013001000130010001300100014000F00140010001300100013001000130010001300100014001000130010001300100013001000140010001300100013001000130010001300100014001000130010001300100013001000130010001400100013001000130010001400100013003100140031001300100013001000140010001300100013001000130082018800410013001000140031001300100014001000130010001300100014000F001400100013001000130010001300100014000F00140010001300320013001000130010001300100014000F001400100013001000130010001300320013003200130032001300100013001000130032001300100013001000140010001300100013001000130010001300100014001000130010001300100013001000140010001300100013003200130010001300100014003100130032001300310014001000130010001300100013001000140031001300100014003100130032001300100013000F0013001000140010001300100013001000130010001300100014001000130031001400310013003200130032001300320013001000130032001300100013003200130031001400100013003100140031001300100013001000140010001300100013001000130010001400100013001000130010001300100013001000140010001300100013003200130032001300310014000F0014001000130010001300100013001000130010001400100013001000130010001300320013003200130031001400100013001000130010001300100013001000140010001300320013001000130010001300100014000F001400100013001000130010001300100014001000130010001300320013003200130010001300100013001000130011001300310013001000140010001300100013001000130010001300100014001000130010001300100013001000130011001300310013001000140010001300100013001000130032001300320013001000130032001300100013003200130032001300100013004F03";
+        let signals = parse_infrared_code_text(rxdata).unwrap();
+        let frames = decord_receiving_data(&signals, None).unwrap();
+        let code = &panasonic_hvac::decode(&frames)[0];
+        let result = to_home_assistant_format(code).unwrap();
+        let captured = frames
+            .iter()
+            .filter_map(|frame| match frame {
+                DecordedInfraredRemoteFrame::Aeha(bits) => Some(
+                    bits.chunks_exact(8)
+                        .filter_map(|x| LsbFirst::try_from(x).ok())
+                        .map(u8::from)
+                        .collect::<Vec<u8>>(),
+                ),
+                _ => None,
+            })
+            .collect::<Vec<Vec<u8>>>();
+        // 表にないプロファイル(0x40)は通常(0x10)として送る
+        let mut expected = captured.clone();
+        expected[1][0x0d] = 0x10;
+        expected[1][18] = expected[1][..18]
+            .iter()
+            .fold(0u8, |acc, &x| acc.wrapping_add(x));
+        assert_eq!(result["data"], serde_json::json!(expected));
+        // タイマーも送る
+        let mut timer = code.clone();
+        timer
+            .0
+            .insert("timer_on_enabled".to_owned(), "enabled".to_owned());
+        timer
+            .0
+            .insert("timer_on_duration_minutes".to_owned(), "90".to_owned());
+        let result = to_home_assistant_format(&timer).unwrap();
+        let frames = result["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|octets| {
+                let octets = serde_json::from_value::<Vec<u8>>(octets.clone()).unwrap();
+                decord_receiving_data(&protocol_aeha::encode_frame(&octets), None).unwrap()
+            })
+            .collect::<Vec<DecordedInfraredRemoteFrame>>();
+        let decoded = &panasonic_hvac::decode(&frames)[0];
+        assert_eq!(
+            decoded
+                .0
+                .get("timer_on_duration_minutes")
+                .map(|s| s.as_str()),
+            Some("90")
+        );
+    }
+}
@@ -6,7 +6,11 @@ pub mod decord_ir_frames;
 pub mod decord_receiving_data;
 pub mod devices;
 pub mod protocol_aeha;
+pub mod protocol_kaseikyo;
 pub mod protocol_nec;
+pub mod protocol_rc5;
+pub mod protocol_rc6;
+pub mod protocol_samsung;
 pub mod protocol_sirc;
 pub use crate::infrared_remote::bit::*;
 pub use crate::infrared_remote::decord_ir_frames::*;
@@ -15,37 +19,285 @@ pub use crate::infrared_remote::devices::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::convert;
-use std::convert::TryInto;
 use std::fmt;
 use std::iter;
 use std::ops;
 use thiserror::Error;
 
 /// オクテット単位にまとめる
+/// 8ビットに満たない最後のオクテットは Bit::Lo で埋める
 pub fn pack_to_octets(bits: &[Bit]) -> Vec<[Bit; 8]> {
-    let mut octets: Vec<[Bit; 8]> = vec![];
-    for idx in (0..bits.len()).step_by(8) {
-        let slice = bits.get(idx..idx + 8).unwrap_or(&bits[idx..]);
-        octets.push(slice.try_into().expect("slice with incorrect length"));
-    }
-    octets
+    pack_to_octets_padded(bits, Bit::Lo)
+}
+
+/// オクテット単位にまとめる
+/// 8ビットに満たない最後のオクテットは padding で埋める
+pub fn pack_to_octets_padded(bits: &[Bit], padding: Bit) -> Vec<[Bit; 8]> {
+    bits.chunks(8)
+        .map(|chunk| {
+            let mut octet = [padding; 8];
+            octet[..chunk.len()].copy_from_slice(chunk);
+            octet
+        })
+        .collect()
+}
+
+#[test]
+fn test_pack_to_octets() {
+    use Bit::{Hi, Lo};
+    let bits = [Hi, Lo, Hi, Hi, Lo, Lo, Hi, Lo, Hi, Hi, Hi, Hi];
+    assert_eq!(
+        pack_to_octets(&bits),
+        vec![
+            [Hi, Lo, Hi, Hi, Lo, Lo, Hi, Lo],
+            [Hi, Hi, Hi, Hi, Lo, Lo, Lo, Lo]
+        ]
+    );
+    assert_eq!(
+        pack_to_octets_padded(&bits[..10], Hi),
+        vec![
+            [Hi, Lo, Hi, Hi, Lo, Lo, Hi, Lo],
+            [Hi, Hi, Hi, Hi, Hi, Hi, Hi, Hi]
+        ]
+    );
+    assert_eq!(pack_to_octets(&bits[..8]).len(), 1);
+    assert!(pack_to_octets(&[]).is_empty());
 }
 
 #[derive(Error, Debug, PartialEq)]
 pub enum InfraredRemoteError {
     #[error("input is empty.")]
     InputIsEmptyError,
-    #[error("insufficient input data. (expected {0} bits, actual {1} bits)")]
-    InsufficientInputData(usize, usize),
+    #[error("insufficient input data. (expected {expected} bits, actual {actual} bits)")]
+    InsufficientInputData { expected: usize, actual: usize },
+    #[error("NEC complement mismatch.")]
+    NecComplementMismatch,
+    #[error("invalid {protocol} frame length. ({actual} bits)")]
+    InvalidFrameLength {
+        protocol: &'static str,
+        actual: usize,
+    },
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum EncoderError {
+    #[error("temperature out of range. (expected {0} to {1}, actual {2})")]
+    TemperatureOutOfRange(u8, u8, u8),
+    #[error("unknown {0}. ({1})")]
+    UnknownValue(String, String),
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SignalValidationError {
+    #[error("too few signals. (expected at least 2, actual {0})")]
+    TooFewSignals(usize),
+    #[error("zero width pulse at {0}. ({1:?})")]
+    ZeroWidthPulse(usize, MarkAndSpaceMicros),
+    #[error("too long mark at {0}. ({1:?})")]
+    TooLongMark(usize, MarkAndSpaceMicros),
+    #[error("too long duration. ({0:?})")]
+    TooLongDuration(Microseconds),
+    #[error("too many signals. (limit {limit}, actual {actual})")]
+    TooManySignals { limit: usize, actual: usize },
+}
+
+/// 値からキーを逆引きする
+pub fn find_key_by_value<K: Copy>(
+    hm: &HashMap<K, &'static str>,
+    name: &str,
+    value: &str,
+) -> Result<K, EncoderError> {
+    hm.iter()
+        .find(|(_, &v)| v == value)
+        .map(|(&k, _)| k)
+        .ok_or(EncoderError::UnknownValue(
+            name.to_owned(),
+            value.to_owned(),
+        ))
 }
 
 /// ずれ時間の許容範囲はとりあえず 300us
 pub const TOLERANCE: Microseconds = Microseconds(300);
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// プロトコル毎のずれ時間の許容範囲
+pub struct ProtocolTolerances {
+    pub nec: Microseconds,
+    pub aeha: Microseconds,
+    pub sirc: Microseconds,
+}
+
+/// プロトコル毎のずれ時間の許容範囲の既定値
+pub const DEFAULT_TOLERANCES: ProtocolTolerances = ProtocolTolerances {
+    nec: TOLERANCE,
+    aeha: TOLERANCE,
+    sirc: TOLERANCE,
+};
+
+// プロトコル毎の公称値
+struct NominalTiming {
+    leader: MarkAndSpaceMicros,
+    bit_zero: MarkAndSpaceMicros,
+    bit_one: MarkAndSpaceMicros,
+    compare_leader_pulse: fn(Microseconds, &MarkAndSpaceMicros) -> bool,
+    demodulate: fn(MarkAndSpaceMicros) -> Bit,
+}
+
+// 1フレームの各マーク, スペースの公称値からの最大のずれ時間
+// リーダーパルスが一致しなければNone
+fn max_deviation(timing: &NominalTiming, frame: &[MarkAndSpaceMicros]) -> Option<u32> {
+    let (leader, trailer) = frame.split_first()?;
+    if !(timing.compare_leader_pulse)(TOLERANCE, leader) {
+        return None;
+    }
+    let deviation = |x: Microseconds, y: Microseconds| x.0.abs_diff(y.0);
+    let last = trailer.len().saturating_sub(1);
+    let data = trailer.iter().enumerate().map(|(index, &ms)| {
+        let nominal = match (timing.demodulate)(ms) {
+            Bit::Lo => timing.bit_zero,
+            Bit::Hi => timing.bit_one,
+        };
+        // 最後のスペースはフレーム間の間隔なので除く
+        if index == last {
+            deviation(ms.mark, nominal.mark)
+        } else {
+            deviation(ms.mark, nominal.mark).max(deviation(ms.space, nominal.space))
+        }
+    });
+    data.chain([
+        deviation(leader.mark, timing.leader.mark),
+        deviation(leader.space, timing.leader.space),
+    ])
+    .max()
+}
+
+/// 見本の信号からプロトコル毎のずれ時間の許容範囲を求める
+/// 観測した最大のずれ時間の1.5倍にする(見本のないプロトコルは既定値)
+pub fn calibrate_tolerance(samples: &[Vec<MarkAndSpaceMicros>]) -> ProtocolTolerances {
+    let calibrate = |timing: NominalTiming| {
+        samples
+            .iter()
+            .flat_map(|sample| sample.split_inclusive(|ms| THRESHOLD_FRAME_GAP <= ms.space))
+            .filter_map(|frame| max_deviation(&timing, frame))
+            .max()
+            .map(|max| Microseconds((max * 3 / 2).max(1)))
+            .unwrap_or(TOLERANCE)
+    };
+    ProtocolTolerances {
+        nec: calibrate(NominalTiming {
+            leader: protocol_nec::LEADER,
+            bit_zero: protocol_nec::TYPICAL_BIT_ZERO,
+            bit_one: protocol_nec::TYPICAL_BIT_ONE,
+            compare_leader_pulse: protocol_nec::compare_leader_pulse,
+            demodulate: protocol_nec::demodulate,
+        }),
+        aeha: calibrate(NominalTiming {
+            leader: protocol_aeha::LEADER,
+            bit_zero: protocol_aeha::TYPICAL_BIT_ZERO,
+            bit_one: protocol_aeha::TYPICAL_BIT_ONE,
+            compare_leader_pulse: protocol_aeha::compare_leader_pulse,
+            demodulate: protocol_aeha::demodulate,
+        }),
+        sirc: calibrate(NominalTiming {
+            leader: protocol_sirc::LEADER,
+            bit_zero: protocol_sirc::TYPICAL_BIT_ZERO,
+            bit_one: protocol_sirc::TYPICAL_BIT_ONE,
+            compare_leader_pulse: protocol_sirc::compare_leader_pulse,
+            demodulate: protocol_sirc::demodulate,
+        }),
+    }
+}
+
+#[test]
+fn test_calibrate_tolerance() {
+    let nec = protocol_nec::encode_nec([0x40, 0xbf, 0x12, 0xed]);
+    // 少しずつずらした3つの見本
+    let samples = [17u32, 43, 71]
+        .into_iter()
+        .map(|seed| {
+            nec.iter()
+                .enumerate()
+                .map(|(index, ms)| {
+                    let jitter = (index as u32 * seed) % 100;
+                    MarkAndSpace {
+                        mark: ms.mark + Microseconds(jitter),
+                        space: ms.space.saturating_sub(Microseconds(jitter / 2)),
+                    }
+                })
+                .collect::<Vec<MarkAndSpaceMicros>>()
+        })
+        .collect::<Vec<Vec<MarkAndSpaceMicros>>>();
+    let result = calibrate_tolerance(&samples);
+    assert!(Microseconds(0) < result.nec);
+    assert!(result.nec < Microseconds(300));
+    // 見本のないプロトコルは既定値
+    assert_eq!(result.aeha, TOLERANCE);
+    assert_eq!(result.sirc, TOLERANCE);
+    // 求めた許容範囲で復号できる
+    for sample in samples.iter() {
+        assert_eq!(
+            decord_receiving_data(sample, Some(&result)).unwrap(),
+            decord_receiving_data(&nec, None).unwrap()
+        );
+    }
+    assert_eq!(calibrate_tolerance(&[]), DEFAULT_TOLERANCES);
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 /// マイクロ秒型
 pub struct Microseconds(pub u32);
 
+impl Microseconds {
+    /// 桁あふれの場合はNoneを返す加算
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Microseconds)
+    }
+
+    /// 桁あふれの場合は最大値にする加算
+    pub fn saturating_add(self, other: Self) -> Self {
+        Microseconds(self.0.saturating_add(other.0))
+    }
+
+    /// 負になる場合はNoneを返す減算
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.0.checked_sub(other.0).map(Microseconds)
+    }
+
+    /// 負になる場合は0にする減算
+    pub fn saturating_sub(self, other: Self) -> Self {
+        Microseconds(self.0.saturating_sub(other.0))
+    }
+}
+
+#[test]
+fn test_microseconds_checked_and_saturating() {
+    assert_eq!(Microseconds(100).checked_sub(Microseconds(200)), None);
+    assert_eq!(
+        Microseconds(300).checked_sub(Microseconds(200)),
+        Some(Microseconds(100))
+    );
+    assert_eq!(
+        Microseconds(100).saturating_sub(Microseconds(200)),
+        Microseconds(0)
+    );
+    assert_eq!(Microseconds(u32::MAX).checked_add(Microseconds(1)), None);
+    assert_eq!(
+        Microseconds(1).checked_add(Microseconds(2)),
+        Some(Microseconds(3))
+    );
+    assert_eq!(
+        Microseconds(u32::MAX).saturating_add(Microseconds(1)),
+        Microseconds(u32::MAX)
+    );
+    // 許容範囲がリーダーパルスより長くても桁あふれしない
+    assert!(protocol_sirc::compare_leader_pulse(
+        Microseconds(1000),
+        &protocol_sirc::LEADER
+    ));
+}
+
 impl ops::Add for Microseconds {
     type Output = Microseconds;
     /// マイクロ秒型の加算演算子
@@ -62,20 +314,176 @@ impl ops::Sub for Microseconds {
     }
 }
 
+impl fmt::Display for Microseconds {
+    /// 1000us未満は "440µs", 1000us以上はミリ秒で "9ms", "1.687ms"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0 < 1000 {
+            write!(f, "{}µs", self.0)
+        } else {
+            let millis = format!("{}.{:03}", self.0 / 1000, self.0 % 1000);
+            write!(
+                f,
+                "{}ms",
+                millis.trim_end_matches('0').trim_end_matches('.')
+            )
+        }
+    }
+}
+
+/// マイクロ秒型を "9000µs" の文字列で読み書きする
+/// #[serde(with = "microseconds_human")] で使う
+/// 読み込みは数値, "9000µs", "9000us", "9ms" を受け付ける
+pub mod microseconds_human {
+    use super::Microseconds;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(t: &Microseconds, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{}µs", t.0))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Microseconds, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Number(u32),
+            Text(String),
+        }
+        match Repr::deserialize(deserializer)? {
+            Repr::Number(n) => Ok(Microseconds(n)),
+            Repr::Text(s) => parse(s.trim())
+                .map(Microseconds)
+                .ok_or_else(|| de::Error::custom(format!("invalid microseconds. ({})", s))),
+        }
+    }
+
+    fn parse(s: &str) -> Option<u32> {
+        if let Some(n) = s.strip_suffix("µs").or_else(|| s.strip_suffix("us")) {
+            n.trim().parse().ok()
+        } else if let Some(n) = s.strip_suffix("ms") {
+            let millis: f64 = n.trim().parse().ok()?;
+            let micros = (millis * 1000.0).round();
+            (0.0..=u32::MAX as f64)
+                .contains(&micros)
+                .then_some(micros as u32)
+        } else {
+            s.parse().ok()
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// 時間を "9000µs" の文字列で読み書きするマークアンドスペース
+pub struct MarkAndSpaceHuman {
+    #[serde(with = "microseconds_human")]
+    pub mark: Microseconds,
+    #[serde(with = "microseconds_human")]
+    pub space: Microseconds,
+}
+
+impl From<MarkAndSpaceMicros> for MarkAndSpaceHuman {
+    fn from(ms: MarkAndSpaceMicros) -> Self {
+        MarkAndSpaceHuman {
+            mark: ms.mark,
+            space: ms.space,
+        }
+    }
+}
+
+impl From<MarkAndSpaceHuman> for MarkAndSpaceMicros {
+    fn from(ms: MarkAndSpaceHuman) -> Self {
+        MarkAndSpace {
+            mark: ms.mark,
+            space: ms.space,
+        }
+    }
+}
+
+#[test]
+fn test_microseconds_human() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Human(#[serde(with = "microseconds_human")] Microseconds);
+
+    assert_eq!(Microseconds(440).to_string(), "440µs");
+    assert_eq!(Microseconds(9000).to_string(), "9ms");
+    assert_eq!(Microseconds(1687).to_string(), "1.687ms");
+    assert_eq!(Microseconds(1500).to_string(), "1.5ms");
+    assert_eq!(serde_json::to_string(&Microseconds(9000)).unwrap(), "9000");
+    assert_eq!(
+        serde_json::to_string(&Human(Microseconds(9000))).unwrap(),
+        r#""9000µs""#
+    );
+    for input in [
+        r#"9000"#,
+        r#""9000µs""#,
+        r#""9000us""#,
+        r#""9ms""#,
+        r#""9.0ms""#,
+    ] {
+        assert_eq!(
+            serde_json::from_str::<Human>(input).unwrap(),
+            Human(Microseconds(9000)),
+            "{}",
+            input
+        );
+    }
+    assert!(serde_json::from_str::<Human>(r#""9s""#).is_err());
+    assert!(serde_json::from_str::<Human>(r#""-1ms""#).is_err());
+    //
+    let human = MarkAndSpaceHuman::from(protocol_nec::LEADER);
+    assert_eq!(
+        serde_json::to_string(&human).unwrap(),
+        r#"{"mark":"8992µs","space":"4496µs"}"#
+    );
+    let parsed = serde_json::from_str::<MarkAndSpaceHuman>(r#"{"mark":"8.992ms","space":4496}"#);
+    assert_eq!(
+        parsed.map(MarkAndSpaceMicros::from).unwrap(),
+        protocol_nec::LEADER
+    );
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 /// 赤外線リモコン信号のキャリア周波数カウンタ型
 pub struct IrCarrierCounter(pub u16);
 
 impl IrCarrierCounter {
     /// 16ビットリトルエンディアンで出力する
-    pub fn to_string_littel_endian_u16(self) -> String {
+    pub fn to_string_little_endian_u16(self) -> String {
         let upper = (self.0 >> 8) & 0xff;
         let lower = self.0 & 0xff;
         format!("{lower:02X}{upper:02X}")
     }
+
+    /// 16ビットリトルエンディアンで出力する(旧名)
+    #[deprecated(since = "0.1.0", note = "use to_string_little_endian_u16")]
+    pub fn to_string_littel_endian_u16(self) -> String {
+        self.to_string_little_endian_u16()
+    }
 }
 
 #[test]
+fn test_to_string_little_endian_u16() {
+    assert_eq!(
+        IrCarrierCounter(0x1234).to_string_little_endian_u16(),
+        "3412"
+    );
+    assert_eq!(
+        IrCarrierCounter(0x00ff).to_string_little_endian_u16(),
+        "FF00"
+    );
+    assert_eq!(
+        MarkAndSpaceIrCarrier {
+            mark: IrCarrierCounter(0x0156),
+            space: IrCarrierCounter(0x00AB),
+        }
+        .to_string_little_endian_u16(),
+        "5601AB00"
+    );
+}
+
+#[test]
+#[allow(deprecated)]
 fn test_to_string_littel_endian_u16() {
     assert_eq!(
         IrCarrierCounter(0x1234).to_string_littel_endian_u16(),
@@ -129,27 +537,107 @@ impl fmt::Display for IrCarrierCounter {
 /// 38000 Hz = 38 kHz
 pub const IR_CARRIER_FREQ: u16 = 38000;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// 赤外線リモコン信号のキャリア周波数(Hz)
+pub struct IrCarrier {
+    pub freq_hz: u32,
+}
+
+/// NECフォーマットのキャリア周波数 38kHz
+pub const NEC_CARRIER: IrCarrier = IrCarrier { freq_hz: 38000 };
+
+/// SONY SIRCフォーマットのキャリア周波数 36kHz
+pub const SIRC_CARRIER: IrCarrier = IrCarrier { freq_hz: 36000 };
+
+/// 家製協(AEHA)フォーマットのキャリア周波数 38kHz
+pub const AEHA_CARRIER: IrCarrier = IrCarrier { freq_hz: 38000 };
+
+impl Default for IrCarrier {
+    fn default() -> Self {
+        IrCarrier {
+            freq_hz: IR_CARRIER_FREQ as u32,
+        }
+    }
+}
+
+impl IrCarrier {
+    /// マイクロ秒をキャリア周波数のカウント数にする
+    pub fn micros_to_count(&self, micros: Microseconds) -> u32 {
+        // 1 秒が freq_hz カウントなので
+        // 1マイクロ秒 が freq_hz/(1000*1000) カウント
+        let y = micros.0 as u64 * self.freq_hz as u64 / 1_000_000u64;
+        y.min(u32::MAX as u64) as u32
+    }
+
+    /// キャリア周波数のカウント数をマイクロ秒にする
+    pub fn count_to_micros(&self, count: u32) -> Microseconds {
+        // 1 カウント が 1/freq_hz 秒 なので
+        // 1000倍してミリ秒に
+        // さらに1000倍してマイクロ秒にする
+        let y = 1_000_000u64 * count as u64 / self.freq_hz.max(1) as u64;
+        Microseconds(y.min(u32::MAX as u64) as u32)
+    }
+}
+
+impl IrCarrierCounter {
+    /// マイクロ秒型から変換する
+    /// キャリア周波数の指定がなければ IR_CARRIER_FREQ を使う
+    pub fn from_micros(x: Microseconds, carrier: Option<IrCarrier>) -> Self {
+        let count = carrier.unwrap_or_default().micros_to_count(x);
+        Self(count.min(u16::MAX as u32) as u16)
+    }
+
+    /// マイクロ秒型へ変換する
+    /// キャリア周波数の指定がなければ IR_CARRIER_FREQ を使う
+    pub fn to_micros(self, carrier: Option<IrCarrier>) -> Microseconds {
+        carrier.unwrap_or_default().count_to_micros(self.0 as u32)
+    }
+}
+
 impl convert::From<IrCarrierCounter> for Microseconds {
     /// 赤外線リモコン信号のキャリア周波数カウンタ型からマイクロ秒型へ変換する
     fn from(x: IrCarrierCounter) -> Self {
-        // 1 カウント が 1/IR_CARRIER_FREQ 秒 なので
-        // 1000倍してミリ秒に
-        // さらに1000倍してマイクロ秒にする
-        let y = 1_000_000u64 * x.0 as u64 / IR_CARRIER_FREQ as u64;
-        Self(y as u32)
+        x.to_micros(None)
     }
 }
 
 impl convert::From<Microseconds> for IrCarrierCounter {
     /// マイクロ秒型から赤外線リモコン信号のキャリア周波数カウンタ型へ変換する
     fn from(x: Microseconds) -> Self {
-        // 1 秒が IR_CARRIER_FREQ カウントなので
-        // 1マイクロ秒 が IrCarrirFreq/(1000*1000) カウント
-        let y = x.0 as u64 * IR_CARRIER_FREQ as u64 / 1_000_000u64;
-        Self(y as u16)
+        IrCarrierCounter::from_micros(x, None)
     }
 }
 
+#[test]
+fn test_ir_carrier() {
+    assert_eq!(NEC_CARRIER.micros_to_count(Microseconds(9000)), 342);
+    assert_eq!(SIRC_CARRIER.micros_to_count(Microseconds(2400)), 86);
+    assert_eq!(
+        IrCarrier { freq_hz: 56000 }.micros_to_count(Microseconds(1000)),
+        56
+    );
+    assert_eq!(SIRC_CARRIER.count_to_micros(36), Microseconds(1000));
+    assert_eq!(AEHA_CARRIER.count_to_micros(0x0156), Microseconds(9000));
+    // 指定がなければ 38kHz
+    assert_eq!(
+        IrCarrierCounter::from_micros(Microseconds(9000), None),
+        IrCarrierCounter::from(Microseconds(9000))
+    );
+    assert_eq!(
+        IrCarrierCounter::from_micros(Microseconds(2400), Some(SIRC_CARRIER)),
+        IrCarrierCounter(86)
+    );
+    assert_eq!(
+        IrCarrierCounter(86).to_micros(Some(SIRC_CARRIER)),
+        Microseconds(2388)
+    );
+    // u16 の範囲を超えたら飽和する
+    assert_eq!(
+        IrCarrierCounter::from_micros(Microseconds(10_000_000), Some(NEC_CARRIER)),
+        IrCarrierCounter(u16::MAX)
+    );
+}
+
 #[test]
 fn test_microseconds_to_ircarriercounter() {
     assert_eq!(
@@ -281,13 +769,39 @@ pub type MarkAndSpaceIrCarrier = MarkAndSpace<IrCarrierCounter>;
 
 impl MarkAndSpaceIrCarrier {
     /// 16ビットリトルエンディアンで出力する
-    pub fn to_string_littel_endian_u16(&self) -> String {
+    pub fn to_string_little_endian_u16(self) -> String {
         format!(
             "{}{}",
-            IrCarrierCounter::from(self.mark).to_string_littel_endian_u16(),
-            IrCarrierCounter::from(self.space).to_string_littel_endian_u16()
+            self.mark.to_string_little_endian_u16(),
+            self.space.to_string_little_endian_u16()
         )
     }
+
+    /// 16ビットリトルエンディアンで出力する(旧名)
+    #[deprecated(since = "0.1.0", note = "use to_string_little_endian_u16")]
+    pub fn to_string_littel_endian_u16(self) -> String {
+        self.to_string_little_endian_u16()
+    }
+}
+
+impl MarkAndSpaceIrCarrier {
+    /// マークアンドスペース(マイクロ秒ベース)から変換する
+    /// キャリア周波数の指定がなければ IR_CARRIER_FREQ を使う
+    pub fn from_micros(micros: MarkAndSpaceMicros, carrier: Option<IrCarrier>) -> Self {
+        Self {
+            mark: IrCarrierCounter::from_micros(micros.mark, carrier),
+            space: IrCarrierCounter::from_micros(micros.space, carrier),
+        }
+    }
+
+    /// マークアンドスペース(マイクロ秒ベース)へ変換する
+    /// キャリア周波数の指定がなければ IR_CARRIER_FREQ を使う
+    pub fn to_micros(self, carrier: Option<IrCarrier>) -> MarkAndSpaceMicros {
+        MarkAndSpaceMicros {
+            mark: self.mark.to_micros(carrier),
+            space: self.space.to_micros(carrier),
+        }
+    }
 }
 
 impl convert::From<MarkAndSpaceIrCarrier> for MarkAndSpaceMicros {
@@ -317,6 +831,375 @@ impl convert::From<MarkAndSpaceMicros> for MarkAndSpaceIrCarrier {
 /// 第1,2,3...フレームを区切る時間(8ms = 8000us)
 pub const THRESHOLD_FRAME_GAP: Microseconds = Microseconds(8000);
 
+/// マークの最大時間(100ms = 100000us)
+pub const MAX_MARK_WIDTH: Microseconds = Microseconds(100_000);
+
+/// 1フレームの最大時間
+pub const MAX_FRAME_DURATION: Microseconds = Microseconds(1_000_000);
+
+/// 入力できるマークアンドスペースの最大数
+pub const MAX_SIGNAL_PAIRS: usize = 10_000;
+
+/// 入力信号の数が上限以下であることを確かめる
+pub fn check_signal_pairs(number_of_signals: usize) -> Result<(), SignalValidationError> {
+    if MAX_SIGNAL_PAIRS < number_of_signals {
+        return Err(SignalValidationError::TooManySignals {
+            limit: MAX_SIGNAL_PAIRS,
+            actual: number_of_signals,
+        });
+    }
+    Ok(())
+}
+
+#[test]
+fn test_check_signal_pairs() {
+    assert_eq!(check_signal_pairs(MAX_SIGNAL_PAIRS), Ok(()));
+    assert_eq!(
+        check_signal_pairs(MAX_SIGNAL_PAIRS + 1),
+        Err(SignalValidationError::TooManySignals {
+            limit: MAX_SIGNAL_PAIRS,
+            actual: MAX_SIGNAL_PAIRS + 1
+        })
+    );
+}
+
+/// 入力信号を確かめる
+/// 最後のスペースは続く信号が無いので0でもよい
+pub fn validate_signal(signals: &[MarkAndSpaceMicros]) -> Result<(), SignalValidationError> {
+    if signals.len() < 2 {
+        return Err(SignalValidationError::TooFewSignals(signals.len()));
+    }
+    let last = signals.len() - 1;
+    for (index, &ms) in signals.iter().enumerate() {
+        if ms.mark == Microseconds(0) || (ms.space == Microseconds(0) && index != last) {
+            return Err(SignalValidationError::ZeroWidthPulse(index, ms));
+        }
+        if MAX_MARK_WIDTH < ms.mark {
+            return Err(SignalValidationError::TooLongMark(index, ms));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_validate_signal() {
+    let nec = protocol_nec::encode_nec([0x40, 0xbf, 0x12, 0xed]);
+    assert_eq!(validate_signal(&nec), Ok(()));
+    // 要素が足りない
+    assert_eq!(
+        validate_signal(&nec[..1]),
+        Err(SignalValidationError::TooFewSignals(1))
+    );
+    assert_eq!(
+        validate_signal(&[]),
+        Err(SignalValidationError::TooFewSignals(0))
+    );
+    // 幅0のマーク
+    let mut signals = nec.clone();
+    signals[3].mark = Microseconds(0);
+    assert_eq!(
+        validate_signal(&signals),
+        Err(SignalValidationError::ZeroWidthPulse(3, signals[3]))
+    );
+    // 幅0のスペース
+    let mut signals = nec.clone();
+    signals[5].space = Microseconds(0);
+    assert_eq!(
+        validate_signal(&signals),
+        Err(SignalValidationError::ZeroWidthPulse(5, signals[5]))
+    );
+    // 最後のスペースは0でもよい
+    let mut signals = nec.clone();
+    signals.last_mut().unwrap().space = Microseconds(0);
+    assert_eq!(validate_signal(&signals), Ok(()));
+    // 長すぎるマーク
+    let mut signals = nec;
+    signals[0].mark = Microseconds(100_001);
+    assert_eq!(
+        validate_signal(&signals),
+        Err(SignalValidationError::TooLongMark(0, signals[0]))
+    );
+}
+
+/// 全てのマークとスペースの合計時間(桁あふれの場合は最大値)
+pub fn total_duration_micros(signals: &[MarkAndSpaceMicros]) -> Microseconds {
+    signals.iter().fold(Microseconds(0), |acc, ms| {
+        acc.saturating_add(ms.mark).saturating_add(ms.space)
+    })
+}
+
+/// フレームの数
+pub fn count_frames(signals: &[MarkAndSpaceMicros]) -> usize {
+    signals
+        .split_inclusive(|ms| THRESHOLD_FRAME_GAP <= ms.space)
+        .count()
+}
+
+/// 各フレームの時間
+pub fn frame_durations(signals: &[MarkAndSpaceMicros]) -> Vec<Microseconds> {
+    signals
+        .split_inclusive(|ms| THRESHOLD_FRAME_GAP <= ms.space)
+        .map(total_duration_micros)
+        .collect()
+}
+
+#[test]
+fn test_total_duration_and_frames() {
+    // daikin_hvac test1
+    let rxdata= "[417,448,418,450,417,450,417,449,418,448,417,25329,3450,1747,418,1315,419,446,419,449,417,450,417,1315,418,449,417,449,417,449,417,450,417,1314,418,450,417,1315,417,1315,418,448,418,1315,418,1315,417,1315,418,1315,417,1315,418,450,417,448,419,1312,419,449,417,449,417,451,416,449,419,448,417,449,417,450,417,448,419,448,417,449,417,1316,417,450,416,1314,419,448,418,449,417,449,418,1314,418,1314,419,449,417,450,417,448,419,447,418,450,417,448,419,448,417,449,417,449,418,448,418,449,418,449,417,448,419,448,417,449,418,449,417,1315,418,1314,418,1315,418,448,419,1313,419,448,419,1313,419,1313,420,34665,3450,1748,418,1314,419,447,418,450,416,450,417,1316,416,450,418,448,417,449,418,449,417,1315,418,449,418,1315,417,1315,417,451,416,1316,417,1314,418,1314,418,1316,416,1316,417,450,417,450,417,1313,418,451,416,449,417,449,418,449,416,450,417,449,417,450,416,449,417,450,416,451,416,449,419,1314,418,448,417,449,417,451,416,449,418,1317,416,450,415,450,417,449,418,448,417,450,416,450,417,451,416,448,417,450,417,449,417,450,417,450,417,449,418,448,417,453,414,449,417,449,417,450,416,450,416,1316,418,449,417,1315,417,449,418,1315,418,449,417,34670,3449,1750,416,1316,417,451,416,449,416,450,417,1315,418,450,416,450,415,451,417,449,417,1316,416,450,418,1315,416,1316,417,449,418,1315,418,1315,417,1316,417,1315,417,1315,418,450,416,450,417,1316,416,454,412,450,416,451,416,450,416,450,416,450,416,451,416,451,417,448,417,450,416,449,418,450,417,448,417,450,417,450,416,450,416,450,417,450,416,1317,416,1316,416,450,416,1317,417,1315,417,1316,417,449,418,448,417,452,414,451,416,1316,416,1316,417,450,416,1316,417,449,418,450,417,449,416,450,417,450,417,450,416,450,416,451,415,450,419,448,416,1316,417,1316,417,1315,418,1317,416,450,417,449,417,1315,417,450,416,450,420,448,415,450,416,450,417,450,416,450,416,450,417,449,418,1315,417,451,416,449,417,1316,416,451,416,450,416,451,415,1316,417,451,416,1316,416,450,418,450,415,450,416,451,416,451,416,449,417,450,416,450,417,450,416,450,416,450,416,1316,417,1317,417,447,418,450,416,451,416,451,416,449,416,450,417,450,417,449,416,450,416,452,414,451,416,450,416,451,415,451,416,451,415,450,416,451,416,1317,416,451,415,451,416,451,415,452,414,451,415,1317,417,1316,416,451,416,451,416,450,415,453,414,451,415,451,416,451,415,452,414,452,415,450,417,451,416,451,414,451,416,451,416,451,414,451,416,451,415,451,416,1317,416,451,415,1317,416,1316,417,1316,416,451,416]";
+    let signals = crate::parsing::parse_infrared_code_text(rxdata).unwrap();
+    // 先頭のプリアンブルと3フレーム
+    assert_eq!(count_frames(&signals), 4);
+    let Microseconds(total) = total_duration_micros(&signals);
+    assert!(total.abs_diff(458_000) <= 458_000 / 100);
+    let durations = frame_durations(&signals);
+    assert_eq!(
+        durations,
+        vec![
+            Microseconds(30078),
+            Microseconds(113917),
+            Microseconds(109591),
+            Microseconds(204357)
+        ]
+    );
+    assert_eq!(
+        durations
+            .into_iter()
+            .fold(Microseconds(0), |acc, t| acc + t),
+        Microseconds(total)
+    );
+    // 桁あふれしない
+    let long = vec![
+        MarkAndSpace {
+            mark: Microseconds(u32::MAX),
+            space: Microseconds(u32::MAX),
+        };
+        2
+    ];
+    assert_eq!(total_duration_micros(&long), Microseconds(u32::MAX));
+    assert!(InfraredRemoteFrame::try_from_iter(long).is_err());
+    //
+    assert_eq!(count_frames(&[]), 0);
+    assert_eq!(total_duration_micros(&[]), Microseconds(0));
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// 2つの信号の比較結果
+pub struct SignalComparison {
+    #[serde(rename = "match")]
+    pub matched: bool,
+    /// 許容範囲内のマークアンドスペースの割合(0.0 - 1.0)
+    pub similarity: f32,
+    /// 最初に許容範囲を超えた位置
+    pub mismatched_at: Option<usize>,
+}
+
+/// 2つの信号をずれ時間の許容範囲で比較する
+/// 長さが異なる場合は一致しない
+pub fn compare_signals(
+    a: &[MarkAndSpaceMicros],
+    b: &[MarkAndSpaceMicros],
+    tolerance: Microseconds,
+) -> SignalComparison {
+    let within = |x: Microseconds, y: Microseconds| x.0.abs_diff(y.0) <= tolerance.0;
+    let results = a
+        .iter()
+        .zip(b.iter())
+        .map(|(x, y)| within(x.mark, y.mark) && within(x.space, y.space))
+        .collect::<Vec<bool>>();
+    let longest = a.len().max(b.len());
+    let similarity = if longest == 0 {
+        1.0
+    } else {
+        results.iter().filter(|&&ok| ok).count() as f32 / longest as f32
+    };
+    let mismatched_at = results
+        .iter()
+        .position(|&ok| !ok)
+        .or((a.len() != b.len()).then_some(results.len()));
+    SignalComparison {
+        matched: mismatched_at.is_none(),
+        similarity,
+        mismatched_at,
+    }
+}
+
+#[test]
+fn test_compare_signals() {
+    let nec = protocol_nec::encode_nec([0x40, 0xbf, 0x12, 0xed]);
+    let result = compare_signals(&nec, &nec.clone(), Microseconds(100));
+    assert_eq!(
+        result,
+        SignalComparison {
+            matched: true,
+            similarity: 1.0,
+            mismatched_at: None
+        }
+    );
+    // 5番目のパルスが壊れている
+    let mut corrupted = nec.clone();
+    corrupted[5].space = corrupted[5].space + Microseconds(500);
+    let result = compare_signals(&nec, &corrupted, Microseconds(100));
+    assert!(!result.matched);
+    assert_eq!(result.mismatched_at, Some(5));
+    assert_eq!(result.similarity, (nec.len() - 1) as f32 / nec.len() as f32);
+    // 許容範囲内
+    assert!(compare_signals(&nec, &corrupted, Microseconds(500)).matched);
+    // 長さが違う
+    let result = compare_signals(&nec, &nec[..10], Microseconds(100));
+    assert!(!result.matched);
+    assert_eq!(result.mismatched_at, Some(10));
+    assert_eq!(
+        serde_json::to_string(&result).unwrap(),
+        format!(
+            r#"{{"match":false,"similarity":{},"mismatched_at":10}}"#,
+            10.0 / nec.len() as f32
+        )
+    );
+}
+
+/// ランレングス圧縮で同じとみなすずれ時間
+pub const RLE_TOLERANCE: Microseconds = Microseconds(20);
+
+/// 連続する同じ(許容範囲内の)マークアンドスペースを (値, 個数) にまとめる
+/// 値は連続の先頭のもの
+pub fn rle_encode(signals: &[MarkAndSpaceMicros]) -> Vec<(MarkAndSpaceMicros, u32)> {
+    let within = |x: Microseconds, y: Microseconds| x.0.abs_diff(y.0) <= RLE_TOLERANCE.0;
+    let mut rle: Vec<(MarkAndSpaceMicros, u32)> = Vec::new();
+    for &ms in signals {
+        match rle.last_mut() {
+            Some((value, count))
+                if within(value.mark, ms.mark) && within(value.space, ms.space) =>
+            {
+                *count += 1
+            }
+            _ => rle.push((ms, 1)),
+        }
+    }
+    rle
+}
+
+/// rle_encode の逆変換
+// iter::repeat_n は Rust 1.82 からなので使わない
+#[allow(clippy::manual_repeat_n)]
+pub fn rle_decode(rle: &[(MarkAndSpaceMicros, u32)]) -> Vec<MarkAndSpaceMicros> {
+    rle.iter()
+        .flat_map(|&(value, count)| iter::repeat(value).take(count as usize))
+        .collect()
+}
+
+#[test]
+fn test_rle() {
+    // 0 のビット(440us / 440us)が続くAEHAフレームに±10usの揺らぎを加える
+    let signals = protocol_aeha::encode_frame(&[0x02, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00])
+        .into_iter()
+        .enumerate()
+        .map(|(i, ms)| {
+            let jitter = Microseconds(10);
+            if i % 2 == 0 {
+                MarkAndSpace {
+                    mark: ms.mark + jitter,
+                    space: ms.space,
+                }
+            } else {
+                MarkAndSpace {
+                    mark: ms.mark,
+                    space: ms.space.saturating_sub(jitter),
+                }
+            }
+        })
+        .collect::<Vec<MarkAndSpaceMicros>>();
+    let rle = rle_encode(&signals);
+    assert!(rle.len() * 2 <= signals.len());
+    assert_eq!(
+        rle.iter().map(|&(_, count)| count as usize).sum::<usize>(),
+        signals.len()
+    );
+    let decoded = rle_decode(&rle);
+    assert!(compare_signals(&signals, &decoded, RLE_TOLERANCE).matched);
+    assert_eq!(
+        decord_receiving_data(&decoded, None).unwrap(),
+        decord_receiving_data(&signals, None).unwrap()
+    );
+    // 空
+    assert_eq!(rle_encode(&[]), vec![]);
+    assert_eq!(rle_decode(&[]), vec![]);
+}
+
+// FNV-1a 128ビット
+fn fnv1a_128(bytes: impl Iterator<Item = u8>) -> u128 {
+    const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+    bytes.fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ byte as u128).wrapping_mul(PRIME)
+    })
+}
+
+/// 未知の機器の信号を分類するための指紋
+/// プロトコル, ビット数, 復調済みのビット列から求めるので, 揺らぎのある同じ信号は同じ指紋になる
+/// 復調できない信号はプロトコルとマークアンドスペースの数から求める
+pub fn signal_fingerprint(signals: &[MarkAndSpaceMicros]) -> [u8; 16] {
+    let protocol = detect_protocol(signals)
+        .map(|detected| detected.protocol)
+        .unwrap_or(InfraredRemoteProtocol::Unknown);
+    let normalized = normalize_to_protocol(signals, protocol);
+    let bits = decord_receiving_data(&normalized, None)
+        .map(|frames| {
+            frames
+                .iter()
+                .filter_map(|frame| frame.bits())
+                .flatten()
+                .copied()
+                .collect::<Vec<Bit>>()
+        })
+        .unwrap_or_default();
+    let bit_count = if bits.is_empty() {
+        signals.len()
+    } else {
+        bits.len()
+    } as u32;
+    let canonical = serde_json::to_string(&protocol)
+        .unwrap_or_default()
+        .into_bytes()
+        .into_iter()
+        .chain(bit_count.to_le_bytes())
+        .chain(bits.iter().map(|&bit| (bit == Bit::Hi) as u8));
+    fnv1a_128(canonical).to_be_bytes()
+}
+
+#[test]
+fn test_signal_fingerprint() {
+    // 空文字列のFNV-1a 128ビットはオフセット基底
+    assert_eq!(fnv1a_128(iter::empty()), 0x6c62272e07bb014262b821756295c58d);
+    let power = protocol_nec::encode_nec([0x40, 0xbf, 0x12, 0xed]);
+    // ±100usの揺らぎ
+    let jittered = power
+        .iter()
+        .enumerate()
+        .map(|(i, ms)| {
+            let jitter = Microseconds(100);
+            if i % 2 == 0 {
+                MarkAndSpace {
+                    mark: ms.mark + jitter,
+                    space: ms.space.saturating_sub(jitter),
+                }
+            } else {
+                MarkAndSpace {
+                    mark: ms.mark.saturating_sub(jitter),
+                    space: ms.space + jitter,
+                }
+            }
+        })
+        .collect::<Vec<MarkAndSpaceMicros>>();
+    assert_eq!(signal_fingerprint(&power), signal_fingerprint(&jittered));
+    // 違うボタン
+    let other = protocol_nec::encode_nec([0x40, 0xbf, 0x1a, 0xe5]);
+    assert_ne!(signal_fingerprint(&power), signal_fingerprint(&other));
+    // 同じビット列でもプロトコルが違う
+    let aeha = protocol_aeha::encode_frame(&[0x40, 0xbf, 0x12, 0xed]);
+    assert_ne!(signal_fingerprint(&power), signal_fingerprint(&aeha));
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 /// 赤外線リモコン信号フレーム
 pub struct InfraredRemoteFrame(pub Vec<MarkAndSpaceMicros>);
@@ -330,3 +1213,103 @@ impl iter::FromIterator<MarkAndSpaceMicros> for InfraredRemoteFrame {
         c
     }
 }
+
+impl InfraredRemoteFrame {
+    /// フレーム全体の時間
+    pub fn duration(&self) -> Microseconds {
+        total_duration_micros(&self.0)
+    }
+
+    /// データビット数の推定値(リーダーパルスとストップビットを除いた数)
+    pub fn bit_count_estimate(&self) -> usize {
+        self.0.len().saturating_sub(2)
+    }
+
+    /// 信号を確かめてフレームにする
+    pub fn try_from_iter<I>(iter: I) -> Result<Self, SignalValidationError>
+    where
+        I: IntoIterator<Item = MarkAndSpaceMicros>,
+    {
+        let frame = iter.into_iter().collect::<InfraredRemoteFrame>();
+        validate_signal(&frame.0)?;
+        let duration = frame.duration();
+        if MAX_FRAME_DURATION < duration {
+            return Err(SignalValidationError::TooLongDuration(duration));
+        }
+        Ok(frame)
+    }
+
+    /// 末尾に追加する
+    pub fn append(&mut self, other: &MarkAndSpaceMicros) {
+        self.0.push(*other);
+    }
+
+    /// 2つのフレームを間隔をあけてつなげる
+    /// aの最後のスペースを間隔にする
+    pub fn merge(a: &Self, gap: Microseconds, b: &Self) -> Self {
+        let mut merged = a.clone();
+        if let Some(last) = merged.0.last_mut() {
+            last.space = gap;
+        }
+        b.0.iter().for_each(|ms| merged.append(ms));
+        merged
+    }
+}
+
+#[test]
+fn test_infrared_remote_frame_duration() {
+    let signals = protocol_nec::encode_nec([0x40, 0xbf, 0x12, 0xed]);
+    let frame = signals.iter().copied().collect::<InfraredRemoteFrame>();
+    let expected = signals
+        .iter()
+        .fold(Microseconds(0), |acc, ms| acc + ms.mark + ms.space);
+    assert_eq!(frame.duration(), expected);
+    assert_eq!(frame.bit_count_estimate(), 32);
+    //
+    let empty = InfraredRemoteFrame(vec![]);
+    assert_eq!(empty.duration(), Microseconds(0));
+    assert_eq!(empty.bit_count_estimate(), 0);
+}
+
+#[test]
+fn test_infrared_remote_frame_merge() {
+    let power =
+        InfraredRemoteFrame::try_from_iter(protocol_nec::encode_nec([0x40, 0xbf, 0x12, 0xed]))
+            .unwrap();
+    let mute =
+        InfraredRemoteFrame::try_from_iter(protocol_nec::encode_nec([0x40, 0xbf, 0x10, 0xef]))
+            .unwrap();
+    let merged = InfraredRemoteFrame::merge(&power, Microseconds(50_000), &mute);
+    assert_eq!(merged.0.len(), power.0.len() + mute.0.len());
+    assert_eq!(merged.0[power.0.len() - 1].space, Microseconds(50_000));
+    // 復号すると2つのフレームに分かれる
+    let frames = decord_receiving_data(&merged.0, None).unwrap();
+    assert_eq!(
+        frames,
+        [
+            decord_receiving_data(&power.0, None).unwrap(),
+            decord_receiving_data(&mute.0, None).unwrap()
+        ]
+        .concat()
+    );
+    //
+    let mut frame = InfraredRemoteFrame(vec![]);
+    frame.append(&protocol_nec::LEADER);
+    assert_eq!(
+        InfraredRemoteFrame::try_from_iter(frame.0.clone()),
+        Err(SignalValidationError::TooFewSignals(1))
+    );
+    frame.append(&protocol_nec::STOP_BIT);
+    assert_eq!(
+        InfraredRemoteFrame::try_from_iter(frame.0.clone()),
+        Ok(frame)
+    );
+    // 長すぎる
+    let long = vec![MarkAndSpaceMicros::from((Microseconds(500), Microseconds(600_000))); 2];
+    assert_eq!(
+        InfraredRemoteFrame::try_from_iter(long),
+        Err(SignalValidationError::TooLongDuration(Microseconds(
+            1_201_000
+        )))
+    );
+}
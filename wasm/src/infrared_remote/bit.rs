@@ -4,6 +4,7 @@
 // See LICENSE file in the project root for full license information.
 use serde::de::Unexpected;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
 
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -116,9 +117,55 @@ macro_rules! vec_bits {
 }
 pub(crate) use vec_bits;
 
-//
-pub fn bits_from_string(str: &str) -> Option<Vec<Bit>> {
-    str.chars().map(|ch| ch.try_into().ok()).collect()
+/// 2進数文字列の解析エラー
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum BitParseError {
+    #[error("invalid character '{0}' at position {1}.")]
+    InvalidCharacter(char, usize),
+}
+
+/// '0', '1' からなる文字列をビット列にする('_'は区切りとして読み飛ばす)
+pub fn bits_try_from_string(str: &str) -> Result<Vec<Bit>, BitParseError> {
+    str.chars()
+        .enumerate()
+        .filter(|(_, ch)| *ch != '_')
+        .map(|(pos, ch)| Bit::try_from(ch).map_err(|_| BitParseError::InvalidCharacter(ch, pos)))
+        .collect()
+}
+
+/// vec_bits!のパニックしない版
+macro_rules! try_vec_bits {
+    ( $($x:expr ),*) => {{
+        (|| -> Result<Vec<Bit>, BitParseError> {
+            let mut temp_vec:Vec<Bit> = Vec::new();
+            $(
+                temp_vec.extend(bits_try_from_string($x)?);
+            )*
+            Ok(temp_vec)
+        })()
+    }};
+}
+pub(crate) use try_vec_bits;
+
+/// ビット列を4ビット毎(LSB first)に16進数文字列(大文字)にする
+/// 4ビットに満たない末尾は0で埋める
+pub fn bits_to_hex_string(bits: &[Bit]) -> String {
+    bits.chunks(4)
+        .map(|nibble| format!("{:X}", folding_to_lsb_first_u16(nibble)))
+        .collect()
+}
+
+/// 16進数文字列の各文字を4ビット(LSB first)のビット列にする
+pub fn bits_from_hex_string(hex: &str) -> Result<Vec<Bit>, BitParseError> {
+    hex.chars()
+        .enumerate()
+        .map(|(pos, ch)| {
+            ch.to_digit(16)
+                .map(|n| (0..4).map(move |i| Bit::from(n >> i & 1 != 0)))
+                .ok_or(BitParseError::InvalidCharacter(ch, pos))
+        })
+        .collect::<Result<Vec<_>, BitParseError>>()
+        .map(|nibbles| nibbles.into_iter().flatten().collect())
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Hash, Eq)]
@@ -128,6 +175,23 @@ impl MsbFirst {
     pub const fn new(value: u8) -> MsbFirst {
         MsbFirst(value)
     }
+
+    /// 最上位ビットを左端にした8文字の2進数文字列にする
+    pub fn to_binary_string(self) -> String {
+        format!("{:08b}", self.0)
+    }
+}
+
+impl std::fmt::Display for MsbFirst {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "0x{:02X}", self.0)
+    }
+}
+
+impl std::fmt::Binary for MsbFirst {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.pad(&self.to_binary_string())
+    }
 }
 
 impl From<MsbFirst> for u8 {
@@ -142,14 +206,34 @@ impl From<u8> for MsbFirst {
     }
 }
 
+impl TryFrom<&[Bit]> for MsbFirst {
+    type Error = &'static str;
+
+    fn try_from(bs: &[Bit]) -> Result<Self, Self::Error> {
+        if bs.len() > 8 {
+            return Err("Must be 8 bits or less");
+        }
+        Ok(MsbFirst(fold_msb_first(bs)))
+    }
+}
+
+impl From<[Bit; 8]> for MsbFirst {
+    fn from(bs: [Bit; 8]) -> Self {
+        bs[..].try_into().expect("8 bits")
+    }
+}
+
 //
 pub fn folding_to_msb_first(bs: &[Bit]) -> MsbFirst {
+    MsbFirst(fold_msb_first(bs))
+}
+
+fn fold_msb_first(bs: &[Bit]) -> u8 {
     // 右端ビットが最下位になるように畳み込む
-    let value = bs.iter().fold(0, |accumulator, &bit| match bit {
+    bs.iter().fold(0, |accumulator, &bit| match bit {
         Bit::Lo => accumulator << 1 | 0,
         Bit::Hi => accumulator << 1 | 1,
-    });
-    MsbFirst(value)
+    })
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Hash, Eq)]
@@ -159,6 +243,23 @@ impl LsbFirst {
     pub const fn new(value: u8) -> LsbFirst {
         LsbFirst(value)
     }
+
+    /// 最下位ビットを左端にした8文字の2進数文字列にする
+    pub fn to_binary_string(self) -> String {
+        format!("{:08b}", self.0.reverse_bits())
+    }
+}
+
+impl std::fmt::Display for LsbFirst {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "0x{:02X}", self.0)
+    }
+}
+
+impl std::fmt::Binary for LsbFirst {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.pad(&self.to_binary_string())
+    }
 }
 
 impl From<LsbFirst> for u8 {
@@ -173,22 +274,137 @@ impl From<u8> for LsbFirst {
     }
 }
 
+impl From<MsbFirst> for LsbFirst {
+    fn from(value: MsbFirst) -> Self {
+        Self::new(reverse_bits(value.0))
+    }
+}
+
+impl From<LsbFirst> for MsbFirst {
+    fn from(value: LsbFirst) -> Self {
+        Self::new(reverse_bits(value.0))
+    }
+}
+
+/// ビットの並びを反転する
+pub fn reverse_bits(b: u8) -> u8 {
+    b.reverse_bits()
+}
+
+impl TryFrom<&[Bit]> for LsbFirst {
+    type Error = &'static str;
+
+    fn try_from(bs: &[Bit]) -> Result<Self, Self::Error> {
+        if bs.len() > 8 {
+            return Err("Must be 8 bits or less");
+        }
+        Ok(LsbFirst(fold_lsb_first(bs)))
+    }
+}
+
+impl From<[Bit; 8]> for LsbFirst {
+    fn from(bs: [Bit; 8]) -> Self {
+        bs[..].try_into().expect("8 bits")
+    }
+}
+
 //
 pub fn folding_to_lsb_first(bs: &[Bit]) -> LsbFirst {
+    LsbFirst(fold_lsb_first(bs))
+}
+
+fn fold_lsb_first(bs: &[Bit]) -> u8 {
     // 左端ビットが最下位になるように畳み込む
-    let value = bs.iter().rfold(0, |accumulator, &bit| match bit {
+    bs.iter().rfold(0, |accumulator, &bit| match bit {
         Bit::Lo => accumulator << 1 | 0,
         Bit::Hi => accumulator << 1 | 1,
-    });
-    LsbFirst(value)
+    })
+}
+
+/// 左端ビットが最下位になるように16ビットまで畳み込む
+pub fn folding_to_lsb_first_u16(bs: &[Bit]) -> u16 {
+    folding_to_lsb_first_u32(&bs[..bs.len().min(16)]) as u16
+}
+
+/// 左端ビットが最下位になるように32ビットまで畳み込む
+pub fn folding_to_lsb_first_u32(bs: &[Bit]) -> u32 {
+    bs.iter().take(32).rfold(0, |accumulator, &bit| {
+        accumulator << 1 | u32::from(bit == Bit::Hi)
+    })
+}
+
+/// 右端ビットが最下位になるように16ビットまで畳み込む
+pub fn folding_to_msb_first_u16(bs: &[Bit]) -> u16 {
+    folding_to_msb_first_u32(&bs[..bs.len().min(16)]) as u16
+}
+
+/// 右端ビットが最下位になるように32ビットまで畳み込む
+pub fn folding_to_msb_first_u32(bs: &[Bit]) -> u32 {
+    bs.iter().take(32).fold(0, |accumulator, &bit| {
+        accumulator << 1 | u32::from(bit == Bit::Hi)
+    })
+}
+
+/// 左端ビットが最下位になるように8ビットに展開する
+pub fn unfolding_from_lsb_first(value: LsbFirst) -> [Bit; 8] {
+    let mut bits = [Bit::Lo; 8];
+    for (n, bit) in bits.iter_mut().enumerate() {
+        *bit = Bit::from(value.0 >> n & 1 != 0);
+    }
+    bits
+}
+
+/// ビット毎に反転する
+pub fn invert_bits(bits: &[Bit]) -> Vec<Bit> {
+    bits.iter()
+        .map(|&bit| match bit {
+            Bit::Lo => Bit::Hi,
+            Bit::Hi => Bit::Lo,
+        })
+        .collect()
+}
+
+// 同じ長さのビット型の配列同士をビット毎に演算する
+fn zip_bits_with(
+    a: &[Bit],
+    b: &[Bit],
+    op: fn(bool, bool) -> bool,
+) -> Result<Vec<Bit>, &'static str> {
+    if a.len() != b.len() {
+        return Err("Must be the same length");
+    }
+    Ok(a.iter()
+        .zip(b.iter())
+        .map(|(&x, &y)| Bit::from(op(x.into(), y.into())))
+        .collect())
+}
+
+/// ビット毎の排他的論理和
+pub fn xor_bits(a: &[Bit], b: &[Bit]) -> Result<Vec<Bit>, &'static str> {
+    zip_bits_with(a, b, |x, y| x ^ y)
+}
+
+/// ビット毎の論理積
+pub fn and_bits(a: &[Bit], b: &[Bit]) -> Result<Vec<Bit>, &'static str> {
+    zip_bits_with(a, b, |x, y| x & y)
+}
+
+/// ビット毎の論理和
+pub fn or_bits(a: &[Bit], b: &[Bit]) -> Result<Vec<Bit>, &'static str> {
+    zip_bits_with(a, b, |x, y| x | y)
 }
 
 /// ビット型の配列を8ビットごとに空白を入れて表示する。
 pub fn show_bit_pattern(input: &[Bit]) -> String {
+    show_bit_pattern_grouped(input, 8, ' ')
+}
+
+/// ビット型の配列をgroupビットごとに区切り文字を入れて表示する。
+pub fn show_bit_pattern_grouped(input: &[Bit], group: usize, sep: char) -> String {
     let mut s = String::new();
     for (index, item) in input.iter().enumerate() {
-        if index >= 8 && index & 7 == 0 {
-            s.push(' ');
+        if group > 0 && index >= group && index % group == 0 {
+            s.push(sep);
         }
         s = format!("{}{}", s, item);
     }
@@ -221,22 +437,22 @@ mod bit_type_tests {
 
     #[test]
     fn test4() {
-        let result = bits_from_string("01000000");
-        let expected = Some(vec_bits!("01000000"));
+        let result = bits_try_from_string("01000000");
+        let expected = Ok(vec_bits!("01000000"));
         assert_eq!(result, expected)
     }
 
     #[test]
     fn test5() {
-        let result = bits_from_string("01000000");
-        let expected = Some(vec_bits!("01000000"));
+        let result = bits_try_from_string("01000000");
+        let expected = Ok(vec_bits!("01000000"));
         assert_eq!(result, expected)
     }
 
     #[test]
     fn test6() {
-        let result = bits_from_string("00000100");
-        let expected = Some(vec_bits!("00000100"));
+        let result = bits_try_from_string("00000100");
+        let expected = Ok(vec_bits!("00000100"));
         assert_eq!(result, expected)
     }
 
@@ -345,4 +561,186 @@ mod bit_type_tests {
         let result: Bit = serde_json::from_str("3").unwrap();
         assert_eq!(result, Bit::Hi);
     }
+
+    #[test]
+    fn test23() {
+        let result = unfolding_from_lsb_first(LsbFirst::new(1 << 0 | 1 << 3 | 1 << 6));
+        let expected = vec_bits!("10010010");
+        assert_eq!(result.to_vec(), expected);
+        let value = LsbFirst::new(0xe0);
+        assert_eq!(
+            folding_to_lsb_first(&unfolding_from_lsb_first(value)),
+            value
+        )
+    }
+
+    #[test]
+    fn test24() {
+        let result = show_bit_pattern_grouped(&vec_bits!("101010010000"), 7, ' ');
+        let expected = "1010100 10000";
+        assert_eq!(result, expected);
+        let result = show_bit_pattern_grouped(&vec_bits!("1010100100001"), 3, '_');
+        let expected = "101_010_010_000_1";
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn test25() {
+        let result = show_bit_pattern_grouped(&[], 7, ' ');
+        assert_eq!(result, "");
+        let result = show_bit_pattern(&[]);
+        assert_eq!(result, "")
+    }
+
+    #[test]
+    fn test26() {
+        assert_eq!(LsbFirst::new(0x00).to_string(), "0x00");
+        assert_eq!(LsbFirst::new(0xff).to_string(), "0xFF");
+        assert_eq!(LsbFirst::new(0x5a).to_string(), "0x5A");
+        assert_eq!(MsbFirst::new(0x00).to_string(), "0x00");
+        assert_eq!(MsbFirst::new(0xff).to_string(), "0xFF");
+        assert_eq!(MsbFirst::new(0x5a).to_string(), "0x5A");
+    }
+
+    #[test]
+    fn test27() {
+        assert_eq!(LsbFirst::new(0x00).to_binary_string(), "00000000");
+        assert_eq!(LsbFirst::new(0xff).to_binary_string(), "11111111");
+        assert_eq!(LsbFirst::new(0x5a).to_binary_string(), "01011010");
+        assert_eq!(LsbFirst::new(0x02).to_binary_string(), "01000000");
+        assert_eq!(format!("{:b}", LsbFirst::new(0x02)), "01000000");
+        assert_eq!(
+            folding_to_lsb_first(
+                &bits_try_from_string(&LsbFirst::new(0x5a).to_binary_string()).unwrap()
+            ),
+            LsbFirst::new(0x5a)
+        );
+    }
+
+    #[test]
+    fn test28() {
+        assert_eq!(MsbFirst::new(0x00).to_binary_string(), "00000000");
+        assert_eq!(MsbFirst::new(0xff).to_binary_string(), "11111111");
+        assert_eq!(MsbFirst::new(0x5a).to_binary_string(), "01011010");
+        assert_eq!(MsbFirst::new(0x02).to_binary_string(), "00000010");
+        assert_eq!(format!("{:b}", MsbFirst::new(0x02)), "00000010");
+        assert_eq!(
+            folding_to_msb_first(
+                &bits_try_from_string(&MsbFirst::new(0x5a).to_binary_string()).unwrap()
+            ),
+            MsbFirst::new(0x5a)
+        );
+    }
+
+    #[test]
+    fn test29() {
+        assert_eq!(MsbFirst::from(LsbFirst::new(0x02)), MsbFirst::new(0x40));
+        assert_eq!(MsbFirst::from(LsbFirst::new(0x20)), MsbFirst::new(0x04));
+        assert_eq!(MsbFirst::from(LsbFirst::new(0xe0)), MsbFirst::new(0x07));
+        assert_eq!(LsbFirst::from(MsbFirst::new(0x40)), LsbFirst::new(0x02));
+        assert_eq!(reverse_bits(0x01), 0x80);
+        assert_eq!(reverse_bits(0x5a), 0x5a);
+        let bits = vec_bits!("01000000");
+        assert_eq!(
+            MsbFirst::from(folding_to_lsb_first(&bits)),
+            folding_to_msb_first(&bits)
+        );
+    }
+
+    #[test]
+    fn test30() {
+        let bits = vec_bits!("10010010");
+        assert_eq!(LsbFirst::try_from(&bits[..]), Ok(LsbFirst::new(0x49)));
+        assert_eq!(MsbFirst::try_from(&bits[..]), Ok(MsbFirst::new(0x92)));
+        assert_eq!(
+            LsbFirst::try_from(&bits[..]),
+            Ok(folding_to_lsb_first(&bits))
+        );
+        assert_eq!(
+            MsbFirst::try_from(&bits[..]),
+            Ok(folding_to_msb_first(&bits))
+        );
+    }
+
+    #[test]
+    fn test31() {
+        let bits = vec_bits!("1011");
+        assert_eq!(LsbFirst::try_from(&bits[..]), Ok(LsbFirst::new(0x0d)));
+        assert_eq!(MsbFirst::try_from(&bits[..]), Ok(MsbFirst::new(0x0b)));
+    }
+
+    #[test]
+    fn test32() {
+        let bits = vec_bits!("100100101");
+        assert!(LsbFirst::try_from(&bits[..]).is_err());
+        assert!(MsbFirst::try_from(&bits[..]).is_err());
+    }
+
+    #[test]
+    fn test33() {
+        assert_eq!(invert_bits(&vec_bits!("10010010")), vec_bits!("01101101"));
+        assert_eq!(invert_bits(&[]), vec![]);
+    }
+
+    #[test]
+    fn test34() {
+        let a = vec_bits!("1100");
+        let b = vec_bits!("1010");
+        assert_eq!(xor_bits(&a, &b), Ok(vec_bits!("0110")));
+        assert_eq!(and_bits(&a, &b), Ok(vec_bits!("1000")));
+        assert_eq!(or_bits(&a, &b), Ok(vec_bits!("1110")));
+    }
+
+    #[test]
+    fn test35() {
+        let a = vec_bits!("1100");
+        let b = vec_bits!("101");
+        assert!(xor_bits(&a, &b).is_err());
+        assert!(and_bits(&a, &b).is_err());
+        assert!(or_bits(&a, &b).is_err());
+    }
+
+    #[test]
+    fn test_folding_u16_u32() {
+        let bits = vec_bits!("1001_0000_0001");
+        assert_eq!(folding_to_lsb_first_u16(&bits), 1 << 0 | 1 << 3 | 1 << 11);
+        assert_eq!(folding_to_msb_first_u16(&bits), 1 << 11 | 1 << 8 | 1 << 0);
+        assert_eq!(folding_to_lsb_first_u32(&bits), 1 << 0 | 1 << 3 | 1 << 11);
+        assert_eq!(folding_to_msb_first_u32(&bits), 1 << 11 | 1 << 8 | 1 << 0);
+        let bits = [Bit::Hi; 40];
+        assert_eq!(folding_to_lsb_first_u16(&bits), u16::MAX);
+        assert_eq!(folding_to_msb_first_u32(&bits), u32::MAX);
+        assert_eq!(folding_to_lsb_first_u32(&[]), 0);
+    }
+
+    #[test]
+    fn test_try_vec_bits() {
+        assert_eq!(try_vec_bits!("0101_0111"), Ok(vec_bits!("01010111")));
+        assert_eq!(try_vec_bits!("01", "10"), Ok(vec_bits!("0110")));
+        assert_eq!(
+            try_vec_bits!("01X"),
+            Err(BitParseError::InvalidCharacter('X', 2))
+        );
+        assert_eq!(
+            bits_try_from_string("0_1_2"),
+            Err(BitParseError::InvalidCharacter('2', 4))
+        );
+        assert_eq!(bits_try_from_string(""), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_bits_hex_string() {
+        let bits = vec_bits!("10110001_11001010");
+        let hex = bits_to_hex_string(&bits);
+        assert_eq!(hex, "D835");
+        assert_eq!(bits_from_hex_string(&hex), Ok(bits));
+        assert_eq!(bits_from_hex_string("d835"), bits_from_hex_string("D835"));
+        // 4ビットに満たない末尾
+        assert_eq!(bits_to_hex_string(&vec_bits!("1011_01")), "D2");
+        assert_eq!(
+            bits_from_hex_string("1G"),
+            Err(BitParseError::InvalidCharacter('G', 1))
+        );
+        assert_eq!(bits_to_hex_string(&[]), "");
+    }
 }
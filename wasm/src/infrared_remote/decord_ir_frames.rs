@@ -3,24 +3,786 @@
 // See LICENSE file in the project root for full license information.
 pub use crate::infrared_remote::*;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 /// 復号後の赤外線リモコン信号
 pub struct InfraredRemoteControlCode(pub HashMap<String, String>);
 
+impl InfraredRemoteControlCode {
+    fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(|s| s.as_str())
+    }
+
+    /// 温度
+    pub fn temperature(&self) -> Option<u8> {
+        self.get("temperature").and_then(|s| s.parse().ok())
+    }
+
+    /// 華氏温度
+    pub fn temperature_fahrenheit(&self) -> Option<f32> {
+        self.temperature().map(|c| (c as f32) * 9.0 / 5.0 + 32.0)
+    }
+
+    /// 温度を華氏に置き換える
+    pub fn into_fahrenheit(mut self) -> Self {
+        if let Some(f) = self.temperature_fahrenheit() {
+            self.0.insert("temperature".to_owned(), format!("{:.1}", f));
+        }
+        self
+    }
+
+    /// モード
+    pub fn hvac_mode(&self) -> Option<&str> {
+        self.get("hvac_mode")
+    }
+
+    /// 電源
+    pub fn power_switch(&self) -> Option<bool> {
+        match self.get("power_switch")? {
+            "power_on" => Some(true),
+            "power_off" => Some(false),
+            _ => None,
+        }
+    }
+
+    /// 風量
+    pub fn fan_speed(&self) -> Option<&str> {
+        self.get("fan_speed")
+    }
+
+    /// 製造者名
+    pub fn manufacturer(&self) -> Option<&str> {
+        self.get("manufacturer")
+    }
+
+    /// チェックサム
+    pub fn checksum(&self) -> Option<u8> {
+        self.get("checksum").and_then(|s| s.parse().ok())
+    }
+
+    /// 項目名
+    pub fn field_names(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(|s| s.as_str())
+    }
+
+    /// 型付きの項目
+    pub fn fields(&self) -> ControlCodeFields<'_> {
+        let mut field_names = self.field_names().collect::<Vec<&str>>();
+        field_names.sort();
+        ControlCodeFields {
+            manufacturer: self.manufacturer(),
+            hvac_mode: self.hvac_mode(),
+            temperature: self.temperature(),
+            power_switch: self.power_switch(),
+            fan_speed: self.fan_speed(),
+            checksum: self.checksum(),
+            field_names,
+        }
+    }
+
+    /// 項目名順に並べたJSON文字列
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string(&self.0.iter().collect::<BTreeMap<&String, &String>>())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+/// 復号結果の型付きの項目
+pub struct ControlCodeFields<'a> {
+    pub manufacturer: Option<&'a str>,
+    pub hvac_mode: Option<&'a str>,
+    pub temperature: Option<u8>,
+    pub power_switch: Option<bool>,
+    pub fan_speed: Option<&'a str>,
+    pub checksum: Option<u8>,
+    /// 項目名順
+    pub field_names: Vec<&'a str>,
+}
+
+impl fmt::Display for InfraredRemoteControlCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lines = self
+            .0
+            .iter()
+            .collect::<BTreeMap<&String, &String>>()
+            .into_iter()
+            .map(|(name, value)| format!("{}: {}", name, value))
+            .collect::<Vec<String>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// 復号結果のJSON Schema (Draft 7)
+/// 値はすべて文字列(表記は機器ごとに異なる), 機器固有の項目も許容する
+pub fn json_schema_for_control_code() -> serde_json::Value {
+    let mut manufacturers = supported_devices()
+        .into_iter()
+        .map(|device| device.manufacturer)
+        .collect::<Vec<&str>>();
+    manufacturers.sort();
+    manufacturers.dedup();
+    // 復号できなかった場合の製造者名
+    manufacturers.push("unknown");
+    let string = || serde_json::json!({ "type": "string" });
+    serde_json::json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "InfraredRemoteControlCode",
+        "type": "object",
+        "properties": {
+            "manufacturer": { "type": "string", "enum": manufacturers },
+            "hvac_mode": string(),
+            "temperature": string(),
+            "power_switch": string(),
+            "fan_speed": string(),
+            "swing": string(),
+            "profile": string(),
+            "checksum": string(),
+            "address": string(),
+            "command": string(),
+        },
+        "required": ["manufacturer"],
+        "additionalProperties": { "type": "string" }
+    })
+}
+
+/// 2つの復号結果の差分
+/// 値の異なる項目, 追加された項目, 削除された項目の (変更前, 変更後) の値を返す
+pub fn diff(
+    a: &InfraredRemoteControlCode,
+    b: &InfraredRemoteControlCode,
+) -> HashMap<String, (Option<String>, Option<String>)> {
+    a.0.keys()
+        .chain(b.0.keys())
+        .filter(|&name| a.0.get(name) != b.0.get(name))
+        .map(|name| {
+            (
+                name.clone(),
+                (a.0.get(name).cloned(), b.0.get(name).cloned()),
+            )
+        })
+        .collect()
+}
+
 /// 復号
 /// 復号後の赤外線リモコン信号
 pub fn decord_ir_frames(frames: &[DecordedInfraredRemoteFrame]) -> Vec<InfraredRemoteControlCode> {
-    vec![
-        toshiba_tv::decode(frames),
-        sirc::decode(frames),
-        panasonic_hvac::decode(frames),
-        daikin_hvac::decode(frames),
-        hitachi_hvac::decode(frames),
-        mitsubishi_electric_hvac::decode(frames),
-    ]
-    .iter()
-    .find(|&v| !v.is_empty())
-    .map(|a| a.to_owned())
-    .unwrap_or(vec![])
+    DECODERS
+        .iter()
+        .map(|decoder| decoder.decode(frames))
+        .find(|v| !v.is_empty())
+        .unwrap_or(vec![])
+}
+
+/// 復号
+/// 対応機器が見つからなければ復調済みのビット列をそのまま返す
+pub fn decord_ir_frames_with_fallback(
+    frames: &[DecordedInfraredRemoteFrame],
+) -> Vec<InfraredRemoteControlCode> {
+    let codes = decord_ir_frames(frames);
+    if !codes.is_empty() {
+        return codes;
+    }
+    frames
+        .iter()
+        .filter_map(|frame| {
+            let (protocol, bits) = match frame {
+                DecordedInfraredRemoteFrame::Aeha(bits) => ("AEHA", bits),
+                DecordedInfraredRemoteFrame::Nec(bits) => ("NEC", bits),
+                DecordedInfraredRemoteFrame::Sirc(bits) => ("SIRC", bits),
+                DecordedInfraredRemoteFrame::SircRepeat { frame, .. } => ("SIRC", frame),
+                DecordedInfraredRemoteFrame::Rc5(bits) => ("RC5", bits),
+                DecordedInfraredRemoteFrame::Rc6(bits) => ("RC6", bits),
+                DecordedInfraredRemoteFrame::Samsung(bits) => ("SAMSUNG", bits),
+                DecordedInfraredRemoteFrame::Kaseikyo(bits) => ("KASEIKYO", bits),
+                _ => return None,
+            };
+            let mut decorded: HashMap<String, String> = HashMap::new();
+            decorded.insert("raw_bits".to_owned(), bits_to_hex_string(bits));
+            decorded.insert("protocol".to_owned(), protocol.to_owned());
+            decorded.insert("manufacturer".to_owned(), "unknown".to_owned());
+            Some(InfraredRemoteControlCode(decorded))
+        })
+        .collect()
+}
+
+/// 赤外線リモコンコード文字列の解析から復号までを一度に行う
+pub fn parse_and_decode(input: &str) -> Result<Vec<InfraredRemoteControlCode>, Box<dyn Error>> {
+    let mark_and_spaces = crate::parsing::parse_infrared_code_text(input)?;
+    let frames = decord_receiving_data(&mark_and_spaces, None)?;
+    Ok(decord_ir_frames(&frames))
+}
+
+/// 復調済みのビット列を復号する
+pub fn decode_raw_bits(
+    protocol: InfraredRemoteProtocol,
+    bits: Vec<Bit>,
+) -> Vec<InfraredRemoteControlCode> {
+    let frame = match protocol {
+        InfraredRemoteProtocol::Nec => DecordedInfraredRemoteFrame::Nec(bits),
+        InfraredRemoteProtocol::Aeha => DecordedInfraredRemoteFrame::Aeha(bits),
+        InfraredRemoteProtocol::Sirc => DecordedInfraredRemoteFrame::Sirc(bits),
+        InfraredRemoteProtocol::Rc5 => DecordedInfraredRemoteFrame::Rc5(bits),
+        InfraredRemoteProtocol::Rc6 => DecordedInfraredRemoteFrame::Rc6(bits),
+        InfraredRemoteProtocol::Samsung => DecordedInfraredRemoteFrame::Samsung(bits),
+        InfraredRemoteProtocol::Kaseikyo => DecordedInfraredRemoteFrame::Kaseikyo(bits),
+        InfraredRemoteProtocol::Unknown => DecordedInfraredRemoteFrame::Unknown(()),
+    };
+    decord_ir_frames(&[frame])
+}
+
+/// 複数の赤外線リモコンコード文字列をまとめて復号する
+/// 解析か復号に失敗したものは None
+pub fn batch_parse_and_decode(inputs: &[String]) -> Vec<Option<Vec<InfraredRemoteControlCode>>> {
+    inputs
+        .iter()
+        .map(|input| {
+            parse_and_decode(input)
+                .ok()
+                .filter(|codes| !codes.is_empty())
+        })
+        .collect()
+}
+
+/// 最初のAEHAフレームのヘッダだけで製造元を判定する
+pub fn identify_manufacturer(frames: &[DecordedInfraredRemoteFrame]) -> Option<&'static str> {
+    let headers: [(&[LsbFirst], &'static str); 4] = [
+        (&panasonic_hvac::FRAME_HEADER, "panasonic"),
+        (&daikin_hvac::FRAME_HEADER, "daikin"),
+        (&hitachi_hvac::FRAME_HEADER, "hitachi"),
+        (
+            &mitsubishi_electric_hvac::FRAME_HEADER,
+            "mitsubishi electric",
+        ),
+    ];
+    let bits = frames.iter().find_map(|f| match f {
+        DecordedInfraredRemoteFrame::Aeha(bits) => Some(bits),
+        _ => None,
+    })?;
+    let octets = pack_to_octets(bits)
+        .iter()
+        .map(|&x| LsbFirst::from(x))
+        .collect::<Vec<LsbFirst>>();
+    headers
+        .into_iter()
+        .find(|(header, _)| octets.starts_with(header))
+        .map(|(_, manufacturer)| manufacturer)
+}
+
+/// 全ての復号器で復号する
+/// 登録済みの復号器毎の復号結果(復号できなかった場合は空)
+pub fn decord_ir_frames_all(
+    frames: &[DecordedInfraredRemoteFrame],
+) -> Vec<Vec<InfraredRemoteControlCode>> {
+    DECODERS
+        .iter()
+        .map(|decoder| decoder.decode(frames))
+        .collect()
+}
+
+#[cfg(test)]
+mod decord_ir_frames_tests {
+    use crate::infrared_remote::*;
+
+    // panasonic_hvac decode_panasonic_tests test1
+    const PANASONIC_RXDATA: &str = "8800410014001000130032001300100013001000130010001300100014001000130010001300100013001000130010001400100013001000130032001300100013001000130010001400100013001000130010001300100014003100130032001300320013001000130010001300320013001000130010001400100013001000130010001300100014000F00140010001300100013001000130010001300100014001000130010001300100013001000140010001300100013001000130010001300100014001000130010001300100013001000130010001400100013001000130010001400100013003100140031001300100013001000140010001300100013001000130082018800410013001000140031001300100014001000130010001300100014000F001400100013001000130010001300100014000F00140010001300320013001000130010001300100014000F001400100013001000130010001300320013003200130032001300100013001000130032001300100013001000140010001300100013001000130010001300100014001000130010001300100013001000140010001300100013003200130010001300100014003100130032001300310014001000130010001300100013001000140031001300100014003100130032001300100013000F0013001000140010001300100013001000130010001300100014001000130031001400310013003200130032001300320013001000130032001300100013003200130031001400100013003100140031001300100013001000140010001300100013001000130010001400100013001000130010001300100013001000140010001300100013003200130032001300310014000F0014001000130010001300100013001000130010001400100013001000130010001300320013003200130031001400100013001000130010001300100013001000140010001300320013001000130010001300100014000F001400100013001000130010001300100014001000130010001300320013003200130010001300100013001000130011001300310013001000140010001300100013001000130010001300100014001000130010001300100013001000130011001300310013001000140010001300100013001000130032001300320013001000130032001300100013003200130032001300100013004F03";
+
+    // daikin_hvac decode_tests test1
+    const DAIKIN_RXDATA: &str = "[417,448,418,450,417,450,417,449,418,448,417,25329,3450,1747,418,1315,419,446,419,449,417,450,417,1315,418,449,417,449,417,449,417,450,417,1314,418,450,417,1315,417,1315,418,448,418,1315,418,1315,417,1315,418,1315,417,1315,418,450,417,448,419,1312,419,449,417,449,417,451,416,449,419,448,417,449,417,450,417,448,419,448,417,449,417,1316,417,450,416,1314,419,448,418,449,417,449,418,1314,418,1314,419,449,417,450,417,448,419,447,418,450,417,448,419,448,417,449,417,449,418,448,418,449,418,449,417,448,419,448,417,449,418,449,417,1315,418,1314,418,1315,418,448,419,1313,419,448,419,1313,419,1313,420,34665,3450,1748,418,1314,419,447,418,450,416,450,417,1316,416,450,418,448,417,449,418,449,417,1315,418,449,418,1315,417,1315,417,451,416,1316,417,1314,418,1314,418,1316,416,1316,417,450,417,450,417,1313,418,451,416,449,417,449,418,449,416,450,417,449,417,450,416,449,417,450,416,451,416,449,419,1314,418,448,417,449,417,451,416,449,418,1317,416,450,415,450,417,449,418,448,417,450,416,450,417,451,416,448,417,450,417,449,417,450,417,450,417,449,418,448,417,453,414,449,417,449,417,450,416,450,416,1316,418,449,417,1315,417,449,418,1315,418,449,417,34670,3449,1750,416,1316,417,451,416,449,416,450,417,1315,418,450,416,450,415,451,417,449,417,1316,416,450,418,1315,416,1316,417,449,418,1315,418,1315,417,1316,417,1315,417,1315,418,450,416,450,417,1316,416,454,412,450,416,451,416,450,416,450,416,450,416,451,416,451,417,448,417,450,416,449,418,450,417,448,417,450,417,450,416,450,416,450,417,450,416,1317,416,1316,416,450,416,1317,417,1315,417,1316,417,449,418,448,417,452,414,451,416,1316,416,1316,417,450,416,1316,417,449,418,450,417,449,416,450,417,450,417,450,416,450,416,451,415,450,419,448,416,1316,417,1316,417,1315,418,1317,416,450,417,449,417,1315,417,450,416,450,420,448,415,450,416,450,417,450,416,450,416,450,417,449,418,1315,417,451,416,449,417,1316,416,451,416,450,416,451,415,1316,417,451,416,1316,416,450,418,450,415,450,416,451,416,451,416,449,417,450,416,450,417,450,416,450,416,450,416,1316,417,1317,417,447,418,450,416,451,416,451,416,449,416,450,417,450,417,449,416,450,416,452,414,451,416,450,416,451,415,451,416,451,415,450,416,451,416,1317,416,451,415,451,416,451,415,452,414,451,415,1317,417,1316,416,451,416,451,416,450,415,453,414,451,415,451,416,451,415,452,414,452,415,450,417,451,416,451,414,451,416,451,416,451,414,451,416,451,415,451,416,1317,416,451,415,1317,416,1316,417,1316,416,451,416]";
+
+    // hitachi_hvac decode_tests test1
+    const HITACHI_RXDATA: &str = "6D0458078200400011003000120010001100110013000F0011001100120010001200100012001000110010001200100012001000110011001200300012001000120010001200100012001000110010001200100012001000120010001100110012001000110011001200100012001000110011001100100012001000120010001200300012001000120030001100300011003100120030001100300012003000120010001200300012003000110030001200300012002F00120030001200300012002F00120030001100110011001100120010001100110010001100120010001200100012001000120010001100110013002F00110030001200110011001000120030001200300012002F00120030001100110011001100110031001100310012000F00120010001200100012003000110011001200100011003100100011001100110012003000120030001200100012002F00120030001100110012003000120030001100110011003100110031001100100011001100110031001100110012001000110011001100110011001000120030001200300012001000120030001100300011003100110011001100110012001000110030001200300011001100120030001200100012002F001100310011003100110011001200100011003100110011001100300012001000110011001200100011001100100011001200100011001100120010001200300012002F00110031001100310012002F001200300011003100120030001100110010001100110011001200100013000F00120010001100110013000F0011003000110031001100310010003100120030001100310011003000120030001100110011001100110011001100110010001100110011001100110011001100120030001200300012002F0012003000110031001000310011003100110031001100110010001100120010001200100011001100110011001200100011001100110030001100310011003100100031001100310011003100100031001100310011001100120010001100110011001100110011001000110011001100110011001200300011003100100031001100310011003100100031001200300012003000110011001100300012003000110011001100310011001100110031000F0013001100300011001100120010001100310011001100110031001100110011003100100031001100120010001100110011001100310011003100100031001100310010001200110031001000310011003100100012001100110011001100110011001100100010001100110011001100110011001100110011001100110011001100110030001100310011003100100031001100310011003100100031001100310011001100120010001000120011001100100012001000110011001100110011001100310011003100100031001100310011003100100031001100310010003200110011000F0013001000110010001200110011001000120011001100110031001000310010003200110031000F003200110031001100300011003100110011001000320010003200100012001000120011001100110011001100110010001200100012000F0013000F0032001100310011003000100032001100310011003000110031001100110011001100110011001100110010001200100011001000120011001100100032001000320010003100100032001100310010003100110031001100110011001100100012000F00320010001300100011001000120011003100110031000F00320011003100110011001100310011003000110031001100110011001100110011001100110010001200100011001100110011001100110011001100310010003100110031001100310010003100110031001100310010003100120010001100110010001200110011001000120011001000110011001100110011003100110031001000310011003100110031001000310011003100110031001100300012003000110031001100300011003100110031001000310011003100110011001100110011001100100012001100100011001100110011001200100011003100110030001100310011003100100031001100310011003100110031001000120010001100110011001200100011001100110011001100110011001100100031001100310011003100110030001200300012003000110030001200300012001000110011001100110011001100100011001100110012001000120010001200300012002F00120030001200300012002F00110031001200300012003000110010001200100011001100110011001200100012001000110011001100110010004F03";
+
+    // mitsubishi_electric_hvac decode_tests test1
+    const MITSUBISHI_ELECTRIC_RXDATA: &str = "840044001200320012003100120011001200110010001200110033001200110012001100120031001100320013001000120032001200100013001000130031001200310013001000110032001300310012001100120011001200310011001200120011001000330012001100110012001200110012001100120010001300100013001000130010001300100012001100130010001200110011001200120011001200110012001000110012001300100013001000120011001200310013001000130010001300100012001100120011001200310013003100120010001300310012001100120010001100330012001000130031001200110012001100120011001200100013001000130031001000130012001000130010001300100012003200100033001200110010001300120011001200100013001000130010001200320012001000130010001300100013001000130010001100120012001100120011001200110012001000130010001100120013001000110012001300100012001100120011001000130012001100120011001200100013001000130010001100120013001000120011001200110012001100120011001200110012001000130031001200110012001100120011001200110012001000130031001200110012001000130031001300100013001000130010001300100012001100120011001200110012001100120011001200100013001000130010001300100013001000120011001200110012003100130010001300100012003100110012001200310013003100120011001200EB01";
+
+    // toshiba_tv decode_tests test1
+    const TOSHIBA_RXDATA: &str = "5601A900180015001800140018001400190013001900140019001400170040001700150018003F0019003E0018003E0019003F0019003E00170040001800140019003E001800150018003F00180014001800140019003F0018001400170016001700150018003F001800140018003F0018003F001800140019003F0018003F0018003E0019004F03";
+
+    // sirc decode_tests test1
+    const SIRC_RXDATA: &str = "5B0018002E001800180018002E001800170018002E00190017001800170018002E00180018001800170018001700180017004F03";
+
+    // philips_tv decode_tests test1
+    const PHILIPS_RXDATA: &str = "[889,889,1778,889,889,889,889,889,889,889,889,889,889,889,889,889,889,1778,889,889,1778,889,889,89000]";
+
+    #[test]
+    fn test1() {
+        let markandspaces =
+            panasonic_hvac::encode("hvac_mode_cool", "power_on", 26, "auto", "auto", "normal")
+                .unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let result = decord_ir_frames(&frames);
+        assert_eq!(result, panasonic_hvac::decode(&frames));
+        assert_eq!(
+            result[0].0.get("manufacturer").map(|s| s.as_str()),
+            Some(PanasonicHvacDecoder.manufacturer_name())
+        );
+    }
+
+    #[test]
+    fn test2() {
+        // 機器の種類は tv, av, hvac のいずれか
+        assert!(DECODERS
+            .iter()
+            .all(|decoder| ["tv", "av", "hvac"].contains(&decoder.device_type())));
+    }
+
+    #[test]
+    fn test3() {
+        let markandspaces =
+            panasonic_hvac::encode("hvac_mode_cool", "power_on", 26, "auto", "auto", "normal")
+                .unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let result = decord_ir_frames_all(&frames);
+        assert_eq!(result.len(), DECODERS.len());
+        let matched = result
+            .iter()
+            .filter(|codes| !codes.is_empty())
+            .collect::<Vec<&Vec<InfraredRemoteControlCode>>>();
+        assert_eq!(matched, vec![&panasonic_hvac::decode(&frames)]);
+        assert_eq!(
+            result.iter().filter(|codes| codes.is_empty()).count(),
+            DECODERS.len() - 1
+        );
+    }
+
+    #[test]
+    fn test4() {
+        let result = supported_devices();
+        let expected = [
+            ("toshiba", "tv"),
+            ("sony", "av"),
+            ("philips", "tv"),
+            ("panasonic", "tv"),
+            ("panasonic", "hvac"),
+            ("pioneer", "av"),
+            ("daikin", "hvac"),
+            ("hitachi", "hvac"),
+            ("mitsubishi electric", "hvac"),
+            ("lg", "hvac"),
+            ("whirlpool", "hvac"),
+            ("samsung", "tv"),
+        ]
+        .into_iter()
+        .map(|(manufacturer, device_type)| SupportedDevice {
+            manufacturer,
+            device_type,
+        })
+        .collect::<Vec<SupportedDevice>>();
+        assert_eq!(result, expected);
+        assert_eq!(
+            serde_json::to_string(&result[4]).unwrap(),
+            r#"{"manufacturer":"panasonic","device_type":"hvac"}"#
+        );
+    }
+
+    #[test]
+    fn test5() {
+        assert_eq!(
+            serde_json::to_string(&SUPPORTED_PROTOCOLS).unwrap(),
+            r#"["NEC","AEHA","SIRC","RC5","RC6","SAMSUNG","KASEIKYO"]"#
+        );
+    }
+
+    #[test]
+    fn test6() {
+        // panasonic_hvac decode_panasonic_tests test1
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("hvac_mode".to_owned(), "hvac_mode_cool".to_owned());
+        decorded.insert("power_switch".to_owned(), "power_on".to_owned());
+        decorded.insert("temperature".to_owned(), "26".to_owned());
+        decorded.insert("fan_speed".to_owned(), "auto".to_owned());
+        decorded.insert("swing".to_owned(), "auto".to_owned());
+        decorded.insert("checksum".to_owned(), "107".to_owned());
+        decorded.insert("manufacturer".to_owned(), "panasonic".to_owned());
+        let code = InfraredRemoteControlCode(decorded);
+        assert_eq!(code.temperature(), Some(26));
+        assert_eq!(code.hvac_mode(), Some("hvac_mode_cool"));
+        assert_eq!(code.power_switch(), Some(true));
+        assert_eq!(code.fan_speed(), Some("auto"));
+        assert_eq!(code.manufacturer(), Some("panasonic"));
+        assert_eq!(code.checksum(), Some(107));
+        let mut names = code.field_names().collect::<Vec<&str>>();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "checksum",
+                "fan_speed",
+                "hvac_mode",
+                "manufacturer",
+                "power_switch",
+                "swing",
+                "temperature"
+            ]
+        );
+        assert_eq!(
+            serde_json::to_string(&code.fields()).unwrap(),
+            r#"{"manufacturer":"panasonic","hvac_mode":"hvac_mode_cool","temperature":26,"power_switch":true,"fan_speed":"auto","checksum":107,"field_names":["checksum","fan_speed","hvac_mode","manufacturer","power_switch","swing","temperature"]}"#
+        );
+        // 項目が無い
+        let code = InfraredRemoteControlCode(HashMap::new());
+        assert_eq!(code.temperature(), None);
+        assert_eq!(code.power_switch(), None);
+        assert_eq!(code.field_names().count(), 0);
+        assert_eq!(
+            code.fields(),
+            ControlCodeFields {
+                manufacturer: None,
+                hvac_mode: None,
+                temperature: None,
+                power_switch: None,
+                fan_speed: None,
+                checksum: None,
+                field_names: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test7() {
+        // panasonic_hvac decode_panasonic_tests test1
+        let markandspaces = crate::parsing::parse_infrared_code_text(PANASONIC_RXDATA).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let code = &decord_ir_frames(&frames)[0];
+        assert_eq!(
+            code.to_string(),
+            [
+                "checksum: 107",
+                "fan_speed: auto",
+                "hvac_mode: hvac_mode_cool",
+                "manufacturer: panasonic",
+                "power_switch: power_on",
+                "swing: auto",
+                "temperature: 26",
+                "timer_off_enabled: disabled",
+                "timer_on_enabled: disabled",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            code.to_json_string(),
+            r#"{"checksum":"107","fan_speed":"auto","hvac_mode":"hvac_mode_cool","manufacturer":"panasonic","power_switch":"power_on","swing":"auto","temperature":"26","timer_off_enabled":"disabled","timer_on_enabled":"disabled"}"#
+        );
+    }
+
+    #[test]
+    fn test8() {
+        // daikin_hvac decode_tests test1
+        let markandspaces = crate::parsing::parse_infrared_code_text(DAIKIN_RXDATA).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let code = &decord_ir_frames(&frames)[0];
+        let text = code.to_string();
+        let json = code.to_json_string();
+        for name in code.field_names() {
+            assert!(text.contains(&format!("{}: ", name)));
+            assert!(json.contains(&format!("\"{}\":", name)));
+        }
+        assert_eq!(text.lines().count(), code.0.len());
+        assert!(text.contains("manufacturer: daikin"));
+        assert!(text.contains("temperature: 22"));
+    }
+
+    #[test]
+    fn test9() {
+        // 冷房, 24度, 風量自動, 電源オン
+        let markandspaces = protocol_nec::encode_nec([0x88, 0x00, 0x89, 0x0a]);
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let cool = &decord_ir_frames(&frames)[0];
+        let again = &decord_ir_frames(&frames)[0];
+        assert!(diff(cool, again).is_empty());
+        // 暖房, 24度, 風量自動, 電源オン
+        let markandspaces = protocol_nec::encode_nec([0x88, 0x00, 0xc9, 0x0a]);
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let heat = &decord_ir_frames(&frames)[0];
+        let mut expected = HashMap::new();
+        expected.insert(
+            "hvac_mode".to_owned(),
+            (
+                Some("hvac_mode_cool".to_owned()),
+                Some("hvac_mode_heat".to_owned()),
+            ),
+        );
+        assert_eq!(diff(cool, heat), expected);
+    }
+
+    #[test]
+    fn test10() {
+        let mut a = HashMap::new();
+        a.insert("checksum".to_owned(), "1".to_owned());
+        a.insert("swing".to_owned(), "auto".to_owned());
+        let mut b = HashMap::new();
+        b.insert("swing".to_owned(), "auto".to_owned());
+        b.insert("profile".to_owned(), "quiet".to_owned());
+        let result = diff(&InfraredRemoteControlCode(a), &InfraredRemoteControlCode(b));
+        let mut expected = HashMap::new();
+        expected.insert("checksum".to_owned(), (Some("1".to_owned()), None));
+        expected.insert("profile".to_owned(), (None, Some("quiet".to_owned())));
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test11() {
+        for (celsius, fahrenheit) in [("16", 60.8), ("26", 78.8), ("30", 86.0)] {
+            let mut decorded = HashMap::new();
+            decorded.insert("temperature".to_owned(), celsius.to_owned());
+            let code = InfraredRemoteControlCode(decorded);
+            let result = code.temperature_fahrenheit().unwrap();
+            assert!((result - fahrenheit).abs() < 1e-4);
+            assert_eq!(
+                code.into_fahrenheit().0.get("temperature"),
+                Some(&format!("{:.1}", fahrenheit))
+            );
+        }
+        let code = InfraredRemoteControlCode(HashMap::new());
+        assert_eq!(code.temperature_fahrenheit(), None);
+        assert_eq!(
+            code.into_fahrenheit(),
+            InfraredRemoteControlCode(HashMap::new())
+        );
+    }
+
+    #[test]
+    fn test12() {
+        // JSON配列形式の文字列にする
+        let to_text = |signals: &[MarkAndSpaceMicros]| {
+            let values = signals
+                .iter()
+                .flat_map(|ms| [ms.mark.0.to_string(), ms.space.0.to_string()])
+                .collect::<Vec<String>>();
+            format!("[{}]", values.join(","))
+        };
+        let vectors = vec![
+            panasonic_hvac::encode("hvac_mode_cool", "power_on", 26, "auto", "auto", "normal")
+                .unwrap(),
+            daikin_hvac::DaikinHvacBuilder::new()
+                .build()
+                .unwrap()
+                .encode()
+                .unwrap(),
+            protocol_samsung::encode_samsung([0x07, 0x07, 0x02, 0xfd]),
+            protocol_sirc::encode_sirc12(0x15, 0x01),
+            protocol_nec::encode_nec([0x88, 0x00, 0x89, 0x0a]),
+        ];
+        for signals in vectors {
+            let text = to_text(&signals);
+            // 個別に呼び出した結果と一致する
+            let parsed = crate::parsing::parse_infrared_code_text(&text).unwrap();
+            let frames = decord_receiving_data(&parsed, None).unwrap();
+            let expected = decord_ir_frames(&frames);
+            assert!(!expected.is_empty());
+            assert_eq!(parse_and_decode(&text).unwrap(), expected);
+        }
+        assert!(parse_and_decode("").is_err());
+    }
+
+    #[test]
+    fn test13() {
+        // JSON配列形式の文字列にする
+        let to_text = |signals: &[MarkAndSpaceMicros]| {
+            let values = signals
+                .iter()
+                .flat_map(|ms| [ms.mark.0.to_string(), ms.space.0.to_string()])
+                .collect::<Vec<String>>();
+            format!("[{}]", values.join(","))
+        };
+        let panasonic = to_text(
+            &panasonic_hvac::encode("hvac_mode_cool", "power_on", 26, "auto", "auto", "normal")
+                .unwrap(),
+        );
+        let daikin = to_text(
+            &daikin_hvac::DaikinHvacBuilder::new()
+                .build()
+                .unwrap()
+                .encode()
+                .unwrap(),
+        );
+        let inputs = vec![
+            panasonic.clone(),
+            "not an ir code".to_owned(),
+            daikin.clone(),
+            "".to_owned(),
+            // 解析できるが復号できない
+            "[100,100,100,100]".to_owned(),
+        ];
+        let result = batch_parse_and_decode(&inputs);
+        assert_eq!(result.len(), inputs.len());
+        assert_eq!(result[0], Some(parse_and_decode(&panasonic).unwrap()));
+        assert_eq!(result[1], None);
+        assert_eq!(result[2], Some(parse_and_decode(&daikin).unwrap()));
+        assert_eq!(result[3], None);
+        assert_eq!(result[4], None);
+        assert_eq!(
+            result[2].as_ref().and_then(|codes| codes[0].manufacturer()),
+            Some("daikin")
+        );
+    }
+
+    #[test]
+    fn test14() {
+        // 東芝テレビの電源
+        let bits = [0x40, 0xbf, 0x12, 0xed]
+            .into_iter()
+            .flat_map(|x| unfolding_from_lsb_first(LsbFirst::new(x)))
+            .collect::<Vec<Bit>>();
+        let result = decode_raw_bits(InfraredRemoteProtocol::Nec, bits.clone());
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].manufacturer(), Some("toshiba"));
+        assert_eq!(
+            result[0].0.get("command").map(|s| s.as_str()),
+            Some("Power")
+        );
+        // 同じビット列でもプロトコルが違う
+        assert_eq!(decode_raw_bits(InfraredRemoteProtocol::Aeha, bits), vec![]);
+    }
+
+    #[test]
+    fn test15() {
+        let markandspaces =
+            panasonic_hvac::encode("hvac_mode_cool", "power_on", 26, "auto", "auto", "normal")
+                .unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        assert_eq!(identify_manufacturer(&frames), Some("panasonic"));
+        //
+        [
+            (vec![0x11, 0xda, 0x27, 0x00, 0xc5], Some("daikin")),
+            (
+                vec![0x01, 0x10, 0x00, 0x40, 0xbf, 0xff, 0x00, 0xcc, 0x33, 0x92],
+                Some("hitachi"),
+            ),
+            (
+                vec![0x23, 0xcb, 0x26, 0x01, 0x00, 0x20],
+                Some("mitsubishi electric"),
+            ),
+            // ヘッダの途中までしかない
+            (vec![0x23, 0xcb, 0x26], None),
+            (vec![0x02, 0x20, 0x80, 0x00, 0x3d, 0xc2], None),
+        ]
+        .into_iter()
+        .for_each(|(octets, expected)| {
+            let frames =
+                decord_receiving_data(&protocol_aeha::encode_frame(&octets), None).unwrap();
+            assert_eq!(identify_manufacturer(&frames), expected);
+        });
+        // AEHA以外
+        let frames =
+            decord_receiving_data(&protocol_nec::encode_nec([0x40, 0xbf, 0x12, 0xed]), None)
+                .unwrap();
+        assert_eq!(identify_manufacturer(&frames), None);
+    }
+
+    // 復号結果がスキーマに沿っているか確かめる
+    fn assert_conforms_to_schema(schema: &serde_json::Value, code: &InfraredRemoteControlCode) {
+        let properties = schema["properties"].as_object().unwrap();
+        for required in schema["required"].as_array().unwrap() {
+            assert!(code.0.contains_key(required.as_str().unwrap()), "{}", code);
+        }
+        for (name, value) in code.0.iter() {
+            let property = properties
+                .get(name)
+                .unwrap_or(&schema["additionalProperties"]);
+            assert_eq!(property["type"], "string");
+            if let Some(values) = property["enum"].as_array() {
+                assert!(
+                    values.contains(&serde_json::json!(value)),
+                    "{}: {}",
+                    name,
+                    value
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test16() {
+        let schema = json_schema_for_control_code();
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["additionalProperties"]["type"], "string");
+        // 各機器の試験データ
+        let captures = [
+            PANASONIC_RXDATA,
+            DAIKIN_RXDATA,
+            HITACHI_RXDATA,
+            MITSUBISHI_ELECTRIC_RXDATA,
+            TOSHIBA_RXDATA,
+            SIRC_RXDATA,
+            PHILIPS_RXDATA,
+        ]
+        .into_iter()
+        .map(|rxdata| crate::parsing::parse_infrared_code_text(rxdata).unwrap());
+        let encoded = [
+            protocol_sirc::encode_sirc12(18, 12),
+            protocol_kaseikyo::encode_frame([0x02, 0x20], [0x80, 0x00, 0x3d]),
+            protocol_kaseikyo::encode_frame([0x02, 0x20], [0xa0, 0x00, 0x1c]),
+            protocol_aeha::encode_frame(&test_frames::with_sum_checksum(&{
+                let mut octets = [0u8; 35];
+                octets[0..4].copy_from_slice(&[0x11, 0xda, 0x17, 0x18]);
+                octets[5] = 0x39;
+                octets[6] = 26 * 2;
+                octets[8] = 0x50;
+                octets
+            })),
+            protocol_nec::encode_nec([0x88, 0x00, 0x89, 0x0a]),
+            test_frames::encode_nec_octets(&[0xd6, 0x29, 0x08, 0x50, 0x09, 0x00]),
+            protocol_samsung::encode_samsung([0x07, 0x07, 0x02, 0xfd]),
+        ];
+        let codes = captures
+            .chain(encoded)
+            .flat_map(|markandspaces| {
+                let frames = decord_receiving_data(&markandspaces, None).unwrap();
+                decord_ir_frames_all(&frames).into_iter().flatten()
+            })
+            .collect::<Vec<InfraredRemoteControlCode>>();
+        // 全ての機器の復号結果がある
+        for device in supported_devices() {
+            assert!(
+                codes
+                    .iter()
+                    .any(|code| code.manufacturer() == Some(device.manufacturer)),
+                "{}",
+                device.manufacturer
+            );
+        }
+        for code in codes {
+            assert_conforms_to_schema(&schema, &code);
+            // 華氏の温度も許容する
+            assert_conforms_to_schema(&schema, &code.into_fahrenheit());
+        }
+        // 対応機器が無い時の復号結果も許容する
+        let frames =
+            decord_receiving_data(&protocol_nec::encode_nec([0x12, 0x34, 0x56, 0xa9]), None)
+                .unwrap();
+        for code in decord_ir_frames_with_fallback(&frames) {
+            assert_conforms_to_schema(&schema, &code);
+        }
+    }
+
+    #[test]
+    fn test17() {
+        // 対応機器はそのまま復号する
+        let markandspaces =
+            panasonic_hvac::encode("hvac_mode_cool", "power_on", 26, "auto", "auto", "normal")
+                .unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        assert_eq!(
+            decord_ir_frames_with_fallback(&frames),
+            panasonic_hvac::decode(&frames)
+        );
+        // 対応機器が無い時は復調済みのビット列を返す
+        let frames =
+            decord_receiving_data(&protocol_nec::encode_nec([0x12, 0x34, 0x56, 0xa9]), None)
+                .unwrap();
+        assert_eq!(decord_ir_frames(&frames), vec![]);
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("raw_bits".to_owned(), "2143659A".to_owned());
+        decorded.insert("protocol".to_owned(), "NEC".to_owned());
+        decorded.insert("manufacturer".to_owned(), "unknown".to_owned());
+        assert_eq!(
+            decord_ir_frames_with_fallback(&frames),
+            vec![InfraredRemoteControlCode(decorded)]
+        );
+    }
 }
@@ -3,6 +3,7 @@
 // See LICENSE file in the project root for full license information.
 pub use crate::infrared_remote::*;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::error::Error;
 use std::fmt;
 
@@ -12,67 +13,789 @@ pub enum DecordedInfraredRemoteFrame {
     Aeha(Vec<Bit>),
     Nec(Vec<Bit>),
     NecRepeat(()),
+    NecRepeatSequence { count: u32 },
     Sirc(Vec<Bit>),
+    SircRepeat { frame: Vec<Bit>, count: u32 },
+    Rc5(Vec<Bit>),
+    Rc6(Vec<Bit>),
+    Samsung(Vec<Bit>),
+    Kaseikyo(Vec<Bit>),
     Unknown(()),
 }
 
+impl DecordedInfraredRemoteFrame {
+    /// 復調済みのビット列(リピートは繰り返し元のフレーム)
+    pub fn bits(&self) -> Option<&[Bit]> {
+        match self {
+            DecordedInfraredRemoteFrame::Aeha(bits)
+            | DecordedInfraredRemoteFrame::Nec(bits)
+            | DecordedInfraredRemoteFrame::Sirc(bits)
+            | DecordedInfraredRemoteFrame::SircRepeat { frame: bits, .. }
+            | DecordedInfraredRemoteFrame::Rc5(bits)
+            | DecordedInfraredRemoteFrame::Rc6(bits)
+            | DecordedInfraredRemoteFrame::Samsung(bits)
+            | DecordedInfraredRemoteFrame::Kaseikyo(bits) => Some(bits),
+            DecordedInfraredRemoteFrame::NecRepeat(_)
+            | DecordedInfraredRemoteFrame::NecRepeatSequence { .. }
+            | DecordedInfraredRemoteFrame::Unknown(_) => None,
+        }
+    }
+}
+
 impl fmt::Display for DecordedInfraredRemoteFrame {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             DecordedInfraredRemoteFrame::Aeha(bits) => write!(f, "AEHA {}", show_bit_pattern(bits)),
             DecordedInfraredRemoteFrame::Nec(bits) => write!(f, "NEC {}", show_bit_pattern(bits)),
             DecordedInfraredRemoteFrame::NecRepeat(_) => write!(f, "NEC (repeat)"),
-            DecordedInfraredRemoteFrame::Sirc(bits) => write!(f, "SIRC {}", show_bit_pattern(bits)),
+            DecordedInfraredRemoteFrame::NecRepeatSequence { count } => {
+                write!(f, "NEC (repeat x{})", count)
+            }
+            DecordedInfraredRemoteFrame::Sirc(bits) => {
+                write!(f, "SIRC {}", show_bit_pattern_grouped(bits, 7, ' '))
+            }
+            DecordedInfraredRemoteFrame::SircRepeat { frame, count } => write!(
+                f,
+                "SIRC {} (x{})",
+                show_bit_pattern_grouped(frame, 7, ' '),
+                count
+            ),
+            DecordedInfraredRemoteFrame::Rc5(bits) => write!(f, "RC5 {}", show_bit_pattern(bits)),
+            DecordedInfraredRemoteFrame::Rc6(bits) => write!(f, "RC6 {}", show_bit_pattern(bits)),
+            DecordedInfraredRemoteFrame::Samsung(bits) => {
+                write!(f, "SAMSUNG {}", show_bit_pattern(bits))
+            }
+            DecordedInfraredRemoteFrame::Kaseikyo(bits) => {
+                write!(f, "KASEIKYO {}", show_bit_pattern(bits))
+            }
             DecordedInfraredRemoteFrame::Unknown(_) => write!(f, "Unknown protocol"),
         }
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// 赤外線リモコンプロトコル
+pub enum InfraredRemoteProtocol {
+    #[serde(rename = "NEC")]
+    Nec,
+    #[serde(rename = "AEHA")]
+    Aeha,
+    #[serde(rename = "SIRC")]
+    Sirc,
+    #[serde(rename = "RC5")]
+    Rc5,
+    #[serde(rename = "RC6")]
+    Rc6,
+    #[serde(rename = "SAMSUNG")]
+    Samsung,
+    #[serde(rename = "KASEIKYO")]
+    Kaseikyo,
+    Unknown,
+}
+
+/// 対応プロトコル
+pub const SUPPORTED_PROTOCOLS: [InfraredRemoteProtocol; 7] = [
+    InfraredRemoteProtocol::Nec,
+    InfraredRemoteProtocol::Aeha,
+    InfraredRemoteProtocol::Sirc,
+    InfraredRemoteProtocol::Rc5,
+    InfraredRemoteProtocol::Rc6,
+    InfraredRemoteProtocol::Samsung,
+    InfraredRemoteProtocol::Kaseikyo,
+];
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// プロトコル判定結果
+pub struct DetectedProtocol {
+    pub protocol: InfraredRemoteProtocol,
+    pub frame_count: usize,
+}
+
+/// 復号せずにプロトコルを判定する
+pub fn detect_protocol(
+    data_stream: &[MarkAndSpaceMicros],
+) -> Result<DetectedProtocol, Box<dyn Error>> {
+    // 入力マークアンドスペース列を各フレームに分ける
+    let frames = data_stream
+        .split_inclusive(|ms| THRESHOLD_FRAME_GAP <= ms.space)
+        .collect::<Vec<&[MarkAndSpaceMicros]>>();
+    // 最初のフレームのリーダーパルスで判定する
+    let first_frame = frames
+        .first()
+        .ok_or(InfraredRemoteError::InputIsEmptyError)?;
+    let (leader, trailer) = first_frame
+        .split_first()
+        .ok_or(InfraredRemoteError::InputIsEmptyError)?;
+    let protocol = if protocol_kaseikyo::compare_leader_pulse(TOLERANCE, leader)
+        && protocol_kaseikyo::demodulate_bits(trailer).is_some()
+    {
+        // KASEIKYOはAEHAの一種なので構造が一致した場合のみKASEIKYOとする
+        InfraredRemoteProtocol::Kaseikyo
+    } else if protocol_aeha::compare_leader_pulse(TOLERANCE, leader) {
+        InfraredRemoteProtocol::Aeha
+    } else if protocol_nec::compare_leader_pulse(TOLERANCE, leader)
+        || protocol_nec::compare_repeat_pulse(TOLERANCE, leader)
+    {
+        InfraredRemoteProtocol::Nec
+    } else if protocol_rc6::compare_leader_pulse(TOLERANCE, leader)
+        && protocol_rc6::demodulate_bits(trailer).is_some()
+    {
+        // RC6とSIRCのリーダーパルスは近いので復調できた場合のみRC6とする
+        InfraredRemoteProtocol::Rc6
+    } else if protocol_sirc::compare_leader_pulse(TOLERANCE, leader) {
+        InfraredRemoteProtocol::Sirc
+    } else if protocol_samsung::compare_leader_pulse(TOLERANCE, leader) {
+        InfraredRemoteProtocol::Samsung
+    } else if protocol_rc5::compare_leader_pulse(TOLERANCE, leader) {
+        InfraredRemoteProtocol::Rc5
+    } else {
+        InfraredRemoteProtocol::Unknown
+    };
+    Ok(DetectedProtocol {
+        protocol,
+        frame_count: frames.len(),
+    })
+}
+
+/// 既定のプロトコルの判定順
+pub const DEFAULT_PROTOCOL_PRIORITY: [InfraredRemoteProtocol; 7] = [
+    InfraredRemoteProtocol::Kaseikyo,
+    InfraredRemoteProtocol::Aeha,
+    InfraredRemoteProtocol::Nec,
+    InfraredRemoteProtocol::Rc6,
+    InfraredRemoteProtocol::Sirc,
+    InfraredRemoteProtocol::Samsung,
+    InfraredRemoteProtocol::Rc5,
+];
+
 /// 復号
 pub fn decord_receiving_data(
     data_stream: &[MarkAndSpaceMicros],
+    tolerances: Option<&ProtocolTolerances>,
+) -> Result<Vec<DecordedInfraredRemoteFrame>, Box<dyn Error>> {
+    decord_receiving_data_sub(
+        data_stream,
+        tolerances,
+        &DEFAULT_PROTOCOL_PRIORITY,
+        &protocol_nec::STANDARD_NEC,
+        &protocol_aeha::STANDARD_AEHA,
+    )
+}
+
+/// NECプロトコルのタイミングを指定した復号
+pub fn decord_receiving_data_with_nec(
+    data_stream: &[MarkAndSpaceMicros],
+    tolerances: Option<&ProtocolTolerances>,
+    nec: &protocol_nec::NecProtocol,
+) -> Result<Vec<DecordedInfraredRemoteFrame>, Box<dyn Error>> {
+    decord_receiving_data_sub(
+        data_stream,
+        tolerances,
+        &DEFAULT_PROTOCOL_PRIORITY,
+        nec,
+        &protocol_aeha::STANDARD_AEHA,
+    )
+}
+
+/// AEHAプロトコルの基準時間を指定した復号
+pub fn decord_receiving_data_with_aeha(
+    data_stream: &[MarkAndSpaceMicros],
+    tolerances: Option<&ProtocolTolerances>,
+    aeha: &protocol_aeha::AehaProtocol,
+) -> Result<Vec<DecordedInfraredRemoteFrame>, Box<dyn Error>> {
+    decord_receiving_data_sub(
+        data_stream,
+        tolerances,
+        &DEFAULT_PROTOCOL_PRIORITY,
+        &protocol_nec::STANDARD_NEC,
+        aeha,
+    )
+}
+
+/// 指定した順にプロトコルを試す復号
+pub fn decord_receiving_data_with_priority(
+    data_stream: &[MarkAndSpaceMicros],
+    priority: &[InfraredRemoteProtocol],
 ) -> Result<Vec<DecordedInfraredRemoteFrame>, Box<dyn Error>> {
+    decord_receiving_data_sub(
+        data_stream,
+        None,
+        priority,
+        &protocol_nec::STANDARD_NEC,
+        &protocol_aeha::STANDARD_AEHA,
+    )
+}
+
+fn decord_receiving_data_sub(
+    data_stream: &[MarkAndSpaceMicros],
+    tolerances: Option<&ProtocolTolerances>,
+    priority: &[InfraredRemoteProtocol],
+    nec: &protocol_nec::NecProtocol,
+    aeha: &protocol_aeha::AehaProtocol,
+) -> Result<Vec<DecordedInfraredRemoteFrame>, Box<dyn Error>> {
+    validate_signal(data_stream)?;
+    let tolerances = tolerances.unwrap_or(&DEFAULT_TOLERANCES);
     // 入力マークアンドスペース列を各フレームに分ける
     let frames = data_stream.split_inclusive(|ms| THRESHOLD_FRAME_GAP <= ms.space);
+    // 最後がフレーム間隔で終わっていなければ最後のフレームは途中で切れている
+    let truncated = data_stream
+        .last()
+        .is_some_and(|ms| ms.space < THRESHOLD_FRAME_GAP);
+    let number_of_frames = frames.clone().count();
     // 赤外線信号を復調して赤外線リモコン信号を取り出す
     frames
-        .map(|single_frame| {
-            // リーダーパルスとそれ以外に分ける
-            let (leader, trailer) = single_frame
-                .split_first()
-                .ok_or(InfraredRemoteError::InputIsEmptyError)?;
-            // 信号を復調する
-            if protocol_aeha::compare_leader_pulse(TOLERANCE, leader) {
-                let mut bits = trailer
-                    .iter()
-                    .map(|&item| protocol_aeha::demodulate(item))
-                    .collect::<Vec<Bit>>();
-                let _ = bits.pop(); // remove stop bit
-                Ok(DecordedInfraredRemoteFrame::Aeha(bits))
-            } else if protocol_nec::compare_leader_pulse(TOLERANCE, leader) {
-                let mut bits = trailer
-                    .iter()
-                    .map(|&item| protocol_nec::demodulate(item))
-                    .collect::<Vec<Bit>>();
-                let _ = bits.pop(); // remove stop bit
-                if bits.len() < 32 {
-                    Err(InfraredRemoteError::InsufficientInputData(32, bits.len()).into())
-                } else {
-                    Ok(DecordedInfraredRemoteFrame::Nec(bits))
+        .enumerate()
+        .map(|(index, single_frame)| {
+            let incomplete = truncated && index + 1 == number_of_frames;
+            // リーダーパルスがあることを確かめる
+            if single_frame.is_empty() {
+                return Err(InfraredRemoteError::InputIsEmptyError.into());
+            }
+            // 指定の順にプロトコルを試す
+            priority
+                .iter()
+                .find_map(|&protocol| {
+                    demodulate_frame(protocol, tolerances, nec, aeha, single_frame, incomplete)
+                })
+                .unwrap_or(Ok(DecordedInfraredRemoteFrame::Unknown(())))
+        })
+        .collect::<Result<Vec<DecordedInfraredRemoteFrame>, Box<dyn Error>>>()
+}
+
+/// 指定のプロトコルで1フレームを復調する
+/// リーダーパルスが一致しない場合はNone
+fn demodulate_frame(
+    protocol: InfraredRemoteProtocol,
+    tolerances: &ProtocolTolerances,
+    nec: &protocol_nec::NecProtocol,
+    aeha: &protocol_aeha::AehaProtocol,
+    single_frame: &[MarkAndSpaceMicros],
+    incomplete: bool,
+) -> Option<Result<DecordedInfraredRemoteFrame, Box<dyn Error>>> {
+    // リーダーパルスとそれ以外に分ける
+    let (leader, trailer) = single_frame.split_first()?;
+    match protocol {
+        InfraredRemoteProtocol::Aeha if aeha.compare_leader_pulse(tolerances.aeha, leader) => {
+            let mut bits = trailer
+                .iter()
+                .map(|&item| aeha.demodulate(item))
+                .collect::<Vec<Bit>>();
+            let _ = bits.pop(); // remove stop bit
+            if incomplete {
+                // 途中で切れたフレーム
+                Some(Ok(DecordedInfraredRemoteFrame::Unknown(())))
+            } else if bits.len() % 8 != 0 {
+                // AEHAは8ビット単位
+                Some(Err(InfraredRemoteError::InvalidFrameLength {
+                    protocol: "AEHA",
+                    actual: bits.len(),
+                }
+                .into()))
+            } else {
+                Some(Ok(DecordedInfraredRemoteFrame::Aeha(bits)))
+            }
+        }
+        InfraredRemoteProtocol::Nec if nec.compare_leader_pulse(tolerances.nec, leader) => {
+            let mut bits = trailer
+                .iter()
+                .map(|&item| nec.demodulate(item))
+                .collect::<Vec<Bit>>();
+            let _ = bits.pop(); // remove stop bit
+            if incomplete && bits.len() < 32 {
+                // 途中で切れたフレーム
+                Some(Ok(DecordedInfraredRemoteFrame::Unknown(())))
+            } else if bits.len() < 32 {
+                Some(Err(InfraredRemoteError::InsufficientInputData {
+                    expected: 32,
+                    actual: bits.len(),
                 }
-            } else if protocol_sirc::compare_leader_pulse(TOLERANCE, leader) {
-                let bits = trailer
-                    .iter()
-                    .map(|&item| protocol_sirc::demodulate(item))
-                    .collect::<Vec<Bit>>();
-                Ok(DecordedInfraredRemoteFrame::Sirc(bits))
-            } else if protocol_nec::compare_repeat_pulse(TOLERANCE, leader) {
-                Ok(DecordedInfraredRemoteFrame::NecRepeat(()))
+                .into()))
             } else {
-                Ok(DecordedInfraredRemoteFrame::Unknown(()))
+                Some(Ok(DecordedInfraredRemoteFrame::Nec(bits)))
             }
+        }
+        InfraredRemoteProtocol::Nec
+            if protocol_nec::compare_repeat_pulse(tolerances.nec, leader) =>
+        {
+            Some(Ok(DecordedInfraredRemoteFrame::NecRepeat(())))
+        }
+        InfraredRemoteProtocol::Rc6 if protocol_rc6::compare_leader_pulse(TOLERANCE, leader) => {
+            // RC6とSIRCのリーダーパルスは近いので復調できた場合のみRC6とする
+            protocol_rc6::demodulate_bits(trailer)
+                .map(|bits| Ok(DecordedInfraredRemoteFrame::Rc6(bits)))
+        }
+        InfraredRemoteProtocol::Sirc
+            if protocol_sirc::compare_leader_pulse(tolerances.sirc, leader) =>
+        {
+            let bits = trailer
+                .iter()
+                .map(|&item| protocol_sirc::demodulate(item))
+                .collect::<Vec<Bit>>();
+            if incomplete {
+                // 途中で切れたフレーム
+                Some(Ok(DecordedInfraredRemoteFrame::Unknown(())))
+            } else if [12, 15, 20].contains(&bits.len()) {
+                // SIRCは12, 15, 20ビットのいずれか
+                Some(Ok(DecordedInfraredRemoteFrame::Sirc(bits)))
+            } else {
+                Some(Err(InfraredRemoteError::InvalidFrameLength {
+                    protocol: "SIRC",
+                    actual: bits.len(),
+                }
+                .into()))
+            }
+        }
+        InfraredRemoteProtocol::Samsung
+            if protocol_samsung::compare_leader_pulse(TOLERANCE, leader) =>
+        {
+            let mut bits = trailer
+                .iter()
+                .map(|&item| protocol_samsung::demodulate(item))
+                .collect::<Vec<Bit>>();
+            let _ = bits.pop(); // remove stop bit
+            Some(Ok(DecordedInfraredRemoteFrame::Samsung(bits)))
+        }
+        InfraredRemoteProtocol::Kaseikyo
+            if protocol_kaseikyo::compare_leader_pulse(tolerances.aeha, leader) =>
+        {
+            // 構造が一致しなければ次のプロトコル(AEHA)を試す
+            protocol_kaseikyo::demodulate_bits(trailer)
+                .map(|bits| Ok(DecordedInfraredRemoteFrame::Kaseikyo(bits)))
+        }
+        InfraredRemoteProtocol::Rc5 if protocol_rc5::compare_leader_pulse(TOLERANCE, leader) => {
+            // RC5はリーダーパルスもデータの一部
+            Some(Ok(protocol_rc5::demodulate_bits(single_frame)
+                .map(DecordedInfraredRemoteFrame::Rc5)
+                .unwrap_or(DecordedInfraredRemoteFrame::Unknown(()))))
+        }
+        _ => None,
+    }
+}
+
+/// マークとスペースが反転した(負論理の)信号の復号
+pub fn decord_receiving_data_inverted(
+    data_stream: &[MarkAndSpaceMicros],
+) -> Result<Vec<DecordedInfraredRemoteFrame>, Box<dyn Error>> {
+    let swapped = data_stream
+        .iter()
+        .map(|ms| MarkAndSpace {
+            mark: ms.space,
+            space: ms.mark,
         })
-        .collect::<Result<Vec<DecordedInfraredRemoteFrame>, Box<dyn Error>>>()
+        .collect::<Vec<MarkAndSpaceMicros>>();
+    decord_receiving_data(&swapped, None)
+}
+
+/// NECフレームの反転バイトを確かめる復号
+pub fn decord_receiving_data_strict(
+    data_stream: &[MarkAndSpaceMicros],
+    tolerances: Option<&ProtocolTolerances>,
+) -> Result<Vec<DecordedInfraredRemoteFrame>, Box<dyn Error>> {
+    let frames = decord_receiving_data(data_stream, tolerances)?;
+    for frame in frames.iter() {
+        if let DecordedInfraredRemoteFrame::Nec(bits) = frame {
+            if !protocol_nec::verify_complement(bits) {
+                return Err(InfraredRemoteError::NecComplementMismatch.into());
+            }
+        }
+    }
+    Ok(frames)
+}
+
+/// 連続するNECリピートを回数付きの1つにまとめる
+pub fn collapse_nec_repeats(
+    frames: &[DecordedInfraredRemoteFrame],
+) -> Vec<DecordedInfraredRemoteFrame> {
+    let mut collapsed: Vec<DecordedInfraredRemoteFrame> = Vec::new();
+    for frame in frames.iter() {
+        let increment = match frame {
+            DecordedInfraredRemoteFrame::NecRepeat(_) => 1,
+            DecordedInfraredRemoteFrame::NecRepeatSequence { count } => *count,
+            _ => {
+                collapsed.push(frame.clone());
+                continue;
+            }
+        };
+        match collapsed.last_mut() {
+            Some(DecordedInfraredRemoteFrame::NecRepeatSequence { count }) => *count += increment,
+            _ => {
+                collapsed.push(DecordedInfraredRemoteFrame::NecRepeatSequence { count: increment })
+            }
+        }
+    }
+    collapsed
+}
+
+/// 連続する同じSIRCフレームを回数付きの1つにまとめる
+/// (SIRCは同じフレームを繰り返し送信する)
+pub fn collapse_sirc_repeats(
+    frames: &[DecordedInfraredRemoteFrame],
+) -> Vec<DecordedInfraredRemoteFrame> {
+    let mut collapsed: Vec<DecordedInfraredRemoteFrame> = Vec::new();
+    for frame in frames.iter() {
+        let (bits, increment) = match frame {
+            DecordedInfraredRemoteFrame::Sirc(bits) => (bits, 1),
+            DecordedInfraredRemoteFrame::SircRepeat { frame, count } => (frame, *count),
+            _ => {
+                collapsed.push(frame.clone());
+                continue;
+            }
+        };
+        // 直前が同じSIRCフレームならその回数に加える
+        let previous = match collapsed.last() {
+            Some(DecordedInfraredRemoteFrame::Sirc(last)) if last == bits => Some(1),
+            Some(DecordedInfraredRemoteFrame::SircRepeat { frame, count }) if frame == bits => {
+                Some(*count)
+            }
+            _ => None,
+        };
+        match previous {
+            Some(count) => {
+                collapsed.pop();
+                collapsed.push(DecordedInfraredRemoteFrame::SircRepeat {
+                    frame: bits.clone(),
+                    count: count + increment,
+                });
+            }
+            None => collapsed.push(frame.clone()),
+        }
+    }
+    collapsed
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// フレームの構造検査の結果
+pub struct FrameValidation {
+    pub valid: bool,
+    pub errors: Vec<String>,
+}
+
+/// 機器毎の復号の前にフレームの構造を検査する
+/// ビット数がプロトコルの規定に合わないフレームと, 異なるプロトコルのフレームの混在を報告する
+pub fn validate_frames(frames: &[DecordedInfraredRemoteFrame]) -> FrameValidation {
+    let mut errors: Vec<String> = Vec::new();
+    for (index, frame) in frames.iter().enumerate() {
+        match frame {
+            // Whirlpoolは48ビット
+            DecordedInfraredRemoteFrame::Nec(bits) if ![32, 48].contains(&bits.len()) => errors
+                .push(format!(
+                    "frame {}: NEC bit count must be 32 or 48. (actual {})",
+                    index,
+                    bits.len()
+                )),
+            DecordedInfraredRemoteFrame::Aeha(bits) if bits.is_empty() || bits.len() % 8 != 0 => {
+                errors.push(format!(
+                    "frame {}: AEHA bit count must be a multiple of 8. (actual {})",
+                    index,
+                    bits.len()
+                ))
+            }
+            DecordedInfraredRemoteFrame::Sirc(bits)
+            | DecordedInfraredRemoteFrame::SircRepeat { frame: bits, .. }
+                if ![12, 15, 20].contains(&bits.len()) =>
+            {
+                errors.push(format!(
+                    "frame {}: SIRC bit count must be 12, 15 or 20. (actual {})",
+                    index,
+                    bits.len()
+                ))
+            }
+            DecordedInfraredRemoteFrame::Unknown(_) => {
+                errors.push(format!("frame {}: unknown protocol.", index))
+            }
+            _ => {}
+        }
+    }
+    // リピートは元のフレームと同じプロトコルとみなす
+    let mut protocols = frames
+        .iter()
+        .filter_map(|frame| match frame {
+            DecordedInfraredRemoteFrame::Aeha(_) => Some("AEHA"),
+            DecordedInfraredRemoteFrame::Nec(_)
+            | DecordedInfraredRemoteFrame::NecRepeat(_)
+            | DecordedInfraredRemoteFrame::NecRepeatSequence { .. } => Some("NEC"),
+            DecordedInfraredRemoteFrame::Sirc(_)
+            | DecordedInfraredRemoteFrame::SircRepeat { .. } => Some("SIRC"),
+            DecordedInfraredRemoteFrame::Rc5(_) => Some("RC5"),
+            DecordedInfraredRemoteFrame::Rc6(_) => Some("RC6"),
+            DecordedInfraredRemoteFrame::Samsung(_) => Some("SAMSUNG"),
+            DecordedInfraredRemoteFrame::Kaseikyo(_) => Some("KASEIKYO"),
+            DecordedInfraredRemoteFrame::Unknown(_) => None,
+        })
+        .collect::<Vec<&str>>();
+    protocols.sort();
+    protocols.dedup();
+    if 1 < protocols.len() {
+        errors.push(format!(
+            "frames of different protocols are mixed. ({})",
+            protocols.join(", ")
+        ));
+    }
+    FrameValidation {
+        valid: errors.is_empty(),
+        errors,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// 信号品質付きの復号後の赤外線リモコン信号
+pub struct DecodedFrameWithQuality {
+    pub frame: DecordedInfraredRemoteFrame,
+    pub quality: f32,
+}
+
+/// 信号品質
+/// 1.0から各マーク, スペースの理想値からのずれの割合の平均を引いた値(0.0 - 1.0)
+fn signal_quality(frame: &DecordedInfraredRemoteFrame, single_frame: &[MarkAndSpaceMicros]) -> f32 {
+    let Microseconds(time_base) = match frame {
+        DecordedInfraredRemoteFrame::Aeha(_) => protocol_aeha::TIME_BASE,
+        DecordedInfraredRemoteFrame::Nec(_) => protocol_nec::TIME_BASE,
+        DecordedInfraredRemoteFrame::NecRepeat(_) => protocol_nec::TIME_BASE,
+        DecordedInfraredRemoteFrame::NecRepeatSequence { .. } => protocol_nec::TIME_BASE,
+        DecordedInfraredRemoteFrame::Sirc(_) => protocol_sirc::TIME_BASE,
+        DecordedInfraredRemoteFrame::SircRepeat { .. } => protocol_sirc::TIME_BASE,
+        DecordedInfraredRemoteFrame::Rc5(_) => protocol_rc5::TIME_BASE,
+        DecordedInfraredRemoteFrame::Rc6(_) => protocol_rc6::TIME_BASE,
+        DecordedInfraredRemoteFrame::Samsung(_) => protocol_samsung::TIME_BASE,
+        DecordedInfraredRemoteFrame::Kaseikyo(_) => protocol_kaseikyo::TIME_BASE,
+        DecordedInfraredRemoteFrame::Unknown(_) => return 0.0,
+    };
+    // 最後のスペースはフレーム間の間隔なので除く
+    let durations = single_frame
+        .iter()
+        .flat_map(|ms| [ms.mark, ms.space])
+        .take((single_frame.len() * 2).saturating_sub(1))
+        .collect::<Vec<Microseconds>>();
+    if durations.is_empty() {
+        return 0.0;
+    }
+    // 時間単位の整数倍を理想値とする
+    let deviation_sum = durations
+        .iter()
+        .map(|&Microseconds(t)| {
+            let ideal = ((t as f32 / time_base as f32).round()).max(1.0) * time_base as f32;
+            (t as f32 - ideal).abs() / ideal
+        })
+        .sum::<f32>();
+    (1.0 - deviation_sum / durations.len() as f32).clamp(0.0, 1.0)
+}
+
+/// 信号品質付きの復号
+pub fn decord_receiving_data_with_quality(
+    data_stream: &[MarkAndSpaceMicros],
+) -> Result<Vec<DecodedFrameWithQuality>, Box<dyn Error>> {
+    // 入力マークアンドスペース列を各フレームに分ける
+    let frames = data_stream.split_inclusive(|ms| THRESHOLD_FRAME_GAP <= ms.space);
+    frames
+        .map(|single_frame| {
+            let frame = decord_receiving_data(single_frame, None)?
+                .pop()
+                .ok_or(InfraredRemoteError::InputIsEmptyError)?;
+            let quality = signal_quality(&frame, single_frame);
+            Ok(DecodedFrameWithQuality { frame, quality })
+        })
+        .collect::<Result<Vec<DecodedFrameWithQuality>, Box<dyn Error>>>()
+}
+
+/// プロトコル毎のタイミング表
+fn timing_table(protocol: InfraredRemoteProtocol) -> &'static [MarkAndSpaceMicros] {
+    match protocol {
+        InfraredRemoteProtocol::Nec => &[
+            protocol_nec::LEADER,
+            protocol_nec::REPEAT,
+            protocol_nec::TYPICAL_BIT_ZERO,
+            protocol_nec::TYPICAL_BIT_ONE,
+            protocol_nec::STOP_BIT,
+        ],
+        InfraredRemoteProtocol::Aeha => &[
+            protocol_aeha::LEADER,
+            protocol_aeha::TYPICAL_BIT_ZERO,
+            protocol_aeha::TYPICAL_BIT_ONE,
+            protocol_aeha::STOP_BIT,
+        ],
+        InfraredRemoteProtocol::Sirc => &[
+            protocol_sirc::LEADER,
+            protocol_sirc::TYPICAL_BIT_ZERO,
+            protocol_sirc::TYPICAL_BIT_ONE,
+        ],
+        _ => &[],
+    }
+}
+
+/// マーク, スペースをプロトコルのタイミング表の最も近い値に揃える
+/// フレーム間の間隔と, タイミング表の無いプロトコルの信号はそのままにする
+pub fn normalize_to_protocol(
+    signals: &[MarkAndSpaceMicros],
+    protocol: InfraredRemoteProtocol,
+) -> Vec<MarkAndSpaceMicros> {
+    let table = timing_table(protocol);
+    let nearest = |t: Microseconds, candidates: &mut dyn Iterator<Item = Microseconds>| {
+        candidates
+            .min_by_key(|&Microseconds(c)| c.abs_diff(t.0))
+            .unwrap_or(t)
+    };
+    signals
+        .iter()
+        .map(|ms| MarkAndSpace {
+            mark: nearest(ms.mark, &mut table.iter().map(|x| x.mark)),
+            space: if THRESHOLD_FRAME_GAP <= ms.space {
+                ms.space
+            } else {
+                nearest(ms.space, &mut table.iter().map(|x| x.space))
+            },
+        })
+        .collect()
+}
+
+/// 逐次復号器
+/// マークアンドスペースを1つずつ受け取り, フレーム間の間隔を受け取った時点でそのフレームを復号する
+#[derive(Clone, Debug, Default)]
+pub struct IrDecodeStream {
+    buffer: Vec<MarkAndSpaceMicros>,
+    tolerances: Option<ProtocolTolerances>,
+    decoded: VecDeque<DecordedInfraredRemoteFrame>,
+}
+
+impl IrDecodeStream {
+    pub fn new(tolerances: Option<ProtocolTolerances>) -> Self {
+        IrDecodeStream {
+            tolerances,
+            ..Default::default()
+        }
+    }
+
+    /// マークアンドスペースを追加する
+    pub fn push(&mut self, ms: MarkAndSpaceMicros) {
+        self.buffer.push(ms);
+        if THRESHOLD_FRAME_GAP <= ms.space {
+            let single_frame = std::mem::take(&mut self.buffer);
+            // 復号できないフレームは不明とする
+            let frames = decord_receiving_data(&single_frame, self.tolerances.as_ref())
+                .unwrap_or_else(|_| vec![DecordedInfraredRemoteFrame::Unknown(())]);
+            self.decoded.extend(frames);
+        }
+    }
+}
+
+impl Iterator for IrDecodeStream {
+    type Item = DecordedInfraredRemoteFrame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.decoded.pop_front()
+    }
+}
+
+type DemodulateFn = fn(MarkAndSpaceMicros) -> Bit;
+type CompareLeaderPulseFn = fn(Microseconds, &MarkAndSpaceMicros) -> bool;
+
+/// 逐次復調器
+/// マークアンドスペースを1つずつ受け取り, 復調したビットを順に返す
+/// リーダーパルスとストップビットは読み飛ばす
+/// NECのリピートパルスはビットにせず回数を数える
+#[derive(Clone, Debug)]
+pub struct MarkAndSpaceDemodulator {
+    protocol: InfraredRemoteProtocol,
+    demodulate: DemodulateFn,
+    compare_leader_pulse: CompareLeaderPulseFn,
+    /// フレームの最後にストップビットがあるか
+    has_stop_bit: bool,
+    /// ストップビットかもしれないので保留しているマークアンドスペース
+    pending: Option<MarkAndSpaceMicros>,
+    demodulated: VecDeque<Bit>,
+    /// 受け取ったNECリピートパルスの数
+    repeats: usize,
+}
+
+impl MarkAndSpaceDemodulator {
+    /// 1つのマークアンドスペースで1ビットを表すプロトコルのみ
+    /// KASEIKYO, RC5, RC6はNone
+    pub fn new(protocol: InfraredRemoteProtocol) -> Option<Self> {
+        let (demodulate, compare_leader_pulse, has_stop_bit): (
+            DemodulateFn,
+            CompareLeaderPulseFn,
+            bool,
+        ) = match protocol {
+            InfraredRemoteProtocol::Aeha => (
+                protocol_aeha::demodulate,
+                protocol_aeha::compare_leader_pulse,
+                true,
+            ),
+            InfraredRemoteProtocol::Nec => (
+                protocol_nec::demodulate,
+                protocol_nec::compare_leader_pulse,
+                true,
+            ),
+            InfraredRemoteProtocol::Sirc => (
+                protocol_sirc::demodulate,
+                protocol_sirc::compare_leader_pulse,
+                false,
+            ),
+            InfraredRemoteProtocol::Samsung => (
+                protocol_samsung::demodulate,
+                protocol_samsung::compare_leader_pulse,
+                true,
+            ),
+            // KASEIKYOはAEHAとして復調する
+            InfraredRemoteProtocol::Kaseikyo
+            | InfraredRemoteProtocol::Rc5
+            | InfraredRemoteProtocol::Rc6
+            | InfraredRemoteProtocol::Unknown => return None,
+        };
+        Some(MarkAndSpaceDemodulator {
+            protocol,
+            demodulate,
+            compare_leader_pulse,
+            has_stop_bit,
+            pending: None,
+            demodulated: VecDeque::new(),
+            repeats: 0,
+        })
+    }
+
+    pub fn protocol(&self) -> InfraredRemoteProtocol {
+        self.protocol
+    }
+
+    /// 受け取ったNECリピートパルスの数
+    pub fn repeat_count(&self) -> usize {
+        self.repeats
+    }
+
+    /// マークアンドスペースを追加する
+    pub fn feed(&mut self, ms: MarkAndSpaceMicros) {
+        if (self.compare_leader_pulse)(TOLERANCE, &ms) {
+            // 保留していたのは前のフレームのストップビット
+            self.pending = None;
+            return;
+        }
+        if self.protocol == InfraredRemoteProtocol::Nec
+            && protocol_nec::compare_repeat_pulse(TOLERANCE, &ms)
+        {
+            // リピートパルスに続くストップビットにはフレーム間の間隔がある
+            self.pending = None;
+            self.repeats += 1;
+            return;
+        }
+        if !self.has_stop_bit {
+            self.demodulated.push_back((self.demodulate)(ms));
+            return;
+        }
+        if let Some(prev) = self.pending.take() {
+            self.demodulated.push_back((self.demodulate)(prev));
+        }
+        // フレーム間の間隔があればストップビット
+        if ms.space < THRESHOLD_FRAME_GAP {
+            self.pending = Some(ms);
+        }
+    }
+}
+
+impl Iterator for MarkAndSpaceDemodulator {
+    type Item = Bit;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.demodulated.pop_front()
+    }
 }
 
 #[cfg(test)]
@@ -83,7 +806,7 @@ mod decord_ir_data_stream_tests {
     #[test]
     fn test1() -> Result<(), Box<dyn Error>> {
         let source= crate::parsing::parse_infrared_code_text("5B0018002E001800180018002E001800170018002E00190017001800170018002E00180018001800170018001700180017004F03")?;
-        let result = decord_receiving_data(&source)?;
+        let result = decord_receiving_data(&source, None)?;
         let expected = vec![DecordedInfraredRemoteFrame::Sirc(
             vec![1, 0, 1, 0, 1, 0, 0, 1, 0, 0, 0, 0]
                 .into_iter()
@@ -97,7 +820,7 @@ mod decord_ir_data_stream_tests {
     #[test]
     fn test2() -> Result<(), Box<dyn Error>> {
         let source= crate::parsing::parse_infrared_code_text("5601A900180015001800140018001400190013001900140019001400170040001700150018003F0019003E0018003E0019003F0019003E00170040001800140019003E001800150018003F00180014001800140019003F0018001400170016001700150018003F001800140018003F0018003F001800140019003F0018003F0018003E0019004F03")?;
-        let result = decord_receiving_data(&source)?;
+        let result = decord_receiving_data(&source, None)?;
         let expected = vec![DecordedInfraredRemoteFrame::Nec(
             vec![
                 0, 0, 0, 0, 0, 0, 1, 0, 1, 1, 1, 1, 1, 1, 0, 1, 0, 1, 0, 0, 1, 0, 0, 0, 1, 0, 1, 1,
@@ -114,8 +837,9 @@ mod decord_ir_data_stream_tests {
     #[test]
     fn test3() -> Result<(), Box<dyn Error>> {
         let source= crate::parsing::parse_infrared_code_text("8700410014000F0014002F001400100013001000130010001300100013000F0014000F0014000F001300100014000E00140010001300100013002F001400100013001000130010001300100013000F0015000E0014000F0013001000130010001300300014000F0014000F0014000F0013001000130010001300100013000F0014000F0014002F00140010001300300014002F0015002F00150030001400100013000F0014002F00140010001300300014002F0015002F00140030001400100013002F0015004F03")?;
-        let result = decord_receiving_data(&source)?;
-        let expected = vec![DecordedInfraredRemoteFrame::Aeha(
+        let result = decord_receiving_data(&source, None)?;
+        // パナソニックのテレビ(KASEIKYO)
+        let expected = vec![DecordedInfraredRemoteFrame::Kaseikyo(
             vec![
                 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0,
                 0, 0, 0, 0, 1, 0, 1, 1, 1, 1, 0, 0, 1, 0, 1, 1, 1, 1, 0, 1,
@@ -127,4 +851,500 @@ mod decord_ir_data_stream_tests {
         assert_eq!(result, expected);
         Ok(())
     }
+
+    #[test]
+    fn test4() -> Result<(), Box<dyn Error>> {
+        let source = crate::parsing::parse_infrared_code_text("[889,889,1778,889,889,889,889,889,889,889,889,889,889,889,889,889,889,1778,889,889,1778,889,889,89000]")?;
+        let result = decord_receiving_data(&source, None)?;
+        let expected = vec![DecordedInfraredRemoteFrame::Rc5(vec_bits!(
+            "11", "0", "00000", "001100"
+        ))];
+        assert_eq!(result, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test5() -> Result<(), Box<dyn Error>> {
+        let source = crate::parsing::parse_infrared_code_text("[2664,888,444,888,444,444,444,444,444,888,888,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,888,444,444,888,444,444,444,83000]")?;
+        let result = decord_receiving_data(&source, None)?;
+        let expected = vec![DecordedInfraredRemoteFrame::Rc6(vec_bits!(
+            "1", "000", "0", "00000000", "00001100"
+        ))];
+        assert_eq!(result, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test6() -> Result<(), Box<dyn Error>> {
+        let testcases = [
+            ("5B0018002E001800180018002E001800170018002E00190017001800170018002E00180018001800170018001700180017004F03", InfraredRemoteProtocol::Sirc),
+            ("5601A900180015001800140018001400190013001900140019001400170040001700150018003F0019003E0018003E0019003F0019003E00170040001800140019003E001800150018003F00180014001800140019003F0018001400170016001700150018003F001800140018003F0018003F001800140019003F0018003F0018003E0019004F03", InfraredRemoteProtocol::Nec),
+            ("8700410014000F0014002F001400100013001000130010001300100013000F0014000F0014000F001300100014000E00140010001300100013002F001400100013001000130010001300100013000F0015000E0014000F0013001000130010001300300014000F0014000F0014000F0013001000130010001300100013000F0014000F0014002F00140010001300300014002F0015002F00150030001400100013000F0014002F00140010001300300014002F0015002F00140030001400100013002F0015004F03", InfraredRemoteProtocol::Kaseikyo),
+            ("[889,889,1778,889,889,889,889,889,889,889,889,889,889,889,889,889,889,1778,889,889,1778,889,889,89000]", InfraredRemoteProtocol::Rc5),
+            ("[2664,888,444,888,444,444,444,444,444,888,888,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,888,444,444,888,444,444,444,83000]", InfraredRemoteProtocol::Rc6),
+            ("[5000,5000,500,500]", InfraredRemoteProtocol::Unknown),
+        ];
+        for (input, protocol) in testcases {
+            let source = crate::parsing::parse_infrared_code_text(input)?;
+            let result = detect_protocol(&source)?;
+            let expected = DetectedProtocol {
+                protocol,
+                frame_count: 1,
+            };
+            assert_eq!(result, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test7() -> Result<(), Box<dyn Error>> {
+        // NECのリピート
+        let source = crate::parsing::parse_infrared_code_text(
+            "[9000,4500,560,40000,9000,2250,560,96000,9000,2250,560,96000]",
+        )?;
+        let result = detect_protocol(&source)?;
+        let expected = DetectedProtocol {
+            protocol: InfraredRemoteProtocol::Nec,
+            frame_count: 3,
+        };
+        assert_eq!(result, expected);
+        assert!(detect_protocol(&[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test8() -> Result<(), Box<dyn Error>> {
+        // リーダーパルスが400us短いSIRC
+        let source = crate::parsing::parse_infrared_code_text(
+            "[2000,600,1200,600,600,600,1200,600,600,600,1200,600,600,600,600,600,1200,600,600,600,600,600,600,600,600,20000]",
+        )?;
+        let result = decord_receiving_data(&source, None)?;
+        assert_eq!(result, vec![DecordedInfraredRemoteFrame::Unknown(())]);
+        //
+        let tolerances = ProtocolTolerances {
+            sirc: Microseconds(500),
+            ..DEFAULT_TOLERANCES
+        };
+        let result = decord_receiving_data(&source, Some(&tolerances))?;
+        let expected = vec![DecordedInfraredRemoteFrame::Sirc(vec_bits!(
+            "1010100", "10000"
+        ))];
+        assert_eq!(result, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test9() -> Result<(), Box<dyn Error>> {
+        let source = protocol_nec::encode_nec([0x40, 0xbf, 0x12, 0xed]);
+        let result = decord_receiving_data_with_quality(&source)?;
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].frame, decord_receiving_data(&source, None)?[0]);
+        assert_eq!(result[0].quality, 1.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test10() -> Result<(), Box<dyn Error>> {
+        // 150usずつずらす
+        let source = protocol_nec::encode_nec([0x40, 0xbf, 0x12, 0xed])
+            .into_iter()
+            .enumerate()
+            .map(|(n, ms)| {
+                let Microseconds(mark) = ms.mark;
+                let Microseconds(space) = ms.space;
+                if n % 2 == 0 {
+                    MarkAndSpaceMicros::from((Microseconds(mark + 150), Microseconds(space - 150)))
+                } else {
+                    MarkAndSpaceMicros::from((Microseconds(mark - 150), Microseconds(space + 150)))
+                }
+            })
+            .collect::<Vec<MarkAndSpaceMicros>>();
+        let result = decord_receiving_data_with_quality(&source)?;
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].frame, decord_receiving_data(&source, None)?[0]);
+        assert!(result[0].quality < 0.9);
+        //
+        let unknown = MarkAndSpaceMicros::from((Microseconds(5000), Microseconds(5000)));
+        let result = decord_receiving_data_with_quality(&[unknown, unknown])?;
+        assert_eq!(result[0].quality, 0.0);
+        // 要素が1つでは復号しない
+        assert!(decord_receiving_data_with_quality(&[unknown]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test11() -> Result<(), Box<dyn Error>> {
+        let first = protocol_nec::encode_nec([0x40, 0xbf, 0x12, 0xed]);
+        let second = protocol_aeha::encode_frame(&[0x02, 0x20, 0x80, 0x00, 0x3d, 0xc2]);
+        let mut stream = IrDecodeStream::new(None);
+        // フレーム間の間隔を受け取るまでは復号しない
+        let (last, init) = first.split_last().unwrap();
+        init.iter().for_each(|&ms| stream.push(ms));
+        assert_eq!(stream.next(), None);
+        stream.push(*last);
+        assert_eq!(stream.next(), decord_receiving_data(&first, None)?.pop());
+        assert_eq!(stream.next(), None);
+        //
+        second
+            .iter()
+            .chain(first.iter())
+            .for_each(|&ms| stream.push(ms));
+        let result = stream.collect::<Vec<DecordedInfraredRemoteFrame>>();
+        let expected = decord_receiving_data(&[second, first].concat(), None)?;
+        assert_eq!(result, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test12() {
+        // 復号できないフレーム
+        let mut stream = IrDecodeStream::new(None);
+        stream.push(protocol_nec::LEADER);
+        stream.push(protocol_nec::STOP_BIT);
+        assert_eq!(
+            stream.next(),
+            Some(DecordedInfraredRemoteFrame::Unknown(()))
+        );
+    }
+
+    #[test]
+    fn test13() -> Result<(), Box<dyn Error>> {
+        let source = protocol_nec::encode_nec([0x40, 0xbf, 0x12, 0xed]);
+        let inverted = source
+            .iter()
+            .map(|ms| MarkAndSpaceMicros::from((ms.space, ms.mark)))
+            .collect::<Vec<MarkAndSpaceMicros>>();
+        // そのままでは復号できない
+        assert!(decord_receiving_data(&inverted, None)?
+            .iter()
+            .all(|frame| *frame == DecordedInfraredRemoteFrame::Unknown(())));
+        let result = decord_receiving_data_inverted(&inverted)?;
+        assert_eq!(result, decord_receiving_data(&source, None)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test14() {
+        let source = protocol_nec::encode_nec([0x40, 0xbf, 0x12, 0xed]);
+        // ±100usずつずらす
+        let jittered = source
+            .iter()
+            .enumerate()
+            .map(|(n, ms)| {
+                let Microseconds(mark) = ms.mark;
+                let Microseconds(space) = ms.space;
+                if n % 2 == 0 {
+                    MarkAndSpaceMicros::from((Microseconds(mark + 100), Microseconds(space - 100)))
+                } else {
+                    MarkAndSpaceMicros::from((Microseconds(mark - 100), Microseconds(space + 100)))
+                }
+            })
+            .collect::<Vec<MarkAndSpaceMicros>>();
+        let result = normalize_to_protocol(&jittered, InfraredRemoteProtocol::Nec);
+        assert_eq!(result[0], protocol_nec::LEADER);
+        assert!(
+            result[1..33]
+                .iter()
+                .all(|&ms| ms == protocol_nec::TYPICAL_BIT_ZERO
+                    || ms == protocol_nec::TYPICAL_BIT_ONE)
+        );
+        // フレーム間の間隔はそのまま
+        assert_eq!(result[33].mark, protocol_nec::STOP_BIT.mark);
+        assert_eq!(result[33].space, jittered[33].space);
+        assert_eq!(result[..33], source[..33]);
+        // タイミング表の無いプロトコル
+        assert_eq!(
+            normalize_to_protocol(&jittered, InfraredRemoteProtocol::Rc5),
+            jittered
+        );
+    }
+
+    #[test]
+    fn test15() {
+        // 1ビット足りないNECフレーム
+        let mut source = protocol_nec::encode_nec([0x40, 0xbf, 0x12, 0xed]);
+        source.remove(32);
+        let result = decord_receiving_data(&source, None);
+        let error = result.unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<InfraredRemoteError>(),
+            Some(&InfraredRemoteError::InsufficientInputData {
+                expected: 32,
+                actual: 31
+            })
+        );
+        assert_eq!(
+            error.to_string(),
+            "insufficient input data. (expected 32 bits, actual 31 bits)"
+        );
+    }
+
+    #[test]
+    fn test16() {
+        // 第2フレームの途中で切れたAEHA信号
+        let markandspaces =
+            panasonic_hvac::encode("hvac_mode_cool", "power_on", 26, "auto", "auto", "normal")
+                .unwrap();
+        let first_frame_len = markandspaces
+            .iter()
+            .position(|ms| THRESHOLD_FRAME_GAP <= ms.space)
+            .unwrap()
+            + 1;
+        let truncated = &markandspaces[..first_frame_len + 21];
+        let frames = decord_receiving_data(truncated, None).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert!(matches!(frames[0], DecordedInfraredRemoteFrame::Aeha(_)));
+        assert_eq!(frames[1], DecordedInfraredRemoteFrame::Unknown(()));
+        // 途中で切れたNEC信号
+        let source = protocol_nec::encode_nec([0x40, 0xbf, 0x12, 0xed]);
+        let frames = decord_receiving_data(&source[..20], None).unwrap();
+        assert_eq!(frames, vec![DecordedInfraredRemoteFrame::Unknown(())]);
+    }
+
+    #[test]
+    fn test17() {
+        let source = protocol_nec::encode_nec([0x40, 0xbf, 0x12, 0xed]);
+        // 既定の判定順では従来と同じ
+        assert_eq!(
+            decord_receiving_data_with_priority(&source, &DEFAULT_PROTOCOL_PRIORITY).unwrap(),
+            decord_receiving_data(&source, None).unwrap()
+        );
+        // 判定順に含まれないプロトコルは不明
+        let priority = [InfraredRemoteProtocol::Aeha, InfraredRemoteProtocol::Sirc];
+        assert_eq!(
+            decord_receiving_data_with_priority(&source, &priority).unwrap(),
+            vec![DecordedInfraredRemoteFrame::Unknown(())]
+        );
+        // 順序を入れ替えても一致したプロトコルで復号する
+        let priority = [InfraredRemoteProtocol::Sirc, InfraredRemoteProtocol::Nec];
+        let frames = decord_receiving_data_with_priority(&source, &priority).unwrap();
+        assert!(matches!(frames[0], DecordedInfraredRemoteFrame::Nec(_)));
+    }
+
+    #[test]
+    fn test18() {
+        // 31ビットのNEC
+        let mut source = protocol_nec::encode_nec([0x40, 0xbf, 0x12, 0xed]);
+        source.remove(5);
+        assert_eq!(
+            decord_receiving_data(&source, None)
+                .unwrap_err()
+                .to_string(),
+            InfraredRemoteError::InsufficientInputData {
+                expected: 32,
+                actual: 31
+            }
+            .to_string()
+        );
+        // 8ビット単位でないAEHA
+        let mut source = protocol_aeha::encode_frame(&[0x02, 0x20, 0xe0]);
+        source.remove(5);
+        assert_eq!(
+            decord_receiving_data(&source, None)
+                .unwrap_err()
+                .to_string(),
+            InfraredRemoteError::InvalidFrameLength {
+                protocol: "AEHA",
+                actual: 23
+            }
+            .to_string()
+        );
+        // 12, 15, 20ビットのいずれでもないSIRC
+        let mut source = protocol_sirc::encode_sirc12(21, 1);
+        source.remove(5);
+        assert_eq!(
+            decord_receiving_data(&source, None)
+                .unwrap_err()
+                .to_string(),
+            InfraredRemoteError::InvalidFrameLength {
+                protocol: "SIRC",
+                actual: 11
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test19() {
+        let mut source = protocol_nec::encode_nec([0x40, 0xbf, 0x12, 0xed]);
+        (0..5).for_each(|_| source.extend(protocol_nec::encode_nec_repeat()));
+        let frames = decord_receiving_data(&source, None).unwrap();
+        assert_eq!(frames.len(), 6);
+        let result = collapse_nec_repeats(&frames);
+        assert_eq!(
+            result,
+            vec![
+                frames[0].clone(),
+                DecordedInfraredRemoteFrame::NecRepeatSequence { count: 5 }
+            ]
+        );
+        assert_eq!(result[1].to_string(), "NEC (repeat x5)");
+        // 間に別のフレームがあればまとめない
+        let frames = [
+            DecordedInfraredRemoteFrame::NecRepeat(()),
+            DecordedInfraredRemoteFrame::Unknown(()),
+            DecordedInfraredRemoteFrame::NecRepeat(()),
+            DecordedInfraredRemoteFrame::NecRepeat(()),
+        ];
+        assert_eq!(
+            collapse_nec_repeats(&frames),
+            vec![
+                DecordedInfraredRemoteFrame::NecRepeatSequence { count: 1 },
+                DecordedInfraredRemoteFrame::Unknown(()),
+                DecordedInfraredRemoteFrame::NecRepeatSequence { count: 2 },
+            ]
+        );
+        assert_eq!(
+            serde_json::to_string(&collapse_nec_repeats(&frames[2..])).unwrap(),
+            r#"[{"NecRepeatSequence":{"count":2}}]"#
+        );
+    }
+
+    #[test]
+    fn test20() {
+        // 1フレームだけのSIRCはそのまま
+        let rxdata= "5B0018002E001800180018002E001800170018002E00190017001800170018002E00180018001800170018001700180017004F03";
+        let markandspaces = crate::parsing::parse_infrared_code_text(rxdata).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        assert_eq!(collapse_sirc_repeats(&frames), frames);
+        // 3回繰り返したSIRC
+        let source = (0..3)
+            .flat_map(|_| protocol_sirc::encode_sirc12(21, 1))
+            .collect::<Vec<MarkAndSpaceMicros>>();
+        let frames = decord_receiving_data(&source, None).unwrap();
+        assert_eq!(frames.len(), 3);
+        let DecordedInfraredRemoteFrame::Sirc(bits) = frames[0].clone() else {
+            panic!("SIRC expected")
+        };
+        assert_eq!(
+            collapse_sirc_repeats(&frames),
+            vec![DecordedInfraredRemoteFrame::SircRepeat {
+                frame: bits,
+                count: 3
+            }]
+        );
+        // 異なるフレームはまとめない
+        let source = protocol_sirc::encode_sirc12(21, 1)
+            .into_iter()
+            .chain(protocol_sirc::encode_sirc12(18, 1))
+            .collect::<Vec<MarkAndSpaceMicros>>();
+        let frames = decord_receiving_data(&source, None).unwrap();
+        assert_eq!(collapse_sirc_repeats(&frames), frames);
+    }
+
+    #[test]
+    fn test21() {
+        let frames =
+            decord_receiving_data(&protocol_nec::encode_nec([0x40, 0xbf, 0x12, 0xed]), None)
+                .unwrap();
+        assert_eq!(
+            validate_frames(&frames),
+            FrameValidation {
+                valid: true,
+                errors: vec![]
+            }
+        );
+        // 31ビットのNECフレーム
+        let mut bits = frames[0].bits().unwrap().to_vec();
+        bits.pop();
+        let result = validate_frames(&[DecordedInfraredRemoteFrame::Nec(bits)]);
+        assert!(!result.valid);
+        assert_eq!(result.errors.len(), 1);
+        assert!(result.errors[0].contains("bit count"));
+        assert!(result.errors[0].contains("31"));
+        // AEHAとSIRCの異常, プロトコルの混在
+        let result = validate_frames(&[
+            DecordedInfraredRemoteFrame::Aeha(vec![Bit::Lo; 12]),
+            DecordedInfraredRemoteFrame::Sirc(vec![Bit::Lo; 13]),
+        ]);
+        assert_eq!(
+            result.errors,
+            vec![
+                "frame 0: AEHA bit count must be a multiple of 8. (actual 12)",
+                "frame 1: SIRC bit count must be 12, 15 or 20. (actual 13)",
+                "frames of different protocols are mixed. (AEHA, SIRC)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test22() {
+        // 第2フレームが44ビットで切れたAEHA信号
+        let markandspaces =
+            panasonic_hvac::encode("hvac_mode_cool", "power_on", 26, "auto", "auto", "normal")
+                .unwrap();
+        let first_frame_len = markandspaces
+            .iter()
+            .position(|ms| THRESHOLD_FRAME_GAP <= ms.space)
+            .unwrap()
+            + 1;
+        // リーダー, 44ビット, 取り除かれるストップビットの位置
+        let truncated = &markandspaces[..first_frame_len + 1 + 44 + 1];
+        let frames = decord_receiving_data(truncated, None).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert!(matches!(frames[0], DecordedInfraredRemoteFrame::Aeha(_)));
+        assert_eq!(frames[1], DecordedInfraredRemoteFrame::Unknown(()));
+        // 途中で切れたSIRC信号
+        let source = protocol_sirc::encode_sirc12(21, 1);
+        let frames = decord_receiving_data(&source[..8], None).unwrap();
+        assert_eq!(frames, vec![DecordedInfraredRemoteFrame::Unknown(())]);
+    }
+
+    #[test]
+    fn test_mark_and_space_demodulator() {
+        let source =
+            panasonic_hvac::encode("hvac_mode_cool", "power_on", 26, "auto", "auto", "normal")
+                .unwrap();
+        let expected = decord_receiving_data(&source, None)
+            .unwrap()
+            .iter()
+            .filter_map(|frame| frame.bits())
+            .flatten()
+            .copied()
+            .collect::<Vec<Bit>>();
+        let mut demodulator = MarkAndSpaceDemodulator::new(InfraredRemoteProtocol::Aeha).unwrap();
+        let mut result = Vec::new();
+        for ms in source {
+            demodulator.feed(ms);
+            result.extend(demodulator.by_ref());
+        }
+        assert_eq!(result, expected);
+        //
+        assert!(MarkAndSpaceDemodulator::new(InfraredRemoteProtocol::Rc5).is_none());
+    }
+
+    #[test]
+    fn test_mark_and_space_demodulator_nec_repeat() {
+        let frame = protocol_nec::encode_nec([0x40, 0xbf, 0x12, 0xed]);
+        let repeat = protocol_nec::encode_nec_repeat();
+        let source = [frame.clone(), repeat.clone(), repeat].concat();
+        let mut demodulator = MarkAndSpaceDemodulator::new(InfraredRemoteProtocol::Nec).unwrap();
+        source.into_iter().for_each(|ms| demodulator.feed(ms));
+        assert_eq!(demodulator.repeat_count(), 2);
+        // リピートパルスはビットにしない
+        assert_eq!(
+            demodulator.collect::<Vec<Bit>>(),
+            decord_receiving_data(&frame, None).unwrap()[0]
+                .bits()
+                .unwrap()
+                .to_vec()
+        );
+    }
+
+    #[test]
+    fn test_mark_and_space_demodulator_sirc() {
+        let source = crate::parsing::parse_infrared_code_text(
+            "5B0018002E001800180018002E001800170018002E00190017001800170018002E00180018001800170018001700180017004F03",
+        )
+        .unwrap();
+        let mut demodulator = MarkAndSpaceDemodulator::new(InfraredRemoteProtocol::Sirc).unwrap();
+        source.into_iter().for_each(|ms| demodulator.feed(ms));
+        assert_eq!(
+            demodulator.collect::<Vec<Bit>>(),
+            vec![1, 0, 1, 0, 1, 0, 0, 1, 0, 0, 0, 0]
+                .into_iter()
+                .map(|n| Bit::try_from(n).unwrap())
+                .collect::<Vec<Bit>>()
+        );
+    }
 }
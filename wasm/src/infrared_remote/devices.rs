@@ -1,6 +1,169 @@
 pub mod daikin_hvac;
 pub mod hitachi_hvac;
+pub mod lg_hvac;
 pub mod mitsubishi_electric_hvac;
 pub mod panasonic_hvac;
+pub mod panasonic_tv;
+pub mod philips_tv;
+pub mod pioneer_av;
+pub mod samsung_tv;
 pub mod sirc;
+pub mod sony_av;
+#[cfg(test)]
+pub mod test_frames;
 pub mod toshiba_tv;
+pub mod whirlpool_hvac;
+
+use crate::infrared_remote::{DecordedInfraredRemoteFrame, InfraredRemoteControlCode};
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum HvacDecodeError {
+    #[error("temperature out of range. (expected {0} to {1}, actual {2})")]
+    TemperatureOutOfRange(u8, u8, u8),
+    #[error("unknown mode. ({0})")]
+    UnknownMode(u8),
+    #[error("unknown fan speed. ({0})")]
+    UnknownFanSpeed(u8),
+    #[error("unknown swing. ({0})")]
+    UnknownSwing(u8),
+    #[error("checksum mismatch. (expected {expected}, actual {actual})")]
+    ChecksumMismatch { expected: u8, actual: u8 },
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console, js_name = warn)]
+    fn console_warn(s: &str);
+}
+
+/// 復号できなかった理由をブラウザのコンソールに記録する
+#[cfg(target_arch = "wasm32")]
+pub fn log_decode_error(manufacturer: &str, error: &HvacDecodeError) {
+    console_warn(&format!("{} decode error: {}", manufacturer, error));
+}
+
+/// wasm以外では記録しない(理由は各機器の decode_sub で得られる)
+#[cfg(not(target_arch = "wasm32"))]
+pub fn log_decode_error(_manufacturer: &str, _error: &HvacDecodeError) {}
+
+/// 復号できなかった場合は理由を記録してNoneにする
+pub fn ok_or_log<T>(manufacturer: &str, result: Result<Option<T>, HvacDecodeError>) -> Option<T> {
+    result.unwrap_or_else(|e| {
+        log_decode_error(manufacturer, &e);
+        None
+    })
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ValidationError {
+    #[error("temperature out of range. (expected {0} to {1}, actual {2})")]
+    TemperatureOutOfRange(u8, u8, u8),
+    #[error("{0} out of range. (expected 0 to {1}, actual {2})")]
+    ValueOutOfRange(String, u16, u16),
+    #[error("inconsistent settings. ({0})")]
+    InconsistentSettings(String),
+    #[error("missing {0}.")]
+    MissingField(String),
+    #[error("unknown {0}. ({1})")]
+    UnknownValue(String, String),
+}
+
+/// 機器毎の復号器
+pub trait HvacDecoder {
+    fn decode(&self, frames: &[DecordedInfraredRemoteFrame]) -> Vec<InfraredRemoteControlCode>;
+    fn manufacturer_name(&self) -> &'static str;
+    fn device_type(&self) -> &'static str;
+}
+
+macro_rules! define_decoder {
+    ($name:ident, $module:ident, $manufacturer:expr, $device_type:expr) => {
+        define_decoder!($name, $module::decode, $manufacturer, $device_type);
+    };
+    ($name:ident, $decode:path, $manufacturer:expr, $device_type:expr) => {
+        pub struct $name;
+
+        impl HvacDecoder for $name {
+            fn decode(
+                &self,
+                frames: &[DecordedInfraredRemoteFrame],
+            ) -> Vec<InfraredRemoteControlCode> {
+                $decode(frames)
+            }
+
+            fn manufacturer_name(&self) -> &'static str {
+                $manufacturer
+            }
+
+            fn device_type(&self) -> &'static str {
+                $device_type
+            }
+        }
+    };
+}
+
+define_decoder!(ToshibaTvDecoder, toshiba_tv, "toshiba", "tv");
+define_decoder!(SonyAvDecoder, sony_av, "sony", "av");
+define_decoder!(SircDecoder, sirc, "sony", "av");
+define_decoder!(PhilipsTvDecoder, philips_tv, "philips", "tv");
+define_decoder!(PanasonicTvDecoder, panasonic_tv, "panasonic", "tv");
+define_decoder!(PanasonicHvacDecoder, panasonic_hvac, "panasonic", "hvac");
+define_decoder!(PioneerAvDecoder, pioneer_av, "pioneer", "av");
+define_decoder!(DaikinHvacDecoder, daikin_hvac, "daikin", "hvac");
+define_decoder!(
+    DaikinBrc52HvacDecoder,
+    daikin_hvac::decode_brc52,
+    "daikin",
+    "hvac"
+);
+define_decoder!(HitachiHvacDecoder, hitachi_hvac, "hitachi", "hvac");
+define_decoder!(
+    MitsubishiElectricHvacDecoder,
+    mitsubishi_electric_hvac,
+    "mitsubishi electric",
+    "hvac"
+);
+define_decoder!(LgHvacDecoder, lg_hvac, "lg", "hvac");
+define_decoder!(WhirlpoolHvacDecoder, whirlpool_hvac, "whirlpool", "hvac");
+define_decoder!(SamsungTvDecoder, samsung_tv, "samsung", "tv");
+
+/// 登録済みの復号器(先に一致したものを優先する)
+pub const DECODERS: &[&dyn HvacDecoder] = &[
+    &ToshibaTvDecoder,
+    &SonyAvDecoder,
+    &SircDecoder,
+    &PhilipsTvDecoder,
+    &PanasonicTvDecoder,
+    &PanasonicHvacDecoder,
+    &PioneerAvDecoder,
+    &DaikinHvacDecoder,
+    &DaikinBrc52HvacDecoder,
+    &HitachiHvacDecoder,
+    &MitsubishiElectricHvacDecoder,
+    &LgHvacDecoder,
+    &WhirlpoolHvacDecoder,
+    &SamsungTvDecoder,
+];
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+/// 対応機器
+pub struct SupportedDevice {
+    pub manufacturer: &'static str,
+    pub device_type: &'static str,
+}
+
+/// 登録済みの復号器から対応機器の一覧を得る(重複は除く)
+pub fn supported_devices() -> Vec<SupportedDevice> {
+    DECODERS.iter().fold(Vec::new(), |mut devices, decoder| {
+        let device = SupportedDevice {
+            manufacturer: decoder.manufacturer_name(),
+            device_type: decoder.device_type(),
+        };
+        if !devices.contains(&device) {
+            devices.push(device);
+        }
+        devices
+    })
+}
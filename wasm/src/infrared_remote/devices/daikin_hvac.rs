@@ -4,6 +4,8 @@
 //
 use crate::infrared_remote::*;
 use once_cell::sync::Lazy;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 //
@@ -31,7 +33,7 @@ use std::collections::HashMap;
 //
 // 4rd byte "00000000"
 //
-const FRAME_HEADER: [LsbFirst; 4] = [
+pub(crate) const FRAME_HEADER: [LsbFirst; 4] = [
     LsbFirst::new(0x11),
     LsbFirst::new(0xda),
     LsbFirst::new(0x27),
@@ -39,7 +41,7 @@ const FRAME_HEADER: [LsbFirst; 4] = [
 ];
 
 // Daikin HVAC first frame
-const COMFORT_MODE: Lazy<HashMap<[LsbFirst; 8], &'static str>> = Lazy::new(|| {
+static COMFORT_MODE: Lazy<HashMap<[LsbFirst; 8], &'static str>> = Lazy::new(|| {
     let mut hm = HashMap::new();
     hm.insert(
         [
@@ -83,7 +85,7 @@ const SECOND_FRAME: [LsbFirst; 8] = [
 ];
 
 //
-const HVAC_MODE: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
+static HVAC_MODE: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
     let mut hm = HashMap::new();
     hm.insert(LsbFirst::new(0x0), "hvac_mode_auto");
     hm.insert(LsbFirst::new(0x2), "hvac_mode_dry");
@@ -94,7 +96,7 @@ const HVAC_MODE: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
 });
 
 //
-const TIMER_ON: Lazy<HashMap<bool, &'static str>> = Lazy::new(|| {
+static TIMER_ON: Lazy<HashMap<bool, &'static str>> = Lazy::new(|| {
     let mut hm = HashMap::new();
     hm.insert(false, "disabled");
     hm.insert(true, "enabled");
@@ -102,7 +104,7 @@ const TIMER_ON: Lazy<HashMap<bool, &'static str>> = Lazy::new(|| {
 });
 
 //
-const TIMER_OFF: Lazy<HashMap<bool, &'static str>> = Lazy::new(|| {
+static TIMER_OFF: Lazy<HashMap<bool, &'static str>> = Lazy::new(|| {
     let mut hm = HashMap::new();
     hm.insert(false, "disabled");
     hm.insert(true, "enabled");
@@ -110,7 +112,7 @@ const TIMER_OFF: Lazy<HashMap<bool, &'static str>> = Lazy::new(|| {
 });
 
 //
-const POWER_SWITCH: Lazy<HashMap<bool, &'static str>> = Lazy::new(|| {
+static POWER_SWITCH: Lazy<HashMap<bool, &'static str>> = Lazy::new(|| {
     let mut hm = HashMap::new();
     hm.insert(false, "power_off");
     hm.insert(true, "power_on");
@@ -118,7 +120,7 @@ const POWER_SWITCH: Lazy<HashMap<bool, &'static str>> = Lazy::new(|| {
 });
 
 //
-const SWING: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
+static SWING: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
     let mut hm = HashMap::new();
     hm.insert(LsbFirst::new(0x0), "disabled");
     hm.insert(LsbFirst::new(0xf), "enabled");
@@ -126,7 +128,20 @@ const SWING: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
 });
 
 //
-const FAN_SPEED: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
+static HORIZONTAL_SWING: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
+    let mut hm = HashMap::new();
+    hm.insert(LsbFirst::new(0x0), "stop");
+    hm.insert(LsbFirst::new(0x1), "position1");
+    hm.insert(LsbFirst::new(0x2), "position2");
+    hm.insert(LsbFirst::new(0x3), "position3");
+    hm.insert(LsbFirst::new(0x4), "position4");
+    hm.insert(LsbFirst::new(0x5), "position5");
+    hm.insert(LsbFirst::new(0xf), "swing");
+    hm
+});
+
+//
+static FAN_SPEED: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
     let mut hm = HashMap::new();
     hm.insert(LsbFirst::new(0x3), "notch1");
     hm.insert(LsbFirst::new(0x4), "notch2");
@@ -139,7 +154,7 @@ const FAN_SPEED: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
 });
 
 //
-const POWERFUL: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
+static POWERFUL: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
     let mut hm = HashMap::new();
     hm.insert(LsbFirst::new(0), "disabled");
     hm.insert(LsbFirst::new(1), "enabled");
@@ -147,19 +162,35 @@ const POWERFUL: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
 });
 
 //
-const ECONO: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
+static ECONO: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
     let mut hm = HashMap::new();
     hm.insert(LsbFirst::new(0x80), "disabled");
     hm.insert(LsbFirst::new(0x84), "enabled");
     hm
 });
 
+/// チェックサム
+/// 全バイトの総和の下位8ビット
+fn checksum(octets: &[LsbFirst]) -> LsbFirst {
+    LsbFirst::from(
+        octets
+            .iter()
+            .fold(0u8, |acc, &x| acc.wrapping_add(u8::from(x))),
+    )
+}
+
 /// デコード
 pub fn decode(frames: &[DecordedInfraredRemoteFrame]) -> Vec<InfraredRemoteControlCode> {
-    decode_sub(frames).map_or(vec![], |a| vec![a])
+    // 復号できなかった場合は理由を記録して空にする
+    ok_or_log("daikin", decode_sub(frames))
+        .into_iter()
+        .collect()
 }
 
-fn decode_sub(frames: &[DecordedInfraredRemoteFrame]) -> Option<InfraredRemoteControlCode> {
+/// 第1フレームと第3フレームを取り出す
+fn target_frames_octets(
+    frames: &[DecordedInfraredRemoteFrame],
+) -> Option<([LsbFirst; 8], Vec<LsbFirst>)> {
     let target_frames: Vec<&[Bit]> = frames
         .iter()
         .flat_map(|fr: &DecordedInfraredRemoteFrame| match fr {
@@ -182,7 +213,6 @@ fn decode_sub(frames: &[DecordedInfraredRemoteFrame]) -> Option<InfraredRemoteCo
         .collect();
     // フレーム３個を取り出す
     if let Some([first_frame, second_frame, third_frame]) = target_frames.get(0..3) {
-        let mut decorded: HashMap<String, String> = HashMap::new();
         // 第1フレーム
         // comfort mode
         let comfort_mode = [
@@ -195,9 +225,6 @@ fn decode_sub(frames: &[DecordedInfraredRemoteFrame]) -> Option<InfraredRemoteCo
             first_frame.get(48..56).map(|x| folding_to_lsb_first(x))?,
             first_frame.get(56..64).map(|x| folding_to_lsb_first(x))?,
         ];
-        COMFORT_MODE
-            .get(&comfort_mode)
-            .map(|&item| decorded.insert("comfort_mode".to_owned(), item.to_owned()));
         // 第2フレーム
         let actual_second_frame = [
             second_frame.get(0..8).map(|x| folding_to_lsb_first(x))?,
@@ -214,6 +241,12 @@ fn decode_sub(frames: &[DecordedInfraredRemoteFrame]) -> Option<InfraredRemoteCo
         } else {
             None
         }?;
+        // 第2フレームのチェックサム(0x54)
+        let _ = if checksum(&actual_second_frame[..7]) == actual_second_frame[7] {
+            Some(1)
+        } else {
+            None
+        }?;
         // 第3フレーム
         // data required 152bits
         let octets = third_frame.get(0..152).map(|x| {
@@ -222,98 +255,941 @@ fn decode_sub(frames: &[DecordedInfraredRemoteFrame]) -> Option<InfraredRemoteCo
                 .map(|x| folding_to_lsb_first(x))
                 .collect::<Vec<LsbFirst>>()
         })?;
-        // ===================================================================================================================
-        // https://github.com/blafois/Daikin-IR-Reverse
-        //
-        // offset   | Description           | Length    | Example       | Decoding
-        // 00-03    | Header                | 4         | 11 da 27 00   |
-        // 04       | Message Identifier    | 1         | 00            |
-        // 05       | Mode, On/Off, Timer   | 1         | 49            | 49 = Heat, On, No Timer
-        // 06       | Temperature           | 1         | 30            | It is temperature x2. 0x30 = 48 / 2 = 24C
-        // 08       | Fan / Swing           | 1         | 30            | 30 = Fan 1/5 No Swing. 3F = Fan 1/5 + Swing.
-        // 0a-0c    | Timer Delay           | 3         | 3c 00 60      |
-        // 0d       | Powerful              | 1         | 01            | Powerful enabled
-        // 10       | Econo                 | 1         | 84            | 4 last bits
-        // 12       | Checksum              | 1         | 8e            | Add all previous bytes and do a OR with mask 0xff
-        // ===================================================================================================================
-        //
-        // Message  Idetifier
-        //
-        let _message_identifier = u8::from(octets[0x4]);
-        //
-        // Mode, On/Off, Timer
-        //
-        let mode_onoff_timer = u8::from(octets[0x5]);
-        HVAC_MODE
-            .get(&LsbFirst::from(mode_onoff_timer >> 4 & 0xf))
-            .map(|&item| decorded.insert("hvac_mode".to_owned(), item.to_owned()));
-        // Always 1
-        let _ = if mode_onoff_timer & 8 == 0 {
-            None
-        } else {
-            Some(1)
-        }?;
-        TIMER_OFF
-            .get(&(mode_onoff_timer & 4 != 0))
-            .map(|&item| decorded.insert("timer_off".to_owned(), item.to_owned()));
-        TIMER_ON
-            .get(&(mode_onoff_timer & 2 != 0))
-            .map(|&item| decorded.insert("timer_on".to_owned(), item.to_owned()));
-        POWER_SWITCH
-            .get(&(mode_onoff_timer & 1 != 0))
-            .map(|&item| decorded.insert("power_switch".to_owned(), item.to_owned()));
-        //
-        // Temperature
-        //
-        decorded.insert(
-            "temperature".to_owned(),
-            (u8::from(octets[0x6]) / 2).to_string(),
-        );
-        //
-        // Fan / Swing
-        //
-        FAN_SPEED
-            .get(&LsbFirst::from(u8::from(octets[0x8]) >> 4 & 0xf))
-            .map(|&item| decorded.insert("fan_speed".to_owned(), item.to_owned()));
-        SWING
-            .get(&LsbFirst::from(u8::from(octets[0x8]) >> 0 & 0xf))
-            .map(|&item| decorded.insert("swing".to_owned(), item.to_owned()));
-        //
-        // Timer Delay
-        //
-        decorded.insert("timer_on_duration_hour".to_owned(), {
-            let higher_nibble = u8::from(octets[0xb]) & 0xf;
-            let lower_byte = u8::from(octets[0xa]);
-            let minutes = (higher_nibble as u16) << 8 | lower_byte as u16;
-            (minutes / 60).to_string()
+        Some((comfort_mode, octets))
+    } else {
+        None
+    }
+}
+
+/// デコード
+/// ダイキンのエアコンの信号ではない場合はOk(None)を返す
+pub fn decode_sub(
+    frames: &[DecordedInfraredRemoteFrame],
+) -> Result<Option<InfraredRemoteControlCode>, HvacDecodeError> {
+    let Some((comfort_mode, octets)) = target_frames_octets(frames) else {
+        return Ok(None);
+    };
+    // ===================================================================================================================
+    // https://github.com/blafois/Daikin-IR-Reverse
+    //
+    // offset   | Description           | Length    | Example       | Decoding
+    // 00-03    | Header                | 4         | 11 da 27 00   |
+    // 04       | Message Identifier    | 1         | 00            |
+    // 05       | Mode, On/Off, Timer   | 1         | 49            | 49 = Heat, On, No Timer
+    // 06       | Temperature           | 1         | 30            | It is temperature x2. 0x30 = 48 / 2 = 24C
+    // 08       | Fan / Swing           | 1         | 30            | 30 = Fan 1/5 No Swing. 3F = Fan 1/5 + Swing.
+    // 09       | Horizontal Swing      | 1         | 00            | 0 = Stop, 1-5 = Position 1-5, F = Swing
+    // 0a-0c    | Timer Delay           | 3         | 3c 00 60      |
+    // 0d       | Powerful              | 1         | 01            | Powerful enabled
+    // 10       | Econo                 | 1         | 84            | 4 last bits
+    // 12       | Checksum              | 1         | 8e            | Add all previous bytes and do a OR with mask 0xff
+    // ===================================================================================================================
+    //
+    // Checksum
+    //
+    let expected = checksum(&octets[0x00..=0x11]);
+    if expected != octets[0x12] {
+        return Err(HvacDecodeError::ChecksumMismatch {
+            expected: u8::from(expected),
+            actual: u8::from(octets[0x12]),
         });
-        decorded.insert("timer_off_duration_hour".to_owned(), {
-            let higher_byte = u8::from(octets[0xc]);
-            let lower_nibble = u8::from(octets[0xb]) >> 4 & 0xf;
-            let minutes = (higher_byte as u16) << 4 | lower_nibble as u16;
-            (minutes / 60).to_string()
+    }
+    let mut decorded: HashMap<String, String> = HashMap::new();
+    // 第1フレーム
+    COMFORT_MODE
+        .get(&comfort_mode)
+        .map(|&item| decorded.insert("comfort_mode".to_owned(), item.to_owned()));
+    //
+    // Message  Idetifier
+    //
+    let _message_identifier = u8::from(octets[0x4]);
+    //
+    // Mode, On/Off, Timer
+    //
+    let mode_onoff_timer = u8::from(octets[0x5]);
+    let hvac_mode = mode_onoff_timer >> 4 & 0xf;
+    let item = HVAC_MODE
+        .get(&LsbFirst::from(hvac_mode))
+        .copied()
+        .ok_or(HvacDecodeError::UnknownMode(hvac_mode))?;
+    decorded.insert("hvac_mode".to_owned(), item.to_owned());
+    // Always 1
+    if mode_onoff_timer & 8 == 0 {
+        return Ok(None);
+    }
+    TIMER_OFF
+        .get(&(mode_onoff_timer & 4 != 0))
+        .map(|&item| decorded.insert("timer_off".to_owned(), item.to_owned()));
+    TIMER_ON
+        .get(&(mode_onoff_timer & 2 != 0))
+        .map(|&item| decorded.insert("timer_on".to_owned(), item.to_owned()));
+    POWER_SWITCH
+        .get(&(mode_onoff_timer & 1 != 0))
+        .map(|&item| decorded.insert("power_switch".to_owned(), item.to_owned()));
+    //
+    // Temperature
+    //
+    let temperature = u8::from(octets[0x6]) / 2;
+    if !(10..=32).contains(&temperature) {
+        return Err(HvacDecodeError::TemperatureOutOfRange(10, 32, temperature));
+    }
+    decorded.insert("temperature".to_owned(), temperature.to_string());
+    //
+    // Fan / Swing
+    //
+    let fan_speed = u8::from(octets[0x8]) >> 4 & 0xf;
+    let item = FAN_SPEED
+        .get(&LsbFirst::from(fan_speed))
+        .copied()
+        .ok_or(HvacDecodeError::UnknownFanSpeed(fan_speed))?;
+    decorded.insert("fan_speed".to_owned(), item.to_owned());
+    let swing = u8::from(octets[0x8]) & 0xf;
+    let item = SWING
+        .get(&LsbFirst::from(swing))
+        .copied()
+        .ok_or(HvacDecodeError::UnknownSwing(swing))?;
+    decorded.insert("swing".to_owned(), item.to_owned());
+    //
+    // Horizontal Swing
+    //
+    // 左右風向の無い機種もあるので, 知らない値は読み飛ばす
+    let horizontal_swing = u8::from(octets[0x9]) & 0xf;
+    HORIZONTAL_SWING
+        .get(&LsbFirst::from(horizontal_swing))
+        .map(|&item| decorded.insert("horizontal_swing".to_owned(), item.to_owned()));
+    //
+    // Timer Delay
+    //
+    decorded.insert("timer_on_duration_hour".to_owned(), {
+        let higher_nibble = u8::from(octets[0xb]) & 0xf;
+        let lower_byte = u8::from(octets[0xa]);
+        let minutes = (higher_nibble as u16) << 8 | lower_byte as u16;
+        (minutes / 60).to_string()
+    });
+    decorded.insert("timer_off_duration_hour".to_owned(), {
+        let higher_byte = u8::from(octets[0xc]);
+        let lower_nibble = u8::from(octets[0xb]) >> 4 & 0xf;
+        let minutes = (higher_byte as u16) << 4 | lower_nibble as u16;
+        (minutes / 60).to_string()
+    });
+    //
+    // Powerful
+    //
+    POWERFUL
+        .get(&octets[0xd])
+        .map(|&item| decorded.insert("powerful".to_owned(), item.to_owned()));
+    //
+    // Econo
+    //
+    ECONO
+        .get(&octets[0x10])
+        .map(|&item| decorded.insert("econo".to_owned(), item.to_owned()));
+    //
+    decorded.insert("checksum".to_owned(), u8::from(octets[0x12]).to_string());
+    decorded.insert("manufacturer".to_owned(), "daikin".to_owned());
+    Ok(Some(InfraredRemoteControlCode(decorded)))
+}
+
+//
+// Daikin BRC52 series (wired remote controller) frame first 4bytes value is
+// 0x11 da 17 18 (LSB first)
+// 35バイトのフレーム1個で送信する
+// 実機の受信データが無いので, この配置は確かめていない
+//
+const BRC52_FRAME_HEADER: [LsbFirst; 4] = [
+    LsbFirst::new(0x11),
+    LsbFirst::new(0xda),
+    LsbFirst::new(0x17),
+    LsbFirst::new(0x18),
+];
+
+/// BRC52のフレームのバイト数
+const BRC52_FRAME_OCTETS: usize = 35;
+
+/// デコード(BRC52 有線リモコン)
+pub fn decode_brc52(frames: &[DecordedInfraredRemoteFrame]) -> Vec<InfraredRemoteControlCode> {
+    // 復号できなかった場合は理由を記録して空にする
+    ok_or_log("daikin", decode_brc52_sub(frames))
+        .into_iter()
+        .collect()
+}
+
+/// デコード(BRC52 有線リモコン)
+/// BRC52の信号ではない場合はOk(None)を返す
+pub fn decode_brc52_sub(
+    frames: &[DecordedInfraredRemoteFrame],
+) -> Result<Option<InfraredRemoteControlCode>, HvacDecodeError> {
+    // フレーム1個のみ
+    let [DecordedInfraredRemoteFrame::Aeha(aeha)] = frames else {
+        return Ok(None);
+    };
+    if aeha.len() != BRC52_FRAME_OCTETS * 8 {
+        return Ok(None);
+    }
+    let octets = pack_to_octets(aeha)
+        .into_iter()
+        .map(LsbFirst::from)
+        .collect::<Vec<LsbFirst>>();
+    if octets[0x00..0x04] != BRC52_FRAME_HEADER {
+        return Ok(None);
+    }
+    // 実機の受信データでは確かめていない配置
+    // ===================================================================================================================
+    // offset   | Description           | Length    | Decoding
+    // 00-03    | Header                | 4         | 11 da 17 18
+    // 05       | Mode, On/Off          | 1         | 無線リモコンの第3フレームと同じ
+    // 06       | Temperature           | 1         | It is temperature x2.
+    // 08       | Fan / Swing           | 1         | 無線リモコンの第3フレームと同じ
+    // 22       | Checksum              | 1         | Add all previous bytes
+    // ===================================================================================================================
+    //
+    // Checksum
+    //
+    let expected = checksum(&octets[0x00..0x22]);
+    if expected != octets[0x22] {
+        return Err(HvacDecodeError::ChecksumMismatch {
+            expected: u8::from(expected),
+            actual: u8::from(octets[0x22]),
         });
+    }
+    let mut decorded: HashMap<String, String> = HashMap::new();
+    //
+    // Mode, On/Off
+    //
+    let mode_onoff = u8::from(octets[0x5]);
+    let hvac_mode = mode_onoff >> 4 & 0xf;
+    let item = HVAC_MODE
+        .get(&LsbFirst::from(hvac_mode))
+        .copied()
+        .ok_or(HvacDecodeError::UnknownMode(hvac_mode))?;
+    decorded.insert("hvac_mode".to_owned(), item.to_owned());
+    POWER_SWITCH
+        .get(&(mode_onoff & 1 != 0))
+        .map(|&item| decorded.insert("power_switch".to_owned(), item.to_owned()));
+    //
+    // Temperature
+    //
+    let temperature = u8::from(octets[0x6]) / 2;
+    if !(10..=32).contains(&temperature) {
+        return Err(HvacDecodeError::TemperatureOutOfRange(10, 32, temperature));
+    }
+    decorded.insert("temperature".to_owned(), temperature.to_string());
+    //
+    // Fan / Swing
+    //
+    let fan_speed = u8::from(octets[0x8]) >> 4 & 0xf;
+    let item = FAN_SPEED
+        .get(&LsbFirst::from(fan_speed))
+        .copied()
+        .ok_or(HvacDecodeError::UnknownFanSpeed(fan_speed))?;
+    decorded.insert("fan_speed".to_owned(), item.to_owned());
+    let swing = u8::from(octets[0x8]) & 0xf;
+    let item = SWING
+        .get(&LsbFirst::from(swing))
+        .copied()
+        .ok_or(HvacDecodeError::UnknownSwing(swing))?;
+    decorded.insert("swing".to_owned(), item.to_owned());
+    //
+    decorded.insert("checksum".to_owned(), u8::from(octets[0x22]).to_string());
+    decorded.insert("manufacturer".to_owned(), "daikin".to_owned());
+    Ok(Some(InfraredRemoteControlCode(decorded)))
+}
+
+/// 送信設定
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Settings {
+    pub comfort_mode: bool,
+    pub mode: String,
+    pub power: bool,
+    pub temperature: u8,
+    pub fan_speed: String,
+    pub swing: bool,
+    pub powerful: bool,
+    pub econo: bool,
+    pub timer_on: bool,
+    pub timer_on_minutes: u16,
+    pub timer_off: bool,
+    pub timer_off_minutes: u16,
+    /// 省略すると "stop"
+    #[serde(default)]
+    pub horizontal_swing: Option<String>,
+}
+
+/// 送信設定から符号化する
+pub fn encode_settings(settings: &Settings) -> Result<Vec<MarkAndSpaceMicros>, EncoderError> {
+    // 設定の名前を取り出して変換する
+    fn value<T>(name: &str, value: &str, parse: fn(&str) -> Option<T>) -> Result<T, EncoderError> {
+        parse(value).ok_or(EncoderError::UnknownValue(
+            name.to_owned(),
+            value.to_owned(),
+        ))
+    }
+    let horizontal_swing = match settings.horizontal_swing.as_deref() {
+        Some(name) => value("horizontal_swing", name, HorizontalSwing::from_name)?,
+        None => HorizontalSwing::Stop,
+    };
+    DaikinHvacBuilder::new()
+        .comfort_mode(settings.comfort_mode)
+        .mode(value("hvac_mode", &settings.mode, HvacMode::from_name)?)
+        .switch(settings.power)
+        .temperature(settings.temperature)
+        .fan(value(
+            "fan_speed",
+            &settings.fan_speed,
+            FanSpeed::from_name,
+        )?)
+        .swing(if settings.swing {
+            Swing::Enabled
+        } else {
+            Swing::Disabled
+        })
+        .horizontal_swing(horizontal_swing)
+        .powerful(settings.powerful)
+        .econo(settings.econo)
+        .on_timer(settings.timer_on)
+        .on_timer_duration_minutes(settings.timer_on_minutes)
+        .off_timer(settings.timer_off)
+        .off_timer_duration_minutes(settings.timer_off_minutes)
+        .build()?
+        .encode()
+}
+
+/// エンコード(値の範囲は DaikinHvacBuilder で確かめる)
+#[allow(clippy::too_many_arguments)]
+fn encode_sub(
+    comfort_mode: bool,
+    mode: &str,
+    power: bool,
+    temperature: u8,
+    fan_speed: &str,
+    swing: bool,
+    horizontal_swing: HorizontalSwing,
+    powerful: bool,
+    econo: bool,
+    timer_on: bool,
+    timer_on_minutes: u16,
+    timer_off: bool,
+    timer_off_minutes: u16,
+) -> Result<Vec<MarkAndSpaceMicros>, EncoderError> {
+    let enabled = |b: bool| if b { "enabled" } else { "disabled" };
+    // 第1フレーム
+    let first_frame = find_key_by_value(&COMFORT_MODE, "comfort_mode", enabled(comfort_mode))?;
+    // 第3フレーム
+    let hvac_mode = u8::from(find_key_by_value(&HVAC_MODE, "hvac_mode", mode)?);
+    let fan_speed = u8::from(find_key_by_value(&FAN_SPEED, "fan_speed", fan_speed)?);
+    let swing = u8::from(find_key_by_value(&SWING, "swing", enabled(swing))?);
+    let horizontal_swing = u8::from(find_key_by_value(
+        &HORIZONTAL_SWING,
+        "horizontal_swing",
+        horizontal_swing.as_str(),
+    )?);
+    let powerful = u8::from(find_key_by_value(&POWERFUL, "powerful", enabled(powerful))?);
+    let econo = u8::from(find_key_by_value(&ECONO, "econo", enabled(econo))?);
+    let mode_onoff_timer =
+        hvac_mode << 4 | 8 | (timer_off as u8) << 2 | (timer_on as u8) << 1 | power as u8;
+    let mut octets: [u8; 19] = [
+        0x11,
+        0xda,
+        0x27,
+        0x00,
+        0x00,
+        mode_onoff_timer,
+        temperature * 2,
+        0x00,
+        fan_speed << 4 | swing,
+        horizontal_swing,
+        (timer_on_minutes & 0xff) as u8,
+        ((timer_off_minutes & 0xf) << 4 | timer_on_minutes >> 8) as u8,
+        (timer_off_minutes >> 4) as u8,
+        powerful,
+        0x00,
+        0xc1,
+        econo,
+        0x00,
+        0x00,
+    ];
+    // チェックサム
+    octets[0x12] = octets[..0x12]
+        .iter()
+        .fold(0u8, |acc, &x| acc.wrapping_add(x));
+    //
+    Ok([
+        protocol_aeha::encode_frame(&first_frame.map(u8::from)),
+        protocol_aeha::encode_frame(&SECOND_FRAME.map(u8::from)),
+        protocol_aeha::encode_frame(&octets),
+    ]
+    .concat())
+}
+
+/// モード
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HvacMode {
+    Auto,
+    Dry,
+    Cool,
+    Heat,
+    Fan,
+}
+
+impl HvacMode {
+    const ALL: [HvacMode; 5] = [
+        HvacMode::Auto,
+        HvacMode::Dry,
+        HvacMode::Cool,
+        HvacMode::Heat,
+        HvacMode::Fan,
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.as_str() == name)
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            HvacMode::Auto => "hvac_mode_auto",
+            HvacMode::Dry => "hvac_mode_dry",
+            HvacMode::Cool => "hvac_mode_cool",
+            HvacMode::Heat => "hvac_mode_heat",
+            HvacMode::Fan => "hvac_mode_fan",
+        }
+    }
+}
+
+/// 風量
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FanSpeed {
+    Notch1,
+    Notch2,
+    Notch3,
+    Notch4,
+    Notch5,
+    Auto,
+    Silent,
+}
+
+impl FanSpeed {
+    const ALL: [FanSpeed; 7] = [
+        FanSpeed::Notch1,
+        FanSpeed::Notch2,
+        FanSpeed::Notch3,
+        FanSpeed::Notch4,
+        FanSpeed::Notch5,
+        FanSpeed::Auto,
+        FanSpeed::Silent,
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.as_str() == name)
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            FanSpeed::Notch1 => "notch1",
+            FanSpeed::Notch2 => "notch2",
+            FanSpeed::Notch3 => "notch3",
+            FanSpeed::Notch4 => "notch4",
+            FanSpeed::Notch5 => "notch5",
+            FanSpeed::Auto => "auto",
+            FanSpeed::Silent => "silent",
+        }
+    }
+}
+
+/// 風向
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Swing {
+    Disabled,
+    Enabled,
+}
+
+/// 左右風向
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HorizontalSwing {
+    Stop,
+    Position1,
+    Position2,
+    Position3,
+    Position4,
+    Position5,
+    Swing,
+}
+
+impl HorizontalSwing {
+    const ALL: [HorizontalSwing; 7] = [
+        HorizontalSwing::Stop,
+        HorizontalSwing::Position1,
+        HorizontalSwing::Position2,
+        HorizontalSwing::Position3,
+        HorizontalSwing::Position4,
+        HorizontalSwing::Position5,
+        HorizontalSwing::Swing,
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.as_str() == name)
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            HorizontalSwing::Stop => "stop",
+            HorizontalSwing::Position1 => "position1",
+            HorizontalSwing::Position2 => "position2",
+            HorizontalSwing::Position3 => "position3",
+            HorizontalSwing::Position4 => "position4",
+            HorizontalSwing::Position5 => "position5",
+            HorizontalSwing::Swing => "swing",
+        }
+    }
+}
+
+/// タイマーの最大時間(分, 12ビット)
+const MAX_TIMER_DURATION_MINUTES: u16 = 0xfff;
+
+/// ダイキンのエアコンの状態
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DaikinHvac {
+    comfort_mode: bool,
+    temperature: u8,
+    mode: HvacMode,
+    switch: bool,
+    fan: FanSpeed,
+    swing: Swing,
+    horizontal_swing: HorizontalSwing,
+    powerful: bool,
+    econo: bool,
+    on_timer: bool,
+    on_timer_duration_minutes: u16,
+    off_timer: bool,
+    off_timer_duration_minutes: u16,
+}
+
+impl DaikinHvac {
+    pub fn comfort_mode(&self) -> bool {
+        self.comfort_mode
+    }
+
+    pub fn temperature(&self) -> u8 {
+        self.temperature
+    }
+
+    pub fn mode(&self) -> HvacMode {
+        self.mode
+    }
+
+    pub fn switch(&self) -> bool {
+        self.switch
+    }
+
+    pub fn fan(&self) -> FanSpeed {
+        self.fan
+    }
+
+    pub fn swing(&self) -> Swing {
+        self.swing
+    }
+
+    pub fn horizontal_swing(&self) -> HorizontalSwing {
+        self.horizontal_swing
+    }
+
+    pub fn powerful(&self) -> bool {
+        self.powerful
+    }
+
+    pub fn econo(&self) -> bool {
+        self.econo
+    }
+
+    /// オンタイマー(分)
+    pub fn on_timer(&self) -> Option<u16> {
+        self.on_timer.then_some(self.on_timer_duration_minutes)
+    }
+
+    /// オフタイマー(分)
+    pub fn off_timer(&self) -> Option<u16> {
+        self.off_timer.then_some(self.off_timer_duration_minutes)
+    }
+
+    /// 符号化する
+    pub fn encode(&self) -> Result<Vec<MarkAndSpaceMicros>, EncoderError> {
+        encode_sub(
+            self.comfort_mode,
+            self.mode.as_str(),
+            self.switch,
+            self.temperature,
+            self.fan.as_str(),
+            self.swing == Swing::Enabled,
+            self.horizontal_swing,
+            self.powerful,
+            self.econo,
+            self.on_timer,
+            self.on_timer_duration_minutes,
+            self.off_timer,
+            self.off_timer_duration_minutes,
+        )
+    }
+}
+
+impl Serialize for DaikinHvac {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("DaikinHvac", 11)?;
+        state.serialize_field("comfort_mode", &self.comfort_mode())?;
+        state.serialize_field("temperature", &self.temperature())?;
+        state.serialize_field("mode", self.mode().as_str())?;
+        state.serialize_field("switch", &self.switch())?;
+        state.serialize_field("fan", self.fan().as_str())?;
+        state.serialize_field("swing", &(self.swing() == Swing::Enabled))?;
+        state.serialize_field("horizontal_swing", self.horizontal_swing().as_str())?;
+        state.serialize_field("powerful", &self.powerful())?;
+        state.serialize_field("econo", &self.econo())?;
+        state.serialize_field("on_timer", &self.on_timer())?;
+        state.serialize_field("off_timer", &self.off_timer())?;
+        state.end()
+    }
+}
+
+impl TryFrom<InfraredRemoteControlCode> for DaikinHvac {
+    type Error = ValidationError;
+
+    fn try_from(code: InfraredRemoteControlCode) -> Result<Self, Self::Error> {
+        // 項目の値を取り出して変換する
+        fn field<T>(
+            code: &InfraredRemoteControlCode,
+            name: &str,
+            parse: impl Fn(&str) -> Option<T>,
+        ) -> Result<T, ValidationError> {
+            let value = code
+                .0
+                .get(name)
+                .ok_or(ValidationError::MissingField(name.to_owned()))?;
+            parse(value).ok_or(ValidationError::UnknownValue(
+                name.to_owned(),
+                value.to_owned(),
+            ))
+        }
+        let enabled = |s: &str| match s {
+            "enabled" => Some(true),
+            "disabled" => Some(false),
+            _ => None,
+        };
+        // 表にない値は復号時に省かれるので, 無ければ無効とする
+        let optional = |name: &str| match code.0.get(name) {
+            Some(_) => field(&code, name, enabled),
+            None => Ok(false),
+        };
+        let horizontal_swing = match code.0.get("horizontal_swing") {
+            Some(_) => field(&code, "horizontal_swing", HorizontalSwing::from_name)?,
+            None => HorizontalSwing::Stop,
+        };
+        DaikinHvacBuilder::new()
+            .comfort_mode(optional("comfort_mode")?)
+            .mode(field(&code, "hvac_mode", HvacMode::from_name)?)
+            .switch(field(&code, "power_switch", |s| match s {
+                "power_on" => Some(true),
+                "power_off" => Some(false),
+                _ => None,
+            })?)
+            .temperature(field(&code, "temperature", |s| s.parse().ok())?)
+            .fan(field(&code, "fan_speed", FanSpeed::from_name)?)
+            .swing(if field(&code, "swing", enabled)? {
+                Swing::Enabled
+            } else {
+                Swing::Disabled
+            })
+            .horizontal_swing(horizontal_swing)
+            .powerful(optional("powerful")?)
+            .econo(optional("econo")?)
+            .on_timer(optional("timer_on")?)
+            .on_timer_duration_hour(field(&code, "timer_on_duration_hour", |s| s.parse().ok())?)
+            .off_timer(optional("timer_off")?)
+            .off_timer_duration_hour(field(&code, "timer_off_duration_hour", |s| s.parse().ok())?)
+            .build()
+    }
+}
+
+/// ダイキンのエアコンの状態を組み立てる
+/// 設定しなかった項目は 快適モード無し, 自動運転, 26度, 電源オフ, 風量自動, スイング無し, 左右風向停止, タイマー無し になる
+#[derive(Clone, Debug, Default)]
+pub struct DaikinHvacBuilder {
+    comfort_mode: Option<bool>,
+    temperature: Option<u8>,
+    mode: Option<HvacMode>,
+    switch: Option<bool>,
+    fan: Option<FanSpeed>,
+    swing: Option<Swing>,
+    horizontal_swing: Option<HorizontalSwing>,
+    powerful: Option<bool>,
+    econo: Option<bool>,
+    on_timer: Option<bool>,
+    on_timer_duration_minutes: Option<u16>,
+    off_timer: Option<bool>,
+    off_timer_duration_minutes: Option<u16>,
+}
+
+impl DaikinHvacBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn comfort_mode(mut self, comfort_mode: bool) -> Self {
+        self.comfort_mode = Some(comfort_mode);
+        self
+    }
+
+    pub fn temperature(mut self, temperature: u8) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    pub fn mode(mut self, mode: HvacMode) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    pub fn switch(mut self, switch: bool) -> Self {
+        self.switch = Some(switch);
+        self
+    }
+
+    pub fn fan(mut self, fan: FanSpeed) -> Self {
+        self.fan = Some(fan);
+        self
+    }
+
+    pub fn swing(mut self, swing: Swing) -> Self {
+        self.swing = Some(swing);
+        self
+    }
+
+    pub fn horizontal_swing(mut self, horizontal_swing: HorizontalSwing) -> Self {
+        self.horizontal_swing = Some(horizontal_swing);
+        self
+    }
+
+    pub fn powerful(mut self, powerful: bool) -> Self {
+        self.powerful = Some(powerful);
+        self
+    }
+
+    pub fn econo(mut self, econo: bool) -> Self {
+        self.econo = Some(econo);
+        self
+    }
+
+    pub fn on_timer(mut self, on_timer: bool) -> Self {
+        self.on_timer = Some(on_timer);
+        self
+    }
+
+    pub fn on_timer_duration_hour(mut self, hour: u8) -> Self {
+        self.on_timer_duration_minutes = Some(hour as u16 * 60);
+        self
+    }
+
+    pub fn on_timer_duration_minutes(mut self, minutes: u16) -> Self {
+        self.on_timer_duration_minutes = Some(minutes);
+        self
+    }
+
+    pub fn off_timer(mut self, off_timer: bool) -> Self {
+        self.off_timer = Some(off_timer);
+        self
+    }
+
+    pub fn off_timer_duration_hour(mut self, hour: u8) -> Self {
+        self.off_timer_duration_minutes = Some(hour as u16 * 60);
+        self
+    }
+
+    pub fn off_timer_duration_minutes(mut self, minutes: u16) -> Self {
+        self.off_timer_duration_minutes = Some(minutes);
+        self
+    }
+
+    pub fn build(self) -> Result<DaikinHvac, ValidationError> {
+        let hvac = DaikinHvac {
+            comfort_mode: self.comfort_mode.unwrap_or(false),
+            temperature: self.temperature.unwrap_or(26),
+            mode: self.mode.unwrap_or(HvacMode::Auto),
+            switch: self.switch.unwrap_or(false),
+            fan: self.fan.unwrap_or(FanSpeed::Auto),
+            swing: self.swing.unwrap_or(Swing::Disabled),
+            horizontal_swing: self.horizontal_swing.unwrap_or(HorizontalSwing::Stop),
+            powerful: self.powerful.unwrap_or(false),
+            econo: self.econo.unwrap_or(false),
+            on_timer: self.on_timer.unwrap_or(false),
+            on_timer_duration_minutes: self.on_timer_duration_minutes.unwrap_or(0),
+            off_timer: self.off_timer.unwrap_or(false),
+            off_timer_duration_minutes: self.off_timer_duration_minutes.unwrap_or(0),
+        };
+        if !(10..=32).contains(&hvac.temperature) {
+            return Err(ValidationError::TemperatureOutOfRange(
+                10,
+                32,
+                hvac.temperature,
+            ));
+        }
+        // パワフル運転と省エネ運転は同時に指定できない
+        if hvac.powerful && hvac.econo {
+            return Err(ValidationError::InconsistentSettings(
+                "powerful and econo cannot be enabled at the same time".to_owned(),
+            ));
+        }
+        // タイマーは12ビット
+        for (name, minutes) in [
+            ("timer_on_minutes", hvac.on_timer_duration_minutes),
+            ("timer_off_minutes", hvac.off_timer_duration_minutes),
+        ] {
+            if minutes > MAX_TIMER_DURATION_MINUTES {
+                return Err(ValidationError::ValueOutOfRange(
+                    name.to_owned(),
+                    MAX_TIMER_DURATION_MINUTES,
+                    minutes,
+                ));
+            }
+        }
+        Ok(hvac)
+    }
+}
+
+#[cfg(test)]
+mod encode_tests {
+    use crate::infrared_remote::*;
+
+    #[test]
+    fn test1() {
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("comfort_mode".to_owned(), "disabled".to_owned());
+        decorded.insert("hvac_mode".to_owned(), "hvac_mode_cool".to_owned());
+        decorded.insert("power_switch".to_owned(), "power_on".to_owned());
+        decorded.insert("timer_on".to_owned(), "enabled".to_owned());
+        decorded.insert("timer_off".to_owned(), "disabled".to_owned());
+        decorded.insert("timer_on_duration_hour".to_owned(), "10".to_owned());
+        decorded.insert("timer_off_duration_hour".to_owned(), "25".to_owned());
+        decorded.insert("temperature".to_owned(), "22".to_owned());
+        decorded.insert("fan_speed".to_owned(), "notch2".to_owned());
+        decorded.insert("powerful".to_owned(), "disabled".to_owned());
+        decorded.insert("swing".to_owned(), "enabled".to_owned());
+        decorded.insert("horizontal_swing".to_owned(), "stop".to_owned());
+        decorded.insert("econo".to_owned(), "disabled".to_owned());
+        decorded.insert("checksum".to_owned(), "244".to_owned());
+        decorded.insert("manufacturer".to_owned(), "daikin".to_owned());
         //
-        // Powerful
-        //
-        POWERFUL
-            .get(&octets[0xd])
-            .map(|&item| decorded.insert("powerful".to_owned(), item.to_owned()));
-        //
-        // Econo
-        //
-        ECONO
-            .get(&octets[0x10])
-            .map(|&item| decorded.insert("econo".to_owned(), item.to_owned()));
-        //
-        // Checksum
-        //
-        decorded.insert("checksum".to_owned(), u8::from(octets[0x12]).to_string());
+        let markandspaces = daikin_hvac::DaikinHvacBuilder::new()
+            .mode(daikin_hvac::HvacMode::Cool)
+            .switch(true)
+            .temperature(22)
+            .fan(daikin_hvac::FanSpeed::Notch2)
+            .swing(daikin_hvac::Swing::Enabled)
+            .on_timer(true)
+            .on_timer_duration_minutes(0x289)
+            .off_timer_duration_minutes(0x600)
+            .build()
+            .unwrap()
+            .encode()
+            .unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        assert_eq!(frames.len(), 3);
+        let result = daikin_hvac::decode(&frames);
+        let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn test2() {
+        let settings: daikin_hvac::Settings = serde_json::from_str(
+            r#"{"comfort_mode":true,"mode":"hvac_mode_heat","power":true,"temperature":24,"fan_speed":"auto","swing":false,"powerful":false,"econo":false,"timer_on":false,"timer_on_minutes":0,"timer_off":false,"timer_off_minutes":0}"#,
+        )
+        .unwrap();
+        assert!(daikin_hvac::encode_settings(&settings).is_ok());
+        // 範囲外の値は組み立てる時に確かめる
+        let mut invalid = settings.clone();
+        invalid.temperature = 33;
+        assert_eq!(
+            daikin_hvac::encode_settings(&invalid),
+            Err(EncoderError::Validation(
+                ValidationError::TemperatureOutOfRange(10, 32, 33)
+            ))
+        );
+        let mut invalid = settings.clone();
+        invalid.timer_on = true;
+        invalid.timer_on_minutes = 0x1000;
+        assert_eq!(
+            daikin_hvac::encode_settings(&invalid),
+            Err(EncoderError::Validation(ValidationError::ValueOutOfRange(
+                "timer_on_minutes".to_owned(),
+                0xfff,
+                0x1000
+            )))
+        );
+        let mut invalid = settings.clone();
+        invalid.powerful = true;
+        invalid.econo = true;
+        assert!(matches!(
+            daikin_hvac::encode_settings(&invalid),
+            Err(EncoderError::Validation(
+                ValidationError::InconsistentSettings(_)
+            ))
+        ));
+        // 知らない名前
+        let mut invalid = settings.clone();
+        invalid.mode = "hvac_mode_x".to_owned();
+        assert_eq!(
+            daikin_hvac::encode_settings(&invalid),
+            Err(EncoderError::UnknownValue(
+                "hvac_mode".to_owned(),
+                "hvac_mode_x".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn test3() {
+        let settings: daikin_hvac::Settings = serde_json::from_str(
+            r#"{"comfort_mode":false,"mode":"hvac_mode_cool","power":true,"temperature":22,"fan_speed":"notch2","swing":true,"powerful":false,"econo":false,"timer_on":true,"timer_on_minutes":649,"timer_off":false,"timer_off_minutes":1536}"#,
+        )
+        .unwrap();
+        let result = daikin_hvac::encode_settings(&settings);
+        let expected = daikin_hvac::DaikinHvacBuilder::new()
+            .mode(daikin_hvac::HvacMode::Cool)
+            .switch(true)
+            .temperature(22)
+            .fan(daikin_hvac::FanSpeed::Notch2)
+            .swing(daikin_hvac::Swing::Enabled)
+            .on_timer(true)
+            .on_timer_duration_minutes(0x289)
+            .off_timer_duration_minutes(0x600)
+            .build()
+            .unwrap()
+            .encode();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test4() {
+        let mut settings: daikin_hvac::Settings = serde_json::from_str(
+            r#"{"comfort_mode":false,"mode":"hvac_mode_cool","power":true,"temperature":22,"fan_speed":"notch2","swing":true,"powerful":false,"econo":false,"timer_on":false,"timer_on_minutes":0,"timer_off":false,"timer_off_minutes":0,"horizontal_swing":"position3"}"#,
+        )
+        .unwrap();
+        let markandspaces = daikin_hvac::encode_settings(&settings).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let result = daikin_hvac::decode(&frames);
+        assert_eq!(
+            result[0].0.get("horizontal_swing").map(|s| s.as_str()),
+            Some("position3")
+        );
         //
-        decorded.insert("manufacturer".to_owned(), "daikin".to_owned());
-        Some(InfraredRemoteControlCode(decorded))
-    } else {
-        None
+        settings.horizontal_swing = Some("left".to_owned());
+        assert_eq!(
+            daikin_hvac::encode_settings(&settings),
+            Err(EncoderError::UnknownValue(
+                "horizontal_swing".to_owned(),
+                "left".to_owned()
+            ))
+        );
     }
 }
 
@@ -322,9 +1198,11 @@ mod decode_tests {
     use crate::infrared_remote::*;
     use crate::parsing;
 
+    // 実機の受信データ(冷房 22度 風量2 スイング オンタイマー)
+    const RXDATA: &str = "[417,448,418,450,417,450,417,449,418,448,417,25329,3450,1747,418,1315,419,446,419,449,417,450,417,1315,418,449,417,449,417,449,417,450,417,1314,418,450,417,1315,417,1315,418,448,418,1315,418,1315,417,1315,418,1315,417,1315,418,450,417,448,419,1312,419,449,417,449,417,451,416,449,419,448,417,449,417,450,417,448,419,448,417,449,417,1316,417,450,416,1314,419,448,418,449,417,449,418,1314,418,1314,419,449,417,450,417,448,419,447,418,450,417,448,419,448,417,449,417,449,418,448,418,449,418,449,417,448,419,448,417,449,418,449,417,1315,418,1314,418,1315,418,448,419,1313,419,448,419,1313,419,1313,420,34665,3450,1748,418,1314,419,447,418,450,416,450,417,1316,416,450,418,448,417,449,418,449,417,1315,418,449,418,1315,417,1315,417,451,416,1316,417,1314,418,1314,418,1316,416,1316,417,450,417,450,417,1313,418,451,416,449,417,449,418,449,416,450,417,449,417,450,416,449,417,450,416,451,416,449,419,1314,418,448,417,449,417,451,416,449,418,1317,416,450,415,450,417,449,418,448,417,450,416,450,417,451,416,448,417,450,417,449,417,450,417,450,417,449,418,448,417,453,414,449,417,449,417,450,416,450,416,1316,418,449,417,1315,417,449,418,1315,418,449,417,34670,3449,1750,416,1316,417,451,416,449,416,450,417,1315,418,450,416,450,415,451,417,449,417,1316,416,450,418,1315,416,1316,417,449,418,1315,418,1315,417,1316,417,1315,417,1315,418,450,416,450,417,1316,416,454,412,450,416,451,416,450,416,450,416,450,416,451,416,451,417,448,417,450,416,449,418,450,417,448,417,450,417,450,416,450,416,450,417,450,416,1317,416,1316,416,450,416,1317,417,1315,417,1316,417,449,418,448,417,452,414,451,416,1316,416,1316,417,450,416,1316,417,449,418,450,417,449,416,450,417,450,417,450,416,450,416,451,415,450,419,448,416,1316,417,1316,417,1315,418,1317,416,450,417,449,417,1315,417,450,416,450,420,448,415,450,416,450,417,450,416,450,416,450,417,449,418,1315,417,451,416,449,417,1316,416,451,416,450,416,451,415,1316,417,451,416,1316,416,450,418,450,415,450,416,451,416,451,416,449,417,450,416,450,417,450,416,450,416,450,416,1316,417,1317,417,447,418,450,416,451,416,451,416,449,416,450,417,450,417,449,416,450,416,452,414,451,416,450,416,451,415,451,416,451,415,450,416,451,416,1317,416,451,415,451,416,451,415,452,414,451,415,1317,417,1316,416,451,416,451,416,450,415,453,414,451,415,451,416,451,415,452,414,452,415,450,417,451,416,451,414,451,416,451,416,451,414,451,416,451,415,451,416,1317,416,451,415,1317,416,1316,417,1316,416,451,416]";
+
     #[test]
     fn test1() {
-        let rxdata= "[417,448,418,450,417,450,417,449,418,448,417,25329,3450,1747,418,1315,419,446,419,449,417,450,417,1315,418,449,417,449,417,449,417,450,417,1314,418,450,417,1315,417,1315,418,448,418,1315,418,1315,417,1315,418,1315,417,1315,418,450,417,448,419,1312,419,449,417,449,417,451,416,449,419,448,417,449,417,450,417,448,419,448,417,449,417,1316,417,450,416,1314,419,448,418,449,417,449,418,1314,418,1314,419,449,417,450,417,448,419,447,418,450,417,448,419,448,417,449,417,449,418,448,418,449,418,449,417,448,419,448,417,449,418,449,417,1315,418,1314,418,1315,418,448,419,1313,419,448,419,1313,419,1313,420,34665,3450,1748,418,1314,419,447,418,450,416,450,417,1316,416,450,418,448,417,449,418,449,417,1315,418,449,418,1315,417,1315,417,451,416,1316,417,1314,418,1314,418,1316,416,1316,417,450,417,450,417,1313,418,451,416,449,417,449,418,449,416,450,417,449,417,450,416,449,417,450,416,451,416,449,419,1314,418,448,417,449,417,451,416,449,418,1317,416,450,415,450,417,449,418,448,417,450,416,450,417,451,416,448,417,450,417,449,417,450,417,450,417,449,418,448,417,453,414,449,417,449,417,450,416,450,416,1316,418,449,417,1315,417,449,418,1315,418,449,417,34670,3449,1750,416,1316,417,451,416,449,416,450,417,1315,418,450,416,450,415,451,417,449,417,1316,416,450,418,1315,416,1316,417,449,418,1315,418,1315,417,1316,417,1315,417,1315,418,450,416,450,417,1316,416,454,412,450,416,451,416,450,416,450,416,450,416,451,416,451,417,448,417,450,416,449,418,450,417,448,417,450,417,450,416,450,416,450,417,450,416,1317,416,1316,416,450,416,1317,417,1315,417,1316,417,449,418,448,417,452,414,451,416,1316,416,1316,417,450,416,1316,417,449,418,450,417,449,416,450,417,450,417,450,416,450,416,451,415,450,419,448,416,1316,417,1316,417,1315,418,1317,416,450,417,449,417,1315,417,450,416,450,420,448,415,450,416,450,417,450,416,450,416,450,417,449,418,1315,417,451,416,449,417,1316,416,451,416,450,416,451,415,1316,417,451,416,1316,416,450,418,450,415,450,416,451,416,451,416,449,417,450,416,450,417,450,416,450,416,450,416,1316,417,1317,417,447,418,450,416,451,416,451,416,449,416,450,417,450,417,449,416,450,416,452,414,451,416,450,416,451,415,451,416,451,415,450,416,451,416,1317,416,451,415,451,416,451,415,452,414,451,415,1317,417,1316,416,451,416,451,416,450,415,453,414,451,415,451,416,451,415,452,414,452,415,450,417,451,416,451,414,451,416,451,416,451,414,451,416,451,415,451,416,1317,416,451,415,1317,416,1316,417,1316,416,451,416]";
         let mut decorded: HashMap<String, String> = HashMap::new();
         decorded.insert("comfort_mode".to_owned(), "disabled".to_owned());
         decorded.insert("hvac_mode".to_owned(), "hvac_mode_cool".to_owned());
@@ -337,13 +1215,282 @@ mod decode_tests {
         decorded.insert("fan_speed".to_owned(), "notch2".to_owned());
         decorded.insert("powerful".to_owned(), "disabled".to_owned());
         decorded.insert("swing".to_owned(), "enabled".to_owned());
+        decorded.insert("horizontal_swing".to_owned(), "stop".to_owned());
         decorded.insert("checksum".to_owned(), "116".to_owned());
         decorded.insert("manufacturer".to_owned(), "daikin".to_owned());
         //
-        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
-        let frames = decord_receiving_data(&markandspaces).unwrap();
+        let markandspaces = parsing::parse_infrared_code_text(RXDATA).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
         let result = daikin_hvac::decode(&frames);
         let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
-        assert_eq!(result, expected)
+        assert_eq!(result, expected);
+        // 復号結果から状態にする
+        let hvac = daikin_hvac::DaikinHvac::try_from(result[0].clone()).unwrap();
+        assert_eq!(
+            serde_json::to_string(&hvac).unwrap(),
+            r#"{"comfort_mode":false,"temperature":22,"mode":"hvac_mode_cool","switch":true,"fan":"notch2","swing":true,"horizontal_swing":"stop","powerful":false,"econo":false,"on_timer":600,"off_timer":null}"#
+        );
+        let mut code = result[0].clone();
+        code.0.remove("hvac_mode");
+        assert_eq!(
+            daikin_hvac::DaikinHvac::try_from(code),
+            Err(ValidationError::MissingField("hvac_mode".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test2() {
+        let markandspaces = daikin_hvac::DaikinHvacBuilder::new()
+            .mode(daikin_hvac::HvacMode::Cool)
+            .switch(true)
+            .temperature(22)
+            .fan(daikin_hvac::FanSpeed::Notch2)
+            .swing(daikin_hvac::Swing::Enabled)
+            .build()
+            .unwrap()
+            .encode()
+            .unwrap();
+        let mut frames = decord_receiving_data(&markandspaces, None).unwrap();
+        // 第3フレームのチェックサムに1を足す
+        if let Some(DecordedInfraredRemoteFrame::Aeha(bits)) = frames.get_mut(2) {
+            let checksum = u8::from(folding_to_lsb_first(&bits[0x12 * 8..0x13 * 8]));
+            let corrupted = unfolding_from_lsb_first(LsbFirst::from(checksum.wrapping_add(1)));
+            bits[0x12 * 8..0x13 * 8].copy_from_slice(&corrupted);
+        }
+        let result = daikin_hvac::decode(&frames);
+        assert_eq!(result, vec![]);
+        assert!(matches!(
+            daikin_hvac::decode_sub(&frames),
+            Err(HvacDecodeError::ChecksumMismatch { expected, actual }) if actual == expected.wrapping_add(1)
+        ));
+    }
+
+    // 実機の受信データの第3フレームのoffset番目のバイトを書き換えてチェックサムを合わせる
+    fn mutate_third_frame(offset: usize, value: u8) -> Vec<DecordedInfraredRemoteFrame> {
+        let markandspaces = parsing::parse_infrared_code_text(RXDATA).unwrap();
+        let mut frames = decord_receiving_data(&markandspaces, None).unwrap();
+        // 先頭にプリアンブルがあるので第3フレームは最後のフレーム
+        if let Some(DecordedInfraredRemoteFrame::Aeha(bits)) = frames.last_mut() {
+            *bits = test_frames::rewrite_with_sum_checksum(bits, |octets| octets[offset] = value);
+        }
+        frames
+    }
+
+    #[test]
+    fn test3() {
+        // 温度 40度
+        let frames = mutate_third_frame(0x6, 40 * 2);
+        assert_eq!(
+            daikin_hvac::decode_sub(&frames),
+            Err(HvacDecodeError::TemperatureOutOfRange(10, 32, 40))
+        );
+        assert_eq!(daikin_hvac::decode(&frames), vec![]);
+        // モード
+        let frames = mutate_third_frame(0x5, 0x19);
+        assert_eq!(
+            daikin_hvac::decode_sub(&frames),
+            Err(HvacDecodeError::UnknownMode(0x1))
+        );
+        // 風量
+        let frames = mutate_third_frame(0x8, 0x1f);
+        assert_eq!(
+            daikin_hvac::decode_sub(&frames),
+            Err(HvacDecodeError::UnknownFanSpeed(0x1))
+        );
+        // 風向
+        let frames = mutate_third_frame(0x8, 0x41);
+        assert_eq!(
+            daikin_hvac::decode_sub(&frames),
+            Err(HvacDecodeError::UnknownSwing(0x1))
+        );
+        // 左右風向(知らない値は項目を出さずに復号する)
+        let frames = mutate_third_frame(0x9, 0x06);
+        let result = daikin_hvac::decode_sub(&frames).unwrap().unwrap();
+        assert_eq!(result.0.get("horizontal_swing"), None);
+        assert_eq!(result.0.get("swing").map(|s| s.as_str()), Some("enabled"));
+        // 書き換えなし
+        let frames = mutate_third_frame(0x4, 0x00);
+        assert!(matches!(daikin_hvac::decode_sub(&frames), Ok(Some(_))));
+    }
+
+    #[test]
+    fn test4() {
+        // 左右風向 位置3
+        let frames = mutate_third_frame(0x9, 0x03);
+        let result = daikin_hvac::decode(&frames);
+        assert_eq!(
+            result[0].0.get("horizontal_swing").map(|s| s.as_str()),
+            Some("position3")
+        );
+        // 左右風向 スイング
+        let frames = mutate_third_frame(0x9, 0x0f);
+        let result = daikin_hvac::decode(&frames);
+        assert_eq!(
+            result[0].0.get("horizontal_swing").map(|s| s.as_str()),
+            Some("swing")
+        );
+    }
+
+    #[test]
+    fn test5() {
+        // 快適モード
+        let markandspaces = daikin_hvac::DaikinHvacBuilder::new()
+            .comfort_mode(true)
+            .mode(daikin_hvac::HvacMode::Cool)
+            .switch(true)
+            .temperature(22)
+            .fan(daikin_hvac::FanSpeed::Notch2)
+            .swing(daikin_hvac::Swing::Enabled)
+            .on_timer_duration_minutes(0x600)
+            .off_timer_duration_minutes(0x600)
+            .build()
+            .unwrap()
+            .encode()
+            .unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let result = daikin_hvac::decode(&frames);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].0["comfort_mode"], "enabled");
+    }
+
+    #[test]
+    fn test6() {
+        // 冷房 26℃ 風量3 スイング無し 電源入
+        // (実機の受信データが無いので配置表から組み立てた)
+        let mut octets = [0u8; 35];
+        octets[0..4].copy_from_slice(&[0x11, 0xda, 0x17, 0x18]);
+        octets[5] = 0x39;
+        octets[6] = 26 * 2;
+        octets[8] = 0x50;
+        let markandspaces = protocol_aeha::encode_frame(&test_frames::with_sum_checksum(&octets));
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("hvac_mode".to_owned(), "hvac_mode_cool".to_owned());
+        decorded.insert("power_switch".to_owned(), "power_on".to_owned());
+        decorded.insert("temperature".to_owned(), "26".to_owned());
+        decorded.insert("fan_speed".to_owned(), "notch3".to_owned());
+        decorded.insert("swing".to_owned(), "disabled".to_owned());
+        decorded.insert("checksum".to_owned(), "215".to_owned());
+        decorded.insert("manufacturer".to_owned(), "daikin".to_owned());
+        //
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let expected = vec![InfraredRemoteControlCode(decorded)];
+        assert_eq!(daikin_hvac::decode_brc52(&frames), expected);
+        // 無線リモコンの復号器とは干渉しない
+        assert_eq!(daikin_hvac::decode(&frames), vec![]);
+        assert_eq!(decord_ir_frames(&frames), expected);
+        // 無線リモコンの信号はBRC52として復号しない
+        let frames = decord_receiving_data(
+            &daikin_hvac::DaikinHvacBuilder::new()
+                .mode(daikin_hvac::HvacMode::Cool)
+                .switch(true)
+                .temperature(26)
+                .fan(daikin_hvac::FanSpeed::Notch3)
+                .build()
+                .unwrap()
+                .encode()
+                .unwrap(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(daikin_hvac::decode_brc52(&frames), vec![]);
+        assert_eq!(daikin_hvac::decode(&frames).len(), 1);
+        // チェックサム不一致
+        octets[34] = 0xff;
+        let frames = decord_receiving_data(&protocol_aeha::encode_frame(&octets), None).unwrap();
+        assert!(matches!(
+            daikin_hvac::decode_brc52_sub(&frames),
+            Err(HvacDecodeError::ChecksumMismatch { .. })
+        ));
+    }
+}
+
+#[cfg(test)]
+mod builder_tests {
+    use crate::infrared_remote::*;
+    use daikin_hvac::{DaikinHvacBuilder, FanSpeed, HorizontalSwing, HvacMode, Swing};
+
+    #[test]
+    fn test1() {
+        let hvac = DaikinHvacBuilder::new().build().unwrap();
+        assert!(!hvac.comfort_mode());
+        assert_eq!(hvac.mode(), HvacMode::Auto);
+        assert_eq!(hvac.temperature(), 26);
+        assert!(!hvac.switch());
+        assert_eq!(hvac.fan(), FanSpeed::Auto);
+        assert_eq!(hvac.swing(), Swing::Disabled);
+        assert_eq!(hvac.horizontal_swing(), HorizontalSwing::Stop);
+        assert_eq!(hvac.on_timer(), None);
+        assert_eq!(hvac.off_timer(), None);
+    }
+
+    #[test]
+    fn test2() {
+        let hvac = DaikinHvacBuilder::new()
+            .mode(HvacMode::Cool)
+            .switch(true)
+            .temperature(22)
+            .fan(FanSpeed::Notch2)
+            .swing(Swing::Enabled)
+            .horizontal_swing(HorizontalSwing::Position2)
+            .on_timer(true)
+            .on_timer_duration_hour(10)
+            .build()
+            .unwrap();
+        assert_eq!(hvac.on_timer(), Some(10 * 60));
+        let frames = decord_receiving_data(&hvac.encode().unwrap(), None).unwrap();
+        let result = &daikin_hvac::decode(&frames)[0];
+        assert_eq!(result.hvac_mode(), Some("hvac_mode_cool"));
+        assert_eq!(result.temperature(), Some(22));
+        assert_eq!(result.fan_speed(), Some("notch2"));
+        assert_eq!(
+            result.0.get("horizontal_swing").map(|s| s.as_str()),
+            Some("position2")
+        );
+        assert_eq!(
+            result.0.get("timer_on_duration_hour").map(|s| s.as_str()),
+            Some("10")
+        );
+    }
+
+    #[test]
+    fn test3() {
+        let result = DaikinHvacBuilder::new().temperature(33).build();
+        assert_eq!(
+            result,
+            Err(ValidationError::TemperatureOutOfRange(10, 32, 33))
+        );
+        let result = DaikinHvacBuilder::new().temperature(9).build();
+        assert_eq!(
+            result,
+            Err(ValidationError::TemperatureOutOfRange(10, 32, 9))
+        );
+        let result = DaikinHvacBuilder::new().powerful(true).econo(true).build();
+        assert!(matches!(
+            result,
+            Err(ValidationError::InconsistentSettings(_))
+        ));
+        let result = DaikinHvacBuilder::new()
+            .on_timer(true)
+            .on_timer_duration_hour(69)
+            .build();
+        assert_eq!(
+            result,
+            Err(ValidationError::ValueOutOfRange(
+                "timer_on_minutes".to_owned(),
+                0xfff,
+                69 * 60
+            ))
+        );
+        let result = DaikinHvacBuilder::new()
+            .off_timer_duration_minutes(0x1000)
+            .build();
+        assert_eq!(
+            result,
+            Err(ValidationError::ValueOutOfRange(
+                "timer_off_minutes".to_owned(),
+                0xfff,
+                0x1000
+            ))
+        );
     }
 }
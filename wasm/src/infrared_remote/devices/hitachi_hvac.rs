@@ -53,7 +53,7 @@ use std::collections::HashMap;
 // |   |   |   |   |   |   |   |  1+2+16+32=51      --   |   |   |   |   |   |   |   |  4+8+64+128=204
 // 1   1   0   0   1   1   0   0 == 33h             --   1   1   0   0   1   1   0   0 == cch
 //
-const FRAME_HEADER: [LsbFirst; 9] = [
+pub(crate) const FRAME_HEADER: [LsbFirst; 9] = [
     LsbFirst::new(0x01),
     LsbFirst::new(0x10),
     LsbFirst::new(0x00),
@@ -66,7 +66,7 @@ const FRAME_HEADER: [LsbFirst; 9] = [
 ];
 
 //
-const HVAC_MODE: Lazy<HashMap<u8, &'static str>> = Lazy::new(|| {
+static HVAC_MODE: Lazy<HashMap<u8, &'static str>> = Lazy::new(|| {
     let mut hm = HashMap::new();
     hm.insert(0x3, "hvac_mode_cool");
     hm.insert(0x4, "hvac_mode_dry_cool");
@@ -80,7 +80,7 @@ const HVAC_MODE: Lazy<HashMap<u8, &'static str>> = Lazy::new(|| {
 });
 
 //
-const POWER_SWITCH: Lazy<HashMap<Bit, &'static str>> = Lazy::new(|| {
+static POWER_SWITCH: Lazy<HashMap<Bit, &'static str>> = Lazy::new(|| {
     let mut hm = HashMap::new();
     hm.insert(Bit::Lo, "power_off");
     hm.insert(Bit::Hi, "power_on");
@@ -88,7 +88,7 @@ const POWER_SWITCH: Lazy<HashMap<Bit, &'static str>> = Lazy::new(|| {
 });
 
 //
-const FAN_SPEED: Lazy<HashMap<u8, &'static str>> = Lazy::new(|| {
+static FAN_SPEED: Lazy<HashMap<u8, &'static str>> = Lazy::new(|| {
     let mut hm = HashMap::new();
     hm.insert(0x1, "silent");
     hm.insert(0x2, "low");
@@ -98,6 +98,33 @@ const FAN_SPEED: Lazy<HashMap<u8, &'static str>> = Lazy::new(|| {
     hm
 });
 
+//
+static VERTICAL_SWING: Lazy<HashMap<u8, &'static str>> = Lazy::new(|| {
+    let mut hm = HashMap::new();
+    hm.insert(0x0, "auto");
+    hm.insert(0x1, "notch1");
+    hm.insert(0x2, "notch2");
+    hm.insert(0x3, "notch3");
+    hm.insert(0x4, "notch4");
+    hm.insert(0x5, "notch5");
+    hm.insert(0x6, "notch6");
+    hm.insert(0x7, "notch7");
+    hm.insert(0x8, "notch8");
+    hm.insert(0x9, "notch9");
+    hm.insert(0xa, "stop");
+    hm
+});
+
+//
+static HORIZONTAL_SWING: Lazy<HashMap<u8, &'static str>> = Lazy::new(|| {
+    let mut hm = HashMap::new();
+    hm.insert(0x0, "auto");
+    hm.insert(0x1, "left");
+    hm.insert(0x2, "middle");
+    hm.insert(0x3, "right");
+    hm
+});
+
 /// デコード
 pub fn decode(frames: &[DecordedInfraredRemoteFrame]) -> Vec<InfraredRemoteControlCode> {
     let target_frames: Vec<&[Bit]> = frames
@@ -128,54 +155,118 @@ pub fn decode(frames: &[DecordedInfraredRemoteFrame]) -> Vec<InfraredRemoteContr
     //
     target_frames
         .iter()
-        .flat_map(|&aeha| {
-            // data required 296bits
-            let octets = aeha.get(0..296).map(|x| {
-                pack_to_octets(x)
-                    .iter()
-                    .map(|x| folding_to_lsb_first(x))
-                    .collect::<Vec<LsbFirst>>()
-            })?;
-            let mut decorded: HashMap<String, String> = HashMap::new();
-            // 温度
-            decorded.insert(
-                "temperature".to_owned(),
-                (u8::from(octets[13]) >> 2 & 0x1f).to_string(),
-            );
-            // モード
-            HVAC_MODE
-                .get(&(u8::from(octets[25]) & 0xf))
-                .map(|&item| decorded.insert("hvac_mode".to_owned(), item.to_owned()));
-            // 風量
-            FAN_SPEED
-                .get(&(u8::from(octets[25]) >> 4 & 0xf))
-                .map(|&item| decorded.insert("fan_speed".to_owned(), item.to_owned()));
-            // 電源
-            let power_switch = Bit::try_from(u8::from(octets[27]) >> 4 & 1).ok()?;
-            POWER_SWITCH
-                .get(&power_switch)
-                .map(|&item| decorded.insert("power_switch".to_owned(), item.to_owned()));
-            // オフタイマ―
-            decorded.insert("off_timer_duration_minutes".to_owned(), {
-                let lower_nibble = u8::from(octets[17]) >> 4 & 0xf;
-                let higher_byte = u8::from(octets[19]);
-                let minutes = (higher_byte as u16) << 4 | lower_nibble as u16;
-                minutes.to_string()
-            });
-            // オンタイマ―
-            decorded.insert("on_timer_duration_minutes".to_owned(), {
-                let lower_byte = u8::from(octets[21]);
-                let higher_nibble = u8::from(octets[23]);
-                let minutes = (higher_nibble as u16) << 8 | lower_byte as u16;
-                minutes.to_string()
-            });
-            //
-            decorded.insert("manufacturer".to_owned(), "hitachi".to_owned());
-            Some(InfraredRemoteControlCode(decorded))
-        })
+        .flat_map(|&aeha| ok_or_log("hitachi", decode_sub(aeha)))
         .collect::<Vec<InfraredRemoteControlCode>>()
 }
 
+/// 1フレームをデコード
+pub fn decode_sub(aeha: &[Bit]) -> Result<Option<InfraredRemoteControlCode>, HvacDecodeError> {
+    // data required 296bits
+    let Some(octets) = aeha.get(0..296).map(|x| {
+        pack_to_octets(x)
+            .iter()
+            .map(|x| folding_to_lsb_first(x))
+            .collect::<Vec<LsbFirst>>()
+    }) else {
+        return Ok(None);
+    };
+    let mut decorded: HashMap<String, String> = HashMap::new();
+    // 温度
+    decorded.insert(
+        "temperature".to_owned(),
+        (u8::from(octets[13]) >> 2 & 0x1f).to_string(),
+    );
+    // モード
+    let mode = u8::from(octets[25]) & 0xf;
+    let item = HVAC_MODE
+        .get(&mode)
+        .copied()
+        .ok_or(HvacDecodeError::UnknownMode(mode))?;
+    decorded.insert("hvac_mode".to_owned(), item.to_string());
+    // 風量
+    let fan = u8::from(octets[25]) >> 4 & 0xf;
+    let item = FAN_SPEED
+        .get(&fan)
+        .copied()
+        .ok_or(HvacDecodeError::UnknownFanSpeed(fan))?;
+    decorded.insert("fan_speed".to_owned(), item.to_string());
+    // 電源
+    let power_switch = if u8::from(octets[27]) >> 4 & 1 == 0 {
+        Bit::Lo
+    } else {
+        Bit::Hi
+    };
+    POWER_SWITCH
+        .get(&power_switch)
+        .map(|&item| decorded.insert("power_switch".to_owned(), item.to_owned()));
+    // 上下風向
+    VERTICAL_SWING
+        .get(&(u8::from(octets[15]) >> 4 & 0xf))
+        .map(|&item| decorded.insert("vertical_swing".to_owned(), item.to_owned()));
+    // 左右風向
+    HORIZONTAL_SWING
+        .get(&(u8::from(octets[16]) & 0xf))
+        .map(|&item| decorded.insert("horizontal_swing".to_owned(), item.to_owned()));
+    // オフタイマ―
+    // 18バイト目の上位4ビットと20バイト目の12ビット
+    decorded.insert("off_timer_duration_minutes".to_owned(), {
+        let bits = [&aeha[17 * 8 + 4..18 * 8], &aeha[19 * 8..20 * 8]].concat();
+        folding_to_lsb_first_u16(&bits).to_string()
+    });
+    // オンタイマ―
+    // 22バイト目と24バイト目の下位4ビットの12ビット
+    decorded.insert("on_timer_duration_minutes".to_owned(), {
+        let bits = [&aeha[21 * 8..22 * 8], &aeha[23 * 8..23 * 8 + 4]].concat();
+        folding_to_lsb_first_u16(&bits).to_string()
+    });
+    //
+    decorded.insert("manufacturer".to_owned(), "hitachi".to_owned());
+    Ok(Some(InfraredRemoteControlCode(decorded)))
+}
+
+/// フレームのバイト数
+const FRAME_OCTETS: usize = 53;
+
+// 実機の受信データ(decode_tests test1)の10バイト目からの値
+// 10バイト目以降は(値, 反転した値)の組なので, 値だけを並べる
+const DATA_TEMPLATE: [u8; 22] = [
+    0x92, 0x13, 0x58, 0x00, 0x00, 0x00, 0x00, 0x00, 0x56, 0xf1, 0x00, 0x00, 0x80, 0x03, 0x01, 0x88,
+    0x00, 0x00, 0xff, 0xff, 0xff, 0xff,
+];
+
+/// エンコード
+/// 風向, タイマーなどの指定しない項目は実機の受信データと同じにする
+pub fn encode(
+    temperature: u8,
+    mode: &str,
+    fan: &str,
+    power: bool,
+) -> Result<Vec<MarkAndSpaceMicros>, EncoderError> {
+    if !(16..=32).contains(&temperature) {
+        return Err(EncoderError::TemperatureOutOfRange(16, 32, temperature));
+    }
+    let hvac_mode = find_key_by_value(&HVAC_MODE, "hvac_mode", mode)?;
+    let fan_speed = find_key_by_value(&FAN_SPEED, "fan_speed", fan)?;
+    let mut octets = [0u8; FRAME_OCTETS];
+    for (octet, header) in octets.iter_mut().zip(FRAME_HEADER.iter()) {
+        *octet = u8::from(*header);
+    }
+    for (index, &value) in DATA_TEMPLATE.iter().enumerate() {
+        octets[9 + 2 * index] = value;
+    }
+    // 温度
+    octets[13] = octets[13] & 0x83 | temperature << 2;
+    // モードと風量
+    octets[25] = fan_speed << 4 | hvac_mode;
+    // 電源
+    octets[27] = octets[27] & !0x10 | (power as u8) << 4;
+    // 反転した値を組にする
+    for index in (9..FRAME_OCTETS).step_by(2) {
+        octets[index + 1] = !octets[index];
+    }
+    Ok(protocol_aeha::encode_frame(&octets))
+}
+
 #[cfg(test)]
 mod decode_tests {
     use crate::infrared_remote::*;
@@ -189,14 +280,157 @@ mod decode_tests {
         decorded.insert("hvac_mode".to_owned(), "hvac_mode_heat".to_owned());
         decorded.insert("fan_speed".to_owned(), "auto".to_owned());
         decorded.insert("power_switch".to_owned(), "power_on".to_owned());
+        decorded.insert("vertical_swing".to_owned(), "auto".to_owned());
         decorded.insert("off_timer_duration_minutes".to_owned(), "0".to_owned());
         decorded.insert("on_timer_duration_minutes".to_owned(), "0".to_owned());
         decorded.insert("manufacturer".to_owned(), "hitachi".to_owned());
         //
         let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
-        let frames = decord_receiving_data(&markandspaces).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
         let result = hitachi_hvac::decode(&frames);
         let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
         assert_eq!(result, expected)
     }
+
+    #[test]
+    fn test2() {
+        let rxdata = "6D0458078200400011003000120010001100110013000F0011001100120010001200100012001000110010001200100012001000110011001200300012001000120010001200100012001000110010001200100012001000120010001100110012001000110011001200100012001000110011001100100012001000120010001200300012001000120030001100300011003100120030001100300012003000120010001200300012003000110030001200300012002F00120030001200300012002F00120030001100110011001100120010001100110010001100120010001200100012001000120010001100110013002F00110030001200110011001000120030001200300012002F00120030001100110011001100110031001100310012000F00120010001200100012003000110011001200100011003100100011001100110012003000120030001200100012002F00120030001100110012003000120030001100110011003100110031001100100011001100110031001100110012001000110011001100110011001000120030001200300012001000120030001100300011003100110011001100110012001000110030001200300011001100120030001200100012002F001100310011003100110011001200100011003100110011001100300012001000110011001200100011001100100011001200100011001100120010001200300012002F00110031001100310012002F001200300011003100120030001100110010001100110011001200100013000F00120010001100110013000F0011003000110031001100310010003100120030001100310011003000120030001100110011001100110011001100110010001100110011001100110011001100120030001200300012002F0012003000110031001000310011003100110031001100110010001100120010001200100011001100110011001200100011001100110030001100310011003100100031001100310011003100100031001100310011001100120010001100110011001100110011001000110011001100110011001200300011003100100031001100310011003100100031001200300012003000110011001100300012003000110011001100310011001100110031000F0013001100300011001100120010001100310011001100110031001100110011003100100031001100120010001100110011001100310011003100100031001100310010001200110031001000310011003100100012001100110011001100110011001100100010001100110011001100110011001100110011001100110011001100110030001100310011003100100031001100310011003100100031001100310011001100120010001000120011001100100012001000110011001100110011001100310011003100100031001100310011003100100031001100310010003200110011000F0013001000110010001200110011001000120011001100110031001000310010003200110031000F003200110031001100300011003100110011001000320010003200100012001000120011001100110011001100110010001200100012000F0013000F0032001100310011003000100032001100310011003000110031001100110011001100110011001100110010001200100011001000120011001100100032001000320010003100100032001100310010003100110031001100110011001100100012000F00320010001300100011001000120011003100110031000F00320011003100110011001100310011003000110031001100110011001100110011001100110010001200100011001100110011001100110011001100310010003100110031001100310010003100110031001100310010003100120010001100110010001200110011001000120011001000110011001100110011003100110031001000310011003100110031001000310011003100110031001100300012003000110031001100300011003100110031001000310011003100110011001100110011001100100012001100100011001100110011001200100011003100110030001100310011003100100031001100310011003100110031001000120010001100110011001200100011001100110011001100110011001100100031001100310011003100110030001200300012003000110030001200300012001000110011001100110011001100100011001100110012001000120010001200300012002F00120030001200300012002F00110031001200300012003000110010001200100011001100110011001200100012001000110011001100110010004F03";
+        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None)
+            .unwrap()
+            .into_iter()
+            .map(|frame| match frame {
+                DecordedInfraredRemoteFrame::Aeha(mut bits) => {
+                    // オフタイマ― 0x123 = 291分
+                    bits[17 * 8 + 4..18 * 8].copy_from_slice(&vec_bits!("1100"));
+                    bits[19 * 8..20 * 8].copy_from_slice(&vec_bits!("0100_1000"));
+                    // オンタイマ― 0x2d0 = 720分
+                    bits[21 * 8..22 * 8].copy_from_slice(&vec_bits!("0000_1011"));
+                    bits[23 * 8..23 * 8 + 4].copy_from_slice(&vec_bits!("0100"));
+                    DecordedInfraredRemoteFrame::Aeha(bits)
+                }
+                other => other,
+            })
+            .collect::<Vec<DecordedInfraredRemoteFrame>>();
+        let result = hitachi_hvac::decode(&frames);
+        assert_eq!(result.len(), 1);
+        assert_eq!(
+            result[0].0.get("off_timer_duration_minutes"),
+            Some(&"291".to_owned())
+        );
+        assert_eq!(
+            result[0].0.get("on_timer_duration_minutes"),
+            Some(&"720".to_owned())
+        );
+    }
+
+    #[test]
+    fn test3() {
+        let rxdata = "6D0458078200400011003000120010001100110013000F0011001100120010001200100012001000110010001200100012001000110011001200300012001000120010001200100012001000110010001200100012001000120010001100110012001000110011001200100012001000110011001100100012001000120010001200300012001000120030001100300011003100120030001100300012003000120010001200300012003000110030001200300012002F00120030001200300012002F00120030001100110011001100120010001100110010001100120010001200100012001000120010001100110013002F00110030001200110011001000120030001200300012002F00120030001100110011001100110031001100310012000F00120010001200100012003000110011001200100011003100100011001100110012003000120030001200100012002F00120030001100110012003000120030001100110011003100110031001100100011001100110031001100110012001000110011001100110011001000120030001200300012001000120030001100300011003100110011001100110012001000110030001200300011001100120030001200100012002F001100310011003100110011001200100011003100110011001100300012001000110011001200100011001100100011001200100011001100120010001200300012002F00110031001100310012002F001200300011003100120030001100110010001100110011001200100013000F00120010001100110013000F0011003000110031001100310010003100120030001100310011003000120030001100110011001100110011001100110010001100110011001100110011001100120030001200300012002F0012003000110031001000310011003100110031001100110010001100120010001200100011001100110011001200100011001100110030001100310011003100100031001100310011003100100031001100310011001100120010001100110011001100110011001000110011001100110011001200300011003100100031001100310011003100100031001200300012003000110011001100300012003000110011001100310011001100110031000F0013001100300011001100120010001100310011001100110031001100110011003100100031001100120010001100110011001100310011003100100031001100310010001200110031001000310011003100100012001100110011001100110011001100100010001100110011001100110011001100110011001100110011001100110030001100310011003100100031001100310011003100100031001100310011001100120010001000120011001100100012001000110011001100110011001100310011003100100031001100310011003100100031001100310010003200110011000F0013001000110010001200110011001000120011001100110031001000310010003200110031000F003200110031001100300011003100110011001000320010003200100012001000120011001100110011001100110010001200100012000F0013000F0032001100310011003000100032001100310011003000110031001100110011001100110011001100110010001200100011001000120011001100100032001000320010003100100032001100310010003100110031001100110011001100100012000F00320010001300100011001000120011003100110031000F00320011003100110011001100310011003000110031001100110011001100110011001100110010001200100011001100110011001100110011001100310010003100110031001100310010003100110031001100310010003100120010001100110010001200110011001000120011001000110011001100110011003100110031001000310011003100110031001000310011003100110031001100300012003000110031001100300011003100110031001000310011003100110011001100110011001100100012001100100011001100110011001200100011003100110030001100310011003100100031001100310011003100110031001000120010001100110011001200100011001100110011001100110011001100100031001100310011003100110030001200300012003000110030001200300012001000110011001100110011001100100011001100110012001000120010001200300012002F00120030001200300012002F00110031001200300012003000110010001200100011001100110011001200100012001000110011001100110010004F03";
+        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        let original = decord_receiving_data(&markandspaces, None).unwrap();
+        let frames = original
+            .iter()
+            .cloned()
+            .map(|frame| match frame {
+                DecordedInfraredRemoteFrame::Aeha(mut bits) => {
+                    // 上下風向 notch3
+                    bits[15 * 8 + 4..16 * 8].copy_from_slice(&vec_bits!("1100"));
+                    // 左右風向 left
+                    bits[16 * 8..16 * 8 + 4].copy_from_slice(&vec_bits!("1000"));
+                    DecordedInfraredRemoteFrame::Aeha(bits)
+                }
+                other => other,
+            })
+            .collect::<Vec<DecordedInfraredRemoteFrame>>();
+        let result = hitachi_hvac::decode(&frames);
+        assert_eq!(result.len(), 1);
+        assert_eq!(
+            result[0].0.get("vertical_swing"),
+            Some(&"notch3".to_owned())
+        );
+        assert_eq!(
+            result[0].0.get("horizontal_swing"),
+            Some(&"left".to_owned())
+        );
+        // 風向以外は変わらない
+        let expected = hitachi_hvac::decode(&original);
+        assert_eq!(
+            result[0].0.get("on_timer_duration_minutes"),
+            expected[0].0.get("on_timer_duration_minutes")
+        );
+    }
+
+    #[test]
+    fn test4() {
+        let markandspaces = hitachi_hvac::encode(22, "hvac_mode_heat", "auto", true).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let result = hitachi_hvac::decode(&frames);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].temperature(), Some(22));
+        assert_eq!(result[0].hvac_mode(), Some("hvac_mode_heat"));
+        assert_eq!(result[0].fan_speed(), Some("auto"));
+        assert_eq!(result[0].power_switch(), Some(true));
+        //
+        let markandspaces = hitachi_hvac::encode(16, "hvac_mode_cool", "silent", false).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let result = hitachi_hvac::decode(&frames);
+        assert_eq!(result[0].temperature(), Some(16));
+        assert_eq!(result[0].hvac_mode(), Some("hvac_mode_cool"));
+        assert_eq!(result[0].fan_speed(), Some("silent"));
+        assert_eq!(result[0].power_switch(), Some(false));
+    }
+
+    #[test]
+    fn test5() {
+        assert_eq!(
+            hitachi_hvac::encode(33, "hvac_mode_heat", "auto", true),
+            Err(EncoderError::TemperatureOutOfRange(16, 32, 33))
+        );
+        assert_eq!(
+            hitachi_hvac::encode(22, "hvac_mode_fan", "auto", true),
+            Err(EncoderError::UnknownValue(
+                "hvac_mode".to_owned(),
+                "hvac_mode_fan".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn test6() {
+        let markandspaces = hitachi_hvac::encode(22, "hvac_mode_heat", "auto", true).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let octets = test_frames::octets_from_bits(frames[0].bits().unwrap());
+        let decode = |offset: usize, value: u8| {
+            let mut octets = octets.clone();
+            octets[offset] = value;
+            hitachi_hvac::decode_sub(&test_frames::bits_from_octets(&octets))
+        };
+        // 範囲外
+        assert_eq!(decode(25, 0x50), Err(HvacDecodeError::UnknownMode(0)));
+        assert_eq!(decode(25, 0x06), Err(HvacDecodeError::UnknownFanSpeed(0)));
+        // データ長が足りない
+        assert_eq!(
+            hitachi_hvac::decode_sub(&test_frames::bits_from_octets(&octets[0..30])),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn test7() {
+        // 実機の受信データと同じ信号になる
+        let rxdata = "6D0458078200400011003000120010001100110013000F0011001100120010001200100012001000110010001200100012001000110011001200300012001000120010001200100012001000110010001200100012001000120010001100110012001000110011001200100012001000110011001100100012001000120010001200300012001000120030001100300011003100120030001100300012003000120010001200300012003000110030001200300012002F00120030001200300012002F00120030001100110011001100120010001100110010001100120010001200100012001000120010001100110013002F00110030001200110011001000120030001200300012002F00120030001100110011001100110031001100310012000F00120010001200100012003000110011001200100011003100100011001100110012003000120030001200100012002F00120030001100110012003000120030001100110011003100110031001100100011001100110031001100110012001000110011001100110011001000120030001200300012001000120030001100300011003100110011001100110012001000110030001200300011001100120030001200100012002F001100310011003100110011001200100011003100110011001100300012001000110011001200100011001100100011001200100011001100120010001200300012002F00110031001100310012002F001200300011003100120030001100110010001100110011001200100013000F00120010001100110013000F0011003000110031001100310010003100120030001100310011003000120030001100110011001100110011001100110010001100110011001100110011001100120030001200300012002F0012003000110031001000310011003100110031001100110010001100120010001200100011001100110011001200100011001100110030001100310011003100100031001100310011003100100031001100310011001100120010001100110011001100110011001000110011001100110011001200300011003100100031001100310011003100100031001200300012003000110011001100300012003000110011001100310011001100110031000F0013001100300011001100120010001100310011001100110031001100110011003100100031001100120010001100110011001100310011003100100031001100310010001200110031001000310011003100100012001100110011001100110011001100100010001100110011001100110011001100110011001100110011001100110030001100310011003100100031001100310011003100100031001100310011001100120010001000120011001100100012001000110011001100110011001100310011003100100031001100310011003100100031001100310010003200110011000F0013001000110010001200110011001000120011001100110031001000310010003200110031000F003200110031001100300011003100110011001000320010003200100012001000120011001100110011001100110010001200100012000F0013000F0032001100310011003000100032001100310011003000110031001100110011001100110011001100110010001200100011001000120011001100100032001000320010003100100032001100310010003100110031001100110011001100100012000F00320010001300100011001000120011003100110031000F00320011003100110011001100310011003000110031001100110011001100110011001100110010001200100011001100110011001100110011001100310010003100110031001100310010003100110031001100310010003100120010001100110010001200110011001000120011001000110011001100110011003100110031001000310011003100110031001000310011003100110031001100300012003000110031001100300011003100110031001000310011003100110011001100110011001100100012001100100011001100110011001200100011003100110030001100310011003100100031001100310011003100110031001000120010001100110011001200100011001100110011001100110011001100100031001100310011003100110030001200300012003000110030001200300012001000110011001100110011001100100011001100110012001000120010001200300012002F00120030001200300012002F00110031001200300012003000110010001200100011001100110011001200100012001000110011001100110010004F03";
+        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        let expected = decord_receiving_data(&markandspaces, None).unwrap();
+        let markandspaces = hitachi_hvac::encode(22, "hvac_mode_heat", "auto", true).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let octets = |frames: &[DecordedInfraredRemoteFrame]| {
+            frames
+                .iter()
+                .find_map(|frame| frame.bits())
+                .map(test_frames::octets_from_bits)
+        };
+        assert_eq!(octets(&frames), octets(&expected));
+        assert!(octets(&frames).is_some());
+    }
 }
@@ -0,0 +1,186 @@
+// Copyright (c) 2023 Akihiro Yamamoto.
+// Licensed under the MIT License <https://spdx.org/licenses/MIT.html>
+// See LICENSE file in the project root for full license information.
+//
+use crate::infrared_remote::*;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+//
+// LG HVAC address 2bytes value is
+// LSB first                                        -- MSB first
+// 0x88 00                                          -- 0x11 00
+//
+const ADDRESS: [LsbFirst; 2] = [LsbFirst::new(0x88), LsbFirst::new(0x00)];
+
+//
+static HVAC_MODE: Lazy<HashMap<u16, &'static str>> = Lazy::new(|| {
+    let mut hm = HashMap::new();
+    hm.insert(0x0, "hvac_mode_cool");
+    hm.insert(0x1, "hvac_mode_dry");
+    hm.insert(0x2, "hvac_mode_fan");
+    hm.insert(0x4, "hvac_mode_heat");
+    hm
+});
+
+//
+static POWER_SWITCH: Lazy<HashMap<u16, &'static str>> = Lazy::new(|| {
+    let mut hm = HashMap::new();
+    hm.insert(0x0, "power_off");
+    hm.insert(0x1, "power_on");
+    hm
+});
+
+//
+static FAN_SPEED: Lazy<HashMap<u16, &'static str>> = Lazy::new(|| {
+    let mut hm = HashMap::new();
+    hm.insert(0x0, "low");
+    hm.insert(0x1, "med");
+    hm.insert(0x2, "high");
+    hm.insert(0x5, "auto");
+    hm
+});
+
+/// デコード
+pub fn decode(frames: &[DecordedInfraredRemoteFrame]) -> Vec<InfraredRemoteControlCode> {
+    frames
+        .iter()
+        .flat_map(|f: &DecordedInfraredRemoteFrame| match f {
+            DecordedInfraredRemoteFrame::Nec(bits) => ok_or_log("lg", decode_sub(bits)),
+            _ => None,
+        })
+        .collect::<Vec<InfraredRemoteControlCode>>()
+}
+
+/// 1フレームをデコード
+pub fn decode_sub(bits: &[Bit]) -> Result<Option<InfraredRemoteControlCode>, HvacDecodeError> {
+    // data required 32bits
+    let Some(address) = bits.get(0..16).map(|x| {
+        pack_to_octets(x)
+            .iter()
+            .map(|&x| LsbFirst::from(x))
+            .collect::<Vec<LsbFirst>>()
+    }) else {
+        return Ok(None);
+    };
+    if address != ADDRESS {
+        return Ok(None);
+    }
+    let Some(command) = bits.get(16..32).map(|x| {
+        pack_to_octets(x)
+            .iter()
+            .map(|&x| u8::from(LsbFirst::from(x)))
+            .collect::<Vec<u8>>()
+    }) else {
+        return Ok(None);
+    };
+    // ===================================================================================================================
+    // 下位16ビット(LSB first)
+    //
+    // bit      | Description
+    // 0-3      | Temperature (+15)
+    // 4-6      | Mode
+    // 7-9      | Fan speed
+    // 11       | Power (bit 3 of the 2nd command byte)
+    // ===================================================================================================================
+    let command = u16::from(command[1]) << 8 | u16::from(command[0]);
+    let mut decorded: HashMap<String, String> = HashMap::new();
+    // 温度
+    decorded.insert("temperature".to_owned(), (15 + (command & 0xf)).to_string());
+    // モード
+    let mode = command >> 4 & 0x7;
+    let item = HVAC_MODE
+        .get(&mode)
+        .copied()
+        .ok_or(HvacDecodeError::UnknownMode(mode as u8))?;
+    decorded.insert("hvac_mode".to_owned(), item.to_string());
+    // 風量
+    let fan = command >> 7 & 0x7;
+    let item = FAN_SPEED
+        .get(&fan)
+        .copied()
+        .ok_or(HvacDecodeError::UnknownFanSpeed(fan as u8))?;
+    decorded.insert("fan_speed".to_owned(), item.to_string());
+    // 電源
+    POWER_SWITCH
+        .get(&(command >> 11 & 0x1))
+        .map(|&item| decorded.insert("power_switch".to_owned(), item.to_owned()));
+    //
+    decorded.insert("manufacturer".to_owned(), "lg".to_owned());
+    Ok(Some(InfraredRemoteControlCode(decorded)))
+}
+
+#[cfg(test)]
+mod decode_tests {
+    use crate::infrared_remote::*;
+    use crate::parsing;
+
+    #[test]
+    fn test1() {
+        // 冷房, 24度, 風量自動, 電源オン
+        let markandspaces = protocol_nec::encode_nec([0x88, 0x00, 0x89, 0x0a]);
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("temperature".to_owned(), "24".to_owned());
+        decorded.insert("hvac_mode".to_owned(), "hvac_mode_cool".to_owned());
+        decorded.insert("fan_speed".to_owned(), "auto".to_owned());
+        decorded.insert("power_switch".to_owned(), "power_on".to_owned());
+        decorded.insert("manufacturer".to_owned(), "lg".to_owned());
+        //
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let result = lg_hvac::decode(&frames);
+        let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
+        assert_eq!(result, expected);
+        // 東芝のアドレスとは一致しない
+        assert_eq!(toshiba_tv::decode(&frames), vec![]);
+        assert_eq!(decord_ir_frames(&frames), expected)
+    }
+
+    #[test]
+    fn test2() {
+        // 暖房, 18度, 風量弱, 電源オフ
+        let markandspaces = protocol_nec::encode_nec([0x88, 0x00, 0x43, 0x00]);
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("temperature".to_owned(), "18".to_owned());
+        decorded.insert("hvac_mode".to_owned(), "hvac_mode_heat".to_owned());
+        decorded.insert("fan_speed".to_owned(), "low".to_owned());
+        decorded.insert("power_switch".to_owned(), "power_off".to_owned());
+        decorded.insert("manufacturer".to_owned(), "lg".to_owned());
+        //
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let result = lg_hvac::decode(&frames);
+        let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn test3() {
+        // 東芝の信号は一致しない
+        let rxdata= "5601A900180015001800140018001400190013001900140019001400170040001700150018003F0019003E0018003E0019003F0019003E00170040001800140019003E001800150018003F00180014001800140019003F0018001400170016001700150018003F001800140018003F0018003F001800140019003F0018003F0018003E0019004F03";
+        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        assert_eq!(lg_hvac::decode(&frames), vec![]);
+        assert_eq!(toshiba_tv::decode(&frames), decord_ir_frames(&frames))
+    }
+
+    #[test]
+    fn test4() {
+        // 範囲外
+        assert_eq!(
+            lg_hvac::decode_sub(&test_frames::bits_from_octets(&[0x88, 0x00, 0x39, 0x0a])),
+            Err(HvacDecodeError::UnknownMode(3))
+        );
+        assert_eq!(
+            lg_hvac::decode_sub(&test_frames::bits_from_octets(&[0x88, 0x00, 0x89, 0x09])),
+            Err(HvacDecodeError::UnknownFanSpeed(3))
+        );
+        let frames =
+            decord_receiving_data(&protocol_nec::encode_nec([0x88, 0x00, 0x39, 0x0a]), None)
+                .unwrap();
+        assert_eq!(lg_hvac::decode(&frames), vec![]);
+        // アドレスが一致しない
+        assert_eq!(
+            lg_hvac::decode_sub(&test_frames::bits_from_octets(&[0x89, 0x00, 0x89, 0x0a])),
+            Ok(None)
+        );
+    }
+}
@@ -4,6 +4,7 @@
 //
 use crate::infrared_remote::*;
 use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 //
@@ -37,7 +38,7 @@ use std::collections::HashMap;
 //
 // 5th byte "00000000"
 //
-const FRAME_HEADER: [LsbFirst; 5] = [
+pub(crate) const FRAME_HEADER: [LsbFirst; 5] = [
     LsbFirst::new(0x23),
     LsbFirst::new(0xcb),
     LsbFirst::new(0x26),
@@ -63,9 +64,166 @@ const POWER_SWITCH: Lazy<HashMap<bool, &'static str>> = Lazy::new(|| {
     hm
 });
 
-/// デコード
-pub fn decode(frames: &[DecordedInfraredRemoteFrame]) -> Vec<InfraredRemoteControlCode> {
-    let target_frames: Vec<&[Bit]> = frames
+/// 風向
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Vane {
+    Auto,
+    Up,
+    Notch2,
+    Notch3,
+    Notch4,
+    Notch5,
+    Down,
+}
+
+impl Vane {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Vane::Auto => "auto",
+            Vane::Up => "up",
+            Vane::Notch2 => "notch2",
+            Vane::Notch3 => "notch3",
+            Vane::Notch4 => "notch4",
+            Vane::Notch5 => "notch5",
+            Vane::Down => "down",
+        }
+    }
+}
+
+impl From<Vane> for u8 {
+    fn from(vane: Vane) -> Self {
+        match vane {
+            Vane::Auto => 0,
+            Vane::Up => 1,
+            Vane::Notch2 => 2,
+            Vane::Notch3 => 3,
+            Vane::Notch4 => 4,
+            Vane::Notch5 => 5,
+            Vane::Down => 6,
+        }
+    }
+}
+
+impl TryFrom<u8> for Vane {
+    type Error = HvacDecodeError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Vane::Auto),
+            1 => Ok(Vane::Up),
+            2 => Ok(Vane::Notch2),
+            3 => Ok(Vane::Notch3),
+            4 => Ok(Vane::Notch4),
+            5 => Ok(Vane::Notch5),
+            6 => Ok(Vane::Down),
+            _ => Err(HvacDecodeError::UnknownSwing(value)),
+        }
+    }
+}
+
+/// 風量
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FanSpeed {
+    Auto,
+    Silent,
+    Low,
+    Med,
+    High,
+    Max,
+}
+
+impl FanSpeed {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FanSpeed::Auto => "auto",
+            FanSpeed::Silent => "silent",
+            FanSpeed::Low => "low",
+            FanSpeed::Med => "med",
+            FanSpeed::High => "high",
+            FanSpeed::Max => "max",
+        }
+    }
+}
+
+impl From<FanSpeed> for u8 {
+    fn from(fan: FanSpeed) -> Self {
+        match fan {
+            FanSpeed::Auto => 0,
+            FanSpeed::Silent => 0x80,
+            FanSpeed::Low => 1,
+            FanSpeed::Med => 2,
+            FanSpeed::High => 3,
+            FanSpeed::Max => 4,
+        }
+    }
+}
+
+impl TryFrom<u8> for FanSpeed {
+    type Error = HvacDecodeError;
+
+    /// 下位3ビットが風量, 下位3ビットが0で最上位ビットが1なら静音
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value & 0x7 {
+            0 if value & 0x80 != 0 => Ok(FanSpeed::Silent),
+            0 => Ok(FanSpeed::Auto),
+            1 => Ok(FanSpeed::Low),
+            2 => Ok(FanSpeed::Med),
+            3 => Ok(FanSpeed::High),
+            4 => Ok(FanSpeed::Max),
+            n => Err(HvacDecodeError::UnknownFanSpeed(n)),
+        }
+    }
+}
+
+/// 三菱電機エアコンの設定
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MitsubishiElectricHvac {
+    pub temperature: u8,
+    pub mode1: u8,
+    pub switch: bool,
+    pub vane: Vane,
+    pub fan: FanSpeed,
+    /// 符号化する時は計算し直す
+    #[serde(default)]
+    pub checksum: u8,
+    /// 5から9バイト目の設定に含まれないビット
+    #[serde(default)]
+    pub unmodelled_bits: [u8; 5],
+    /// 10から16バイト目(意味の分からないバイト)
+    #[serde(default)]
+    pub unmodelled_octets: [u8; 7],
+}
+
+/// 5から9バイト目の設定に含まれるビット
+/// 風量の最上位ビットは下位3ビットが0の時だけ静音を表す
+fn modelled_mask(octets: &[u8]) -> [u8; 5] {
+    let fan = if octets[8] & 0x7 == 0 { 0x87 } else { 0x07 };
+    [0x20, 0x38, 0x0f, fan, 0x07]
+}
+
+impl From<MitsubishiElectricHvac> for InfraredRemoteControlCode {
+    fn from(hvac: MitsubishiElectricHvac) -> Self {
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("temperature".to_owned(), hvac.temperature.to_string());
+        HVAC_MODE
+            .get(&hvac.mode1)
+            .map(|&item| decorded.insert("hvac_mode".to_owned(), item.to_owned()));
+        POWER_SWITCH
+            .get(&hvac.switch)
+            .map(|&item| decorded.insert("power_switch".to_owned(), item.to_owned()));
+        decorded.insert("vane".to_owned(), hvac.vane.as_str().to_owned());
+        decorded.insert("fan_speed".to_owned(), hvac.fan.as_str().to_owned());
+        decorded.insert("checksum".to_owned(), hvac.checksum.to_string());
+        decorded.insert("manufacturer".to_owned(), "mitsubishi electric".to_owned());
+        InfraredRemoteControlCode(decorded)
+    }
+}
+
+/// ヘッダが一致するフレーム
+fn target_frames(frames: &[DecordedInfraredRemoteFrame]) -> Vec<&[Bit]> {
+    frames
         .iter()
         .flat_map(|fr: &DecordedInfraredRemoteFrame| match fr {
             DecordedInfraredRemoteFrame::Aeha(aeha) => {
@@ -85,44 +243,99 @@ pub fn decode(frames: &[DecordedInfraredRemoteFrame]) -> Vec<InfraredRemoteContr
             }
             _ => None,
         })
-        .collect();
-    //
-    target_frames
+        .collect()
+}
+
+/// 1フレームのデコード
+/// データ長が足りない場合は Ok(None)
+pub fn decode_sub(aeha: &[Bit]) -> Result<Option<MitsubishiElectricHvac>, HvacDecodeError> {
+    // data required 144bits
+    let Some(octets) = aeha.get(0..144).map(|x| {
+        pack_to_octets(x)
+            .iter()
+            .map(|x| folding_to_lsb_first(x))
+            .collect::<Vec<LsbFirst>>()
+    }) else {
+        return Ok(None);
+    };
+    // チェックサム
+    // 0から16バイト目までの総和の下位8ビット
+    let checksum = u8::from(octets[17]);
+    let expected_checksum = octets[0..17]
         .iter()
-        .flat_map(|&aeha| {
-            // data required 144bits
-            let octets = aeha.get(0..144).map(|x| {
-                pack_to_octets(x)
-                    .iter()
-                    .map(|x| folding_to_lsb_first(x))
-                    .collect::<Vec<LsbFirst>>()
-            })?;
-            let mut decorded: HashMap<String, String> = HashMap::new();
-            // 温度
-            let temp = 16 + (u8::from(octets[7]) & 0xf);
-            decorded.insert("temperature".to_owned(), temp.to_string());
-            // モード
-            let hvac_mode = u8::from(octets[6]) >> 3 & 0x7;
-            HVAC_MODE
-                .get(&hvac_mode)
-                .map(|&item| decorded.insert("hvac_mode".to_owned(), item.to_owned()));
-            // 電源
-            let power_switch = Bit::try_from(u8::from(octets[5]) >> 5 & 1).ok()?;
-            POWER_SWITCH
-                .get(&power_switch.into())
-                .map(|&item| decorded.insert("power_switch".to_owned(), item.to_owned()));
-            // チェックサム
-            let checksum = u8::from(octets[17]);
-            decorded.insert("checksum".to_owned(), checksum.to_string());
-            //
-            decorded.insert("manufacturer".to_owned(), "mitsubishi electric".to_owned());
-            Some(InfraredRemoteControlCode(decorded))
-        })
+        .fold(0u8, |acc, &x| acc.wrapping_add(u8::from(x)));
+    if checksum != expected_checksum {
+        return Err(HvacDecodeError::ChecksumMismatch {
+            expected: expected_checksum,
+            actual: checksum,
+        });
+    }
+    Ok(Some(MitsubishiElectricHvac {
+        // 温度
+        temperature: 16 + (u8::from(octets[7]) & 0xf),
+        // モード
+        mode1: u8::from(octets[6]) >> 3 & 0x7,
+        // 電源
+        switch: u8::from(octets[5]) >> 5 & 1 == 1,
+        // 風向
+        vane: Vane::try_from(u8::from(octets[9]) & 0x7)?,
+        // 風量
+        fan: FanSpeed::try_from(u8::from(octets[8]))?,
+        checksum,
+        unmodelled_bits: {
+            let octets = octets.iter().map(|&x| u8::from(x)).collect::<Vec<u8>>();
+            let mask = modelled_mask(&octets);
+            [5, 6, 7, 8, 9].map(|index| octets[index] & !mask[index - 5])
+        },
+        unmodelled_octets: [10, 11, 12, 13, 14, 15, 16].map(|index| u8::from(octets[index])),
+    }))
+}
+
+/// デコード
+pub fn decode(frames: &[DecordedInfraredRemoteFrame]) -> Vec<InfraredRemoteControlCode> {
+    target_frames(frames)
+        .into_iter()
+        .flat_map(|aeha| ok_or_log("mitsubishi electric", decode_sub(aeha)))
+        .map(InfraredRemoteControlCode::from)
         .collect::<Vec<InfraredRemoteControlCode>>()
 }
 
+/// エンコード
+/// チェックサムは設定の値を使わずに計算し直す
+pub fn encode_mitsubishi_electric_hvac(state: &MitsubishiElectricHvac) -> Vec<MarkAndSpaceMicros> {
+    let mut octets = [0u8; 18];
+    for (octet, header) in octets.iter_mut().zip(FRAME_HEADER.iter()) {
+        *octet = u8::from(*header);
+    }
+    // 設定に含まれないビットとバイトは元のまま
+    octets[5..10].copy_from_slice(&state.unmodelled_bits);
+    octets[10..17].copy_from_slice(&state.unmodelled_octets);
+    // 電源
+    octets[5] |= (state.switch as u8) << 5;
+    // モード
+    octets[6] |= (state.mode1 & 0x7) << 3;
+    // 温度
+    octets[7] |= state.temperature.wrapping_sub(16) & 0xf;
+    // 風量
+    octets[8] = match state.fan {
+        FanSpeed::Auto | FanSpeed::Silent => octets[8] & !0x80,
+        _ => octets[8],
+    } | u8::from(state.fan);
+    // 風向
+    octets[9] |= u8::from(state.vane);
+    // チェックサム
+    // 0から16バイト目までの総和の下位8ビット
+    octets[17] = octets[0..17]
+        .iter()
+        .fold(0u8, |acc, &x| acc.wrapping_add(x));
+    protocol_aeha::encode_frame(&octets)
+}
+
 #[cfg(test)]
 mod decode_tests {
+    use crate::infrared_remote::mitsubishi_electric_hvac::{
+        FanSpeed, MitsubishiElectricHvac, Vane,
+    };
     use crate::infrared_remote::*;
     use crate::parsing;
 
@@ -133,13 +346,171 @@ mod decode_tests {
         decorded.insert("temperature".to_owned(), "26".to_owned());
         decorded.insert("hvac_mode".to_owned(), "cool".to_owned());
         decorded.insert("power_switch".to_owned(), "on".to_owned());
+        decorded.insert("vane".to_owned(), "auto".to_owned());
+        decorded.insert("fan_speed".to_owned(), "med".to_owned());
         decorded.insert("checksum".to_owned(), "105".to_owned());
         decorded.insert("manufacturer".to_owned(), "mitsubishi electric".to_owned());
         //
         let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
-        let frames = decord_receiving_data(&markandspaces).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
         let result = mitsubishi_electric_hvac::decode(&frames);
         let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
         assert_eq!(result, expected)
     }
+
+    #[test]
+    fn test2() {
+        let rxdata= "840044001200320012003100120011001200110010001200110033001200110012001100120031001100320013001000120032001200100013001000130031001200310013001000110032001300310012001100120011001200310011001200120011001000330012001100110012001200110012001100120010001300100013001000130010001300100012001100130010001200110011001200120011001200110012001000110012001300100013001000120011001200310013001000130010001300100012001100120011001200310013003100120010001300310012001100120010001100330012001000130031001200110012001100120011001200100013001000130031001000130012001000130010001300100012003200100033001200110010001300120011001200100013001000130010001200320012001000130010001300100013001000130010001100120012001100120011001200110012001000130010001100120013001000110012001300100012001100120011001000130012001100120011001200100013001000130010001100120013001000120011001200110012001100120011001200110012001000130031001200110012001100120011001200110012001000130031001200110012001000130031001300100013001000130010001300100012001100120011001200110012001100120011001200100013001000130010001300100013001000120011001200110012003100130010001300100012003100110012001200310013003100120011001200EB01";
+        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        let mut frames = decord_receiving_data(&markandspaces, None).unwrap();
+        // チェックサムを書き換える
+        for frame in frames.iter_mut() {
+            if let DecordedInfraredRemoteFrame::Aeha(bits) = frame {
+                if let Some(checksum_bits) = bits.get_mut(17 * 8..18 * 8) {
+                    let checksum = u8::from(folding_to_lsb_first(checksum_bits));
+                    let corrupted =
+                        unfolding_from_lsb_first(LsbFirst::from(checksum.wrapping_add(1)));
+                    checksum_bits.copy_from_slice(&corrupted);
+                }
+            }
+        }
+        let result = mitsubishi_electric_hvac::decode(&frames);
+        assert_eq!(result, vec![]);
+    }
+
+    // 風向と風量を書き換えてチェックサムを付け直す
+    fn rewrite(frames: &[DecordedInfraredRemoteFrame], fan: u8, vane: u8) -> Vec<Bit> {
+        let bits = frames
+            .iter()
+            .find_map(|frame| match frame {
+                DecordedInfraredRemoteFrame::Aeha(bits) => Some(bits.clone()),
+                _ => None,
+            })
+            .unwrap();
+        test_frames::rewrite_with_sum_checksum(&bits[0..144], |octets| {
+            octets[8] = fan;
+            octets[9] = octets[9] & !0x7 | vane;
+        })
+    }
+
+    #[test]
+    fn test3() {
+        let rxdata= "840044001200320012003100120011001200110010001200110033001200110012001100120031001100320013001000120032001200100013001000130031001200310013001000110032001300310012001100120011001200310011001200120011001000330012001100110012001200110012001100120010001300100013001000130010001300100012001100130010001200110011001200120011001200110012001000110012001300100013001000120011001200310013001000130010001300100012001100120011001200310013003100120010001300310012001100120010001100330012001000130031001200110012001100120011001200100013001000130031001000130012001000130010001300100012003200100033001200110010001300120011001200100013001000130010001200320012001000130010001300100013001000130010001100120012001100120011001200110012001000130010001100120013001000110012001300100012001100120011001000130012001100120011001200100013001000130010001100120013001000120011001200110012001100120011001200110012001000130031001200110012001100120011001200110012001000130031001200110012001000130031001300100013001000130010001300100012001100120011001200110012001100120011001200100013001000130010001300100013001000120011001200110012003100130010001300100012003100110012001200310013003100120011001200EB01";
+        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let vanes = [
+            Vane::Auto,
+            Vane::Up,
+            Vane::Notch2,
+            Vane::Notch3,
+            Vane::Notch4,
+            Vane::Notch5,
+            Vane::Down,
+        ];
+        for (n, vane) in vanes.into_iter().enumerate() {
+            let bits = rewrite(&frames, 0x03, n as u8);
+            let hvac = mitsubishi_electric_hvac::decode_sub(&bits)
+                .unwrap()
+                .unwrap();
+            assert_eq!(hvac.vane, vane);
+            assert_eq!(hvac.fan, FanSpeed::High);
+            assert_eq!(hvac.temperature, 26);
+        }
+        let bits = rewrite(&frames, 0x80, 0);
+        let hvac = mitsubishi_electric_hvac::decode_sub(&bits)
+            .unwrap()
+            .unwrap();
+        assert_eq!(hvac.fan, FanSpeed::Silent);
+        let bits = rewrite(&frames, 0x04, 0);
+        let hvac = mitsubishi_electric_hvac::decode_sub(&bits)
+            .unwrap()
+            .unwrap();
+        assert_eq!(hvac.fan, FanSpeed::Max);
+        // 範囲外
+        let bits = rewrite(&frames, 0x00, 7);
+        assert_eq!(
+            mitsubishi_electric_hvac::decode_sub(&bits),
+            Err(HvacDecodeError::UnknownSwing(7))
+        );
+        let bits = rewrite(&frames, 0x05, 0);
+        assert_eq!(
+            mitsubishi_electric_hvac::decode_sub(&bits),
+            Err(HvacDecodeError::UnknownFanSpeed(5))
+        );
+        // データ長が足りない
+        assert_eq!(mitsubishi_electric_hvac::decode_sub(&bits[0..64]), Ok(None));
+    }
+
+    #[test]
+    fn test4() {
+        // test1 と同じ信号
+        let rxdata= "840044001200320012003100120011001200110010001200110033001200110012001100120031001100320013001000120032001200100013001000130031001200310013001000110032001300310012001100120011001200310011001200120011001000330012001100110012001200110012001100120010001300100013001000130010001300100012001100130010001200110011001200120011001200110012001000110012001300100013001000120011001200310013001000130010001300100012001100120011001200310013003100120010001300310012001100120010001100330012001000130031001200110012001100120011001200100013001000130031001000130012001000130010001300100012003200100033001200110010001300120011001200100013001000130010001200320012001000130010001300100013001000130010001100120012001100120011001200110012001000130010001100120013001000110012001300100012001100120011001000130012001100120011001200100013001000130010001100120013001000120011001200110012001100120011001200110012001000130031001200110012001100120011001200110012001000130031001200110012001000130031001300100013001000130010001300100012001100120011001200110012001100120011001200100013001000130010001300100013001000120011001200110012003100130010001300100012003100110012001200310013003100120011001200EB01";
+        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let bits = frames
+            .iter()
+            .find_map(|frame| match frame {
+                DecordedInfraredRemoteFrame::Aeha(bits) => Some(bits.clone()),
+                _ => None,
+            })
+            .unwrap();
+        let hvac = mitsubishi_electric_hvac::decode_sub(&bits)
+            .unwrap()
+            .unwrap();
+        //
+        let redecode = |hvac: &MitsubishiElectricHvac| {
+            let encoded = mitsubishi_electric_hvac::encode_mitsubishi_electric_hvac(hvac);
+            let frames = decord_receiving_data(&encoded, None).unwrap();
+            let DecordedInfraredRemoteFrame::Aeha(bits) = &frames[0] else {
+                panic!("not AEHA");
+            };
+            mitsubishi_electric_hvac::decode_sub(bits).unwrap().unwrap()
+        };
+        // 元の信号と同じになる
+        let encoded = mitsubishi_electric_hvac::encode_mitsubishi_electric_hvac(&hvac);
+        let frames = decord_receiving_data(&encoded, None).unwrap();
+        assert_eq!(
+            test_frames::octets_from_bits(frames[0].bits().unwrap()),
+            test_frames::octets_from_bits(&bits)
+        );
+        let result = redecode(&hvac);
+        assert_eq!(result, hvac);
+        // 設定を変えても設定に含まれないビットは保つ
+        let changed = MitsubishiElectricHvac {
+            fan: FanSpeed::Auto,
+            vane: Vane::Down,
+            ..hvac
+        };
+        let result = redecode(&changed);
+        assert_eq!(result.fan, FanSpeed::Auto);
+        assert_eq!(result.vane, Vane::Down);
+        assert_eq!(result.unmodelled_octets, hvac.unmodelled_octets);
+        // 自動の時は風量の最上位ビットを使わない
+        let mut expected = hvac.unmodelled_bits;
+        expected[3] &= !0x80;
+        assert_eq!(result.unmodelled_bits, expected);
+        assert_eq!(redecode(&result), result);
+    }
+
+    #[test]
+    fn test5() {
+        // 省略した項目は0
+        let hvac: MitsubishiElectricHvac = serde_json::from_str(
+            r#"{"temperature":26,"mode1":3,"switch":true,"vane":"notch3","fan":"silent"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            hvac,
+            MitsubishiElectricHvac {
+                temperature: 26,
+                mode1: 3,
+                switch: true,
+                vane: Vane::Notch3,
+                fan: FanSpeed::Silent,
+                checksum: 0,
+                unmodelled_bits: [0; 5],
+                unmodelled_octets: [0; 7],
+            }
+        );
+    }
 }
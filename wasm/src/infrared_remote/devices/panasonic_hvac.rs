@@ -4,6 +4,8 @@
 //
 use crate::infrared_remote::*;
 use once_cell::sync::Lazy;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 //
@@ -35,7 +37,7 @@ use std::collections::HashMap;
 // |   |   |   |   |   |   |   |                --   |   |   |   |   |   |   |   |
 // 0   0   1   0   0   0   0   0 == 04h         --   0   0   1   0   0   0   0   0 == 20h
 //
-const FRAME_HEADER: [LsbFirst; 4] = [
+pub(crate) const FRAME_HEADER: [LsbFirst; 4] = [
     LsbFirst::new(0x02),
     LsbFirst::new(0x20),
     LsbFirst::new(0xe0),
@@ -93,7 +95,7 @@ const FIRST_FRAME: [LsbFirst; 8] = [
 ];
 
 //
-const HVAC_MODE: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
+static HVAC_MODE: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
     let mut hm = HashMap::new();
     hm.insert(LsbFirst::new(0x0), "hvac_mode_auto");
     hm.insert(LsbFirst::new(0x2), "hvac_mode_dry");
@@ -104,7 +106,7 @@ const HVAC_MODE: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
 });
 
 //
-const POWER_SWITCH: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
+static POWER_SWITCH: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
     let mut hm = HashMap::new();
     hm.insert(LsbFirst::new(0x8), "power_off");
     hm.insert(LsbFirst::new(0x9), "power_on");
@@ -112,7 +114,7 @@ const POWER_SWITCH: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
 });
 
 //
-const SWING: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
+static SWING: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
     let mut hm = HashMap::new();
     hm.insert(LsbFirst::new(0x1), "horizontal");
     hm.insert(LsbFirst::new(0x2), "notch2");
@@ -124,7 +126,7 @@ const SWING: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
 });
 
 //
-const FAN_SPEED: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
+static FAN_SPEED: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
     let mut hm = HashMap::new();
     hm.insert(LsbFirst::new(0x3), "slowest");
     hm.insert(LsbFirst::new(0x4), "notch2");
@@ -136,7 +138,7 @@ const FAN_SPEED: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
 });
 
 //
-const PROFILE: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
+static PROFILE: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
     let mut hm = HashMap::new();
     hm.insert(LsbFirst::new(0x10), "normal");
     hm.insert(LsbFirst::new(0x11), "boost");
@@ -146,11 +148,14 @@ const PROFILE: Lazy<HashMap<LsbFirst, &'static str>> = Lazy::new(|| {
 
 /// デコード
 pub fn decode(frames: &[DecordedInfraredRemoteFrame]) -> Vec<InfraredRemoteControlCode> {
-    decode_sub(frames).map_or(vec![], |a| vec![a])
+    // 復号できなかった場合は理由を記録して空にする
+    ok_or_log("panasonic", decode_sub(frames))
+        .into_iter()
+        .collect()
 }
 
-/// デコード
-fn decode_sub(frames: &[DecordedInfraredRemoteFrame]) -> Option<InfraredRemoteControlCode> {
+/// 第1フレームを確認して第2フレームを取り出す
+fn second_frame_octets(frames: &[DecordedInfraredRemoteFrame]) -> Option<Vec<LsbFirst>> {
     let target_frames: Vec<&[Bit]> = frames
         .iter()
         .flat_map(|fr: &DecordedInfraredRemoteFrame| match fr {
@@ -173,7 +178,6 @@ fn decode_sub(frames: &[DecordedInfraredRemoteFrame]) -> Option<InfraredRemoteCo
         .collect();
     // フレーム2個を取り出す
     if let Some([first_frame, second_frame]) = target_frames.get(0..2) {
-        let mut decorded: HashMap<String, String> = HashMap::new();
         // 第1フレーム
         let actual_first_frame = [
             first_frame.get(0..8).map(|x| folding_to_lsb_first(x))?,
@@ -192,69 +196,736 @@ fn decode_sub(frames: &[DecordedInfraredRemoteFrame]) -> Option<InfraredRemoteCo
         }?;
         // 第2フレーム
         // data required 152bits
-        let octets = second_frame.get(0..152).map(|x| {
+        second_frame.get(0..152).map(|x| {
             pack_to_octets(x)
                 .iter()
                 .map(|x| folding_to_lsb_first(x))
                 .collect::<Vec<LsbFirst>>()
-        })?;
-        // ===================================================================================================================
-        // https://www.analysir.com/blog/2014/12/27/reverse-engineering-panasonic-ac-infrared-protocol/
-        //
-        // offset   | Description           | Length
-        // 05       | Mode, On/Off          | 1
-        // 06       | Temperature           | 1
-        // 08       | Fan / Swing           | 1
-        // 0d       | Profile               | 1
-        // 12       | Checksum              | 1
-        // ===================================================================================================================
-        //
-        // Mode, On/Off
-        //
-        HVAC_MODE
-            .get(&LsbFirst::from(u8::from(octets[0x5]) >> 4 & 0xf))
-            .map(|&item| decorded.insert("hvac_mode".to_owned(), item.to_owned()));
-        POWER_SWITCH
-            .get(&LsbFirst::from(u8::from(octets[0x5]) >> 0 & 0xf))
-            .map(|&item| decorded.insert("power_switch".to_owned(), item.to_owned()));
-        //
-        // Temperature
-        //
+        })
+    } else {
+        None
+    }
+}
+
+/// デコード
+/// パナソニックのエアコンの信号ではない場合はOk(None)を返す
+pub fn decode_sub(
+    frames: &[DecordedInfraredRemoteFrame],
+) -> Result<Option<InfraredRemoteControlCode>, HvacDecodeError> {
+    let Some(octets) = second_frame_octets(frames) else {
+        return Ok(None);
+    };
+    // ===================================================================================================================
+    // https://www.analysir.com/blog/2014/12/27/reverse-engineering-panasonic-ac-infrared-protocol/
+    //
+    // offset   | Description           | Length
+    // 05       | Mode, On/Off          | 1
+    // 06       | Temperature           | 1
+    // 08       | Fan / Swing           | 1
+    // 0a-0c    | Timer                 | 3
+    // 0d       | Profile               | 1
+    // 12       | Checksum              | 1
+    // ===================================================================================================================
+    //
+    // Checksum
+    //
+    // 第2フレームの先頭から0x11番目までの総和の下位8ビット
+    let checksum = octets[0x00..=0x11]
+        .iter()
+        .fold(0u8, |acc, &x| acc.wrapping_add(u8::from(x)));
+    if checksum != u8::from(octets[0x12]) {
+        return Err(HvacDecodeError::ChecksumMismatch {
+            expected: checksum,
+            actual: u8::from(octets[0x12]),
+        });
+    }
+    let mut decorded: HashMap<String, String> = HashMap::new();
+    //
+    // Mode, On/Off
+    //
+    let hvac_mode = u8::from(octets[0x5]) >> 4 & 0xf;
+    let item = HVAC_MODE
+        .get(&LsbFirst::from(hvac_mode))
+        .copied()
+        .ok_or(HvacDecodeError::UnknownMode(hvac_mode))?;
+    decorded.insert("hvac_mode".to_owned(), item.to_owned());
+    // 1,2ビット目はタイマー
+    POWER_SWITCH
+        .get(&LsbFirst::from(u8::from(octets[0x5]) & 0x9))
+        .map(|&item| decorded.insert("power_switch".to_owned(), item.to_owned()));
+    //
+    // Temperature
+    //
+    // Always 0
+    if u8::from(octets[0x6]) & 1 != 0 {
+        return Ok(None);
+    }
+    let temperature = 16 + (u8::from(octets[0x6]) >> 1 & 0xf);
+    if !(16..=30).contains(&temperature) {
+        return Err(HvacDecodeError::TemperatureOutOfRange(16, 30, temperature));
+    }
+    decorded.insert("temperature".to_owned(), temperature.to_string());
+    //
+    // Fan / Swing
+    //
+    let fan_speed = u8::from(octets[0x8]) >> 4 & 0xf;
+    let item = FAN_SPEED
+        .get(&LsbFirst::from(fan_speed))
+        .copied()
+        .ok_or(HvacDecodeError::UnknownFanSpeed(fan_speed))?;
+    decorded.insert("fan_speed".to_owned(), item.to_owned());
+    let swing = u8::from(octets[0x8]) & 0xf;
+    let item = SWING
+        .get(&LsbFirst::from(swing))
+        .copied()
+        .ok_or(HvacDecodeError::UnknownSwing(swing))?;
+    decorded.insert("swing".to_owned(), item.to_owned());
+    //
+    // Timer
+    //
+    // オンタイマーは0x0aバイト目と0x0bバイト目の下位3ビット
+    // オフタイマーは0x0bバイト目の上位4ビットと0x0cバイト目の下位7ビット
+    // 分単位
+    let timer_on = u8::from(octets[0x5]) & 2 != 0;
+    let timer_off = u8::from(octets[0x5]) & 4 != 0;
+    let timer_on_minutes = (u8::from(octets[0xb]) as u16 & 0x7) << 8 | u8::from(octets[0xa]) as u16;
+    let timer_off_minutes =
+        (u8::from(octets[0xc]) as u16 & 0x7f) << 4 | (u8::from(octets[0xb]) as u16) >> 4;
+    let enabled = |b: bool| if b { "enabled" } else { "disabled" };
+    decorded.insert("timer_on_enabled".to_owned(), enabled(timer_on).to_owned());
+    if timer_on {
         decorded.insert(
-            "temperature".to_owned(),
-            (16 + (u8::from(octets[0x6]) >> 1 & 0xf)).to_string(),
+            "timer_on_duration_minutes".to_owned(),
+            timer_on_minutes.to_string(),
         );
-        // Always 0
-        let _ = if u8::from(octets[0x6]) & 1 == 0 {
-            Some(1)
-        } else {
-            None
-        }?;
-        //
-        // Fan / Swing
-        //
-        FAN_SPEED
-            .get(&LsbFirst::from(u8::from(octets[0x8]) >> 4 & 0xf))
-            .map(|&item| decorded.insert("fan_speed".to_owned(), item.to_owned()));
-        SWING
-            .get(&LsbFirst::from(u8::from(octets[0x8]) >> 0 & 0xf))
-            .map(|&item| decorded.insert("swing".to_owned(), item.to_owned()));
-        //
-        // Profile
-        //
-        PROFILE
-            .get(&octets[0xd])
-            .map(|&item| decorded.insert("profile".to_owned(), item.to_owned()));
+    }
+    decorded.insert(
+        "timer_off_enabled".to_owned(),
+        enabled(timer_off).to_owned(),
+    );
+    if timer_off {
+        decorded.insert(
+            "timer_off_duration_minutes".to_owned(),
+            timer_off_minutes.to_string(),
+        );
+    }
+    //
+    // Profile
+    //
+    PROFILE
+        .get(&octets[0xd])
+        .map(|&item| decorded.insert("profile".to_owned(), item.to_owned()));
+    //
+    decorded.insert("checksum".to_owned(), u8::from(octets[0x12]).to_string());
+    decorded.insert("manufacturer".to_owned(), "panasonic".to_owned());
+    Ok(Some(InfraredRemoteControlCode(decorded)))
+}
+
+/// 送信設定
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Settings {
+    pub mode: String,
+    pub power: String,
+    pub temperature: u8,
+    pub fan_speed: String,
+    pub swing: String,
+    pub profile: String,
+}
+
+/// 送信設定から符号化する
+pub fn encode_settings(settings: &Settings) -> Result<Vec<MarkAndSpaceMicros>, EncoderError> {
+    encode(
+        &settings.mode,
+        &settings.power,
+        settings.temperature,
+        &settings.fan_speed,
+        &settings.swing,
+        &settings.profile,
+    )
+}
+
+/// エンコード
+pub fn encode(
+    mode: &str,
+    power: &str,
+    temperature: u8,
+    fan_speed: &str,
+    swing: &str,
+    profile: &str,
+) -> Result<Vec<MarkAndSpaceMicros>, EncoderError> {
+    encode_sub(
+        mode,
+        power,
+        temperature,
+        fan_speed,
+        swing,
+        profile,
+        None,
+        None,
+    )
+}
+
+/// タイマーを設定しない時の値
+const TIMER_UNSET: u16 = 0x600;
+
+/// タイマーを指定してエンコード
+#[allow(clippy::too_many_arguments)]
+fn encode_sub(
+    mode: &str,
+    power: &str,
+    temperature: u8,
+    fan_speed: &str,
+    swing: &str,
+    profile: &str,
+    on_timer: Option<u16>,
+    off_timer: Option<u16>,
+) -> Result<Vec<MarkAndSpaceMicros>, EncoderError> {
+    if !(16..=30).contains(&temperature) {
+        return Err(EncoderError::TemperatureOutOfRange(16, 30, temperature));
+    }
+    let hvac_mode = u8::from(find_key_by_value(&HVAC_MODE, "hvac_mode", mode)?);
+    let power_switch = u8::from(find_key_by_value(&POWER_SWITCH, "power_switch", power)?);
+    let fan_speed = u8::from(find_key_by_value(&FAN_SPEED, "fan_speed", fan_speed)?);
+    let swing = u8::from(find_key_by_value(&SWING, "swing", swing)?);
+    let profile = u8::from(find_key_by_value(&PROFILE, "profile", profile)?);
+    let timer_flags = (on_timer.is_some() as u8) << 1 | (off_timer.is_some() as u8) << 2;
+    let on_minutes = on_timer.unwrap_or(TIMER_UNSET);
+    let off_minutes = off_timer.unwrap_or(TIMER_UNSET);
+    // 第2フレーム
+    let mut octets: [u8; 19] = [
+        0x02,
+        0x20,
+        0xe0,
+        0x04,
+        0x00,
+        hvac_mode << 4 | power_switch | timer_flags,
+        temperature << 1,
+        0x80,
+        fan_speed << 4 | swing,
+        0x0d,
+        (on_minutes & 0xff) as u8,
+        ((off_minutes & 0xf) << 4 | 0x08 | on_minutes >> 8 & 0x7) as u8,
+        (0x80 | off_minutes >> 4 & 0x7f) as u8,
+        profile,
+        0x00,
+        0x86,
+        0x00,
+        0x08,
+        0x00,
+    ];
+    // チェックサム
+    octets[0x12] = octets[..0x12]
+        .iter()
+        .fold(0u8, |acc, &x| acc.wrapping_add(x));
+    //
+    Ok([
+        protocol_aeha::encode_frame(&FIRST_FRAME.map(u8::from)),
+        protocol_aeha::encode_frame(&octets),
+    ]
+    .concat())
+}
+
+/// モード
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HvacMode {
+    Auto,
+    Dry,
+    Cool,
+    Heat,
+    Fan,
+}
+
+impl HvacMode {
+    const ALL: [HvacMode; 5] = [
+        HvacMode::Auto,
+        HvacMode::Dry,
+        HvacMode::Cool,
+        HvacMode::Heat,
+        HvacMode::Fan,
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.as_str() == name)
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            HvacMode::Auto => "hvac_mode_auto",
+            HvacMode::Dry => "hvac_mode_dry",
+            HvacMode::Cool => "hvac_mode_cool",
+            HvacMode::Heat => "hvac_mode_heat",
+            HvacMode::Fan => "hvac_mode_fan",
+        }
+    }
+}
+
+/// 風量
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FanSpeed {
+    Slowest,
+    Notch2,
+    Notch3,
+    Notch4,
+    Notch5,
+    Auto,
+}
+
+impl FanSpeed {
+    const ALL: [FanSpeed; 6] = [
+        FanSpeed::Slowest,
+        FanSpeed::Notch2,
+        FanSpeed::Notch3,
+        FanSpeed::Notch4,
+        FanSpeed::Notch5,
+        FanSpeed::Auto,
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.as_str() == name)
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            FanSpeed::Slowest => "slowest",
+            FanSpeed::Notch2 => "notch2",
+            FanSpeed::Notch3 => "notch3",
+            FanSpeed::Notch4 => "notch4",
+            FanSpeed::Notch5 => "notch5",
+            FanSpeed::Auto => "auto",
+        }
+    }
+}
+
+/// 風向
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Swing {
+    Horizontal,
+    Notch2,
+    Notch3,
+    Notch4,
+    Notch5,
+    Auto,
+}
+
+impl Swing {
+    const ALL: [Swing; 6] = [
+        Swing::Horizontal,
+        Swing::Notch2,
+        Swing::Notch3,
+        Swing::Notch4,
+        Swing::Notch5,
+        Swing::Auto,
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.as_str() == name)
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Swing::Horizontal => "horizontal",
+            Swing::Notch2 => "notch2",
+            Swing::Notch3 => "notch3",
+            Swing::Notch4 => "notch4",
+            Swing::Notch5 => "notch5",
+            Swing::Auto => "auto",
+        }
+    }
+}
+
+/// 運転
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Profile {
+    Normal,
+    Boost,
+    Quiet,
+}
+
+impl Profile {
+    const ALL: [Profile; 3] = [Profile::Normal, Profile::Boost, Profile::Quiet];
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.as_str() == name)
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Profile::Normal => "normal",
+            Profile::Boost => "boost",
+            Profile::Quiet => "quiet",
+        }
+    }
+}
+
+/// タイマーの最大時間(分)
+const MAX_TIMER_MINUTES: u16 = 24 * 60;
+
+/// パナソニックのエアコンの状態
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PanasonicHvac {
+    temperature: u8,
+    mode: HvacMode,
+    switch: bool,
+    swing: Swing,
+    fan: FanSpeed,
+    profile: Profile,
+    on_timer: Option<u16>,
+    off_timer: Option<u16>,
+}
+
+impl PanasonicHvac {
+    pub fn temperature(&self) -> u8 {
+        self.temperature
+    }
+
+    pub fn mode(&self) -> HvacMode {
+        self.mode
+    }
+
+    pub fn switch(&self) -> bool {
+        self.switch
+    }
+
+    pub fn swing(&self) -> Swing {
+        self.swing
+    }
+
+    pub fn fan(&self) -> FanSpeed {
+        self.fan
+    }
+
+    pub fn profile(&self) -> Profile {
+        self.profile
+    }
+
+    /// オンタイマー(分)
+    pub fn on_timer(&self) -> Option<u16> {
+        self.on_timer
+    }
+
+    /// オフタイマー(分)
+    pub fn off_timer(&self) -> Option<u16> {
+        self.off_timer
+    }
+}
+
+impl PanasonicHvac {
+    /// 符号化する
+    pub fn encode(&self) -> Result<Vec<MarkAndSpaceMicros>, EncoderError> {
+        encode_sub(
+            self.mode.as_str(),
+            if self.switch { "power_on" } else { "power_off" },
+            self.temperature,
+            self.fan.as_str(),
+            self.swing.as_str(),
+            self.profile.as_str(),
+            self.on_timer,
+            self.off_timer,
+        )
+    }
+}
+
+impl Serialize for PanasonicHvac {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("PanasonicHvac", 8)?;
+        state.serialize_field("temperature", &self.temperature())?;
+        state.serialize_field("mode", self.mode().as_str())?;
+        state.serialize_field("switch", &self.switch())?;
+        state.serialize_field("swing", self.swing().as_str())?;
+        state.serialize_field("fan", self.fan().as_str())?;
+        state.serialize_field("profile", self.profile().as_str())?;
+        state.serialize_field("on_timer", &self.on_timer())?;
+        state.serialize_field("off_timer", &self.off_timer())?;
+        state.end()
+    }
+}
+
+impl From<PanasonicHvac> for InfraredRemoteControlCode {
+    fn from(hvac: PanasonicHvac) -> Self {
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("hvac_mode".to_owned(), hvac.mode.as_str().to_owned());
+        decorded.insert(
+            "power_switch".to_owned(),
+            if hvac.switch { "power_on" } else { "power_off" }.to_owned(),
+        );
+        decorded.insert("temperature".to_owned(), hvac.temperature.to_string());
+        decorded.insert("fan_speed".to_owned(), hvac.fan.as_str().to_owned());
+        decorded.insert("swing".to_owned(), hvac.swing.as_str().to_owned());
+        decorded.insert("profile".to_owned(), hvac.profile.as_str().to_owned());
+        let enabled = |b: bool| if b { "enabled" } else { "disabled" };
+        decorded.insert(
+            "timer_on_enabled".to_owned(),
+            enabled(hvac.on_timer.is_some()).to_owned(),
+        );
+        if let Some(minutes) = hvac.on_timer {
+            decorded.insert("timer_on_duration_minutes".to_owned(), minutes.to_string());
+        }
+        decorded.insert(
+            "timer_off_enabled".to_owned(),
+            enabled(hvac.off_timer.is_some()).to_owned(),
+        );
+        if let Some(minutes) = hvac.off_timer {
+            decorded.insert("timer_off_duration_minutes".to_owned(), minutes.to_string());
+        }
+        decorded.insert("manufacturer".to_owned(), "panasonic".to_owned());
+        InfraredRemoteControlCode(decorded)
+    }
+}
+
+impl TryFrom<InfraredRemoteControlCode> for PanasonicHvac {
+    type Error = ValidationError;
+
+    fn try_from(code: InfraredRemoteControlCode) -> Result<Self, Self::Error> {
+        // 項目の値を取り出して変換する
+        fn field<T>(
+            code: &InfraredRemoteControlCode,
+            name: &str,
+            parse: impl Fn(&str) -> Option<T>,
+        ) -> Result<T, ValidationError> {
+            let value = code
+                .0
+                .get(name)
+                .ok_or(ValidationError::MissingField(name.to_owned()))?;
+            parse(value).ok_or(ValidationError::UnknownValue(
+                name.to_owned(),
+                value.to_owned(),
+            ))
+        }
+        // タイマーは省略できる
+        let timer = |enabled: &str, duration: &str| -> Result<Option<u16>, ValidationError> {
+            match code.0.get(enabled).map(|s| s.as_str()) {
+                Some("enabled") => field(&code, duration, |s| s.parse().ok()).map(Some),
+                Some("disabled") | None => Ok(None),
+                Some(value) => Err(ValidationError::UnknownValue(
+                    enabled.to_owned(),
+                    value.to_owned(),
+                )),
+            }
+        };
+        // 表にないプロファイルは復号時に省かれるので, 無ければ通常とする
+        let profile = match code.0.get("profile") {
+            Some(_) => field(&code, "profile", Profile::from_name)?,
+            None => Profile::Normal,
+        };
+        let mut builder = PanasonicHvacBuilder::new();
+        if let Some(minutes) = timer("timer_on_enabled", "timer_on_duration_minutes")? {
+            builder = builder.on_timer(minutes);
+        }
+        if let Some(minutes) = timer("timer_off_enabled", "timer_off_duration_minutes")? {
+            builder = builder.off_timer(minutes);
+        }
+        builder
+            .mode(field(&code, "hvac_mode", HvacMode::from_name)?)
+            .switch(field(&code, "power_switch", |s| match s {
+                "power_on" => Some(true),
+                "power_off" => Some(false),
+                _ => None,
+            })?)
+            .temperature(field(&code, "temperature", |s| s.parse().ok())?)
+            .fan(field(&code, "fan_speed", FanSpeed::from_name)?)
+            .swing(field(&code, "swing", Swing::from_name)?)
+            .profile(profile)
+            .build()
+    }
+}
+
+/// パナソニックのエアコンの状態を組み立てる
+/// 設定しなかった項目は 自動運転, 26度, 電源オフ, 風量自動, 風向自動, 通常運転, タイマー無し になる
+#[derive(Clone, Debug, Default)]
+pub struct PanasonicHvacBuilder {
+    temperature: Option<u8>,
+    mode: Option<HvacMode>,
+    switch: Option<bool>,
+    swing: Option<Swing>,
+    fan: Option<FanSpeed>,
+    profile: Option<Profile>,
+    on_timer: Option<u16>,
+    off_timer: Option<u16>,
+}
+
+impl PanasonicHvacBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn temperature(mut self, temperature: u8) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    pub fn mode(mut self, mode: HvacMode) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    pub fn switch(mut self, switch: bool) -> Self {
+        self.switch = Some(switch);
+        self
+    }
+
+    pub fn swing(mut self, swing: Swing) -> Self {
+        self.swing = Some(swing);
+        self
+    }
+
+    pub fn fan(mut self, fan: FanSpeed) -> Self {
+        self.fan = Some(fan);
+        self
+    }
+
+    pub fn profile(mut self, profile: Profile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    /// オンタイマー(分)
+    pub fn on_timer(mut self, minutes: u16) -> Self {
+        self.on_timer = Some(minutes);
+        self
+    }
+
+    /// オフタイマー(分)
+    pub fn off_timer(mut self, minutes: u16) -> Self {
+        self.off_timer = Some(minutes);
+        self
+    }
 
+    pub fn build(self) -> Result<PanasonicHvac, ValidationError> {
+        let hvac = PanasonicHvac {
+            temperature: self.temperature.unwrap_or(26),
+            mode: self.mode.unwrap_or(HvacMode::Auto),
+            switch: self.switch.unwrap_or(false),
+            swing: self.swing.unwrap_or(Swing::Auto),
+            fan: self.fan.unwrap_or(FanSpeed::Auto),
+            profile: self.profile.unwrap_or(Profile::Normal),
+            on_timer: self.on_timer,
+            off_timer: self.off_timer,
+        };
+        if !(16..=30).contains(&hvac.temperature) {
+            return Err(ValidationError::TemperatureOutOfRange(
+                16,
+                30,
+                hvac.temperature,
+            ));
+        }
+        // タイマーは24時間まで
+        for (name, minutes) in [("on_timer", hvac.on_timer), ("off_timer", hvac.off_timer)] {
+            if let Some(minutes) = minutes.filter(|&m| m > MAX_TIMER_MINUTES) {
+                return Err(ValidationError::InconsistentSettings(format!(
+                    "{} must be at most {} minutes (actual {})",
+                    name, MAX_TIMER_MINUTES, minutes
+                )));
+            }
+        }
+        // 除湿運転は風量自動のみ
+        if hvac.mode == HvacMode::Dry && hvac.fan != FanSpeed::Auto {
+            return Err(ValidationError::InconsistentSettings(
+                "fan speed must be auto in dry mode".to_owned(),
+            ));
+        }
+        Ok(hvac)
+    }
+}
+
+#[cfg(test)]
+mod encode_panasonic_tests {
+    use crate::infrared_remote::*;
+
+    #[test]
+    fn test1() {
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("hvac_mode".to_owned(), "hvac_mode_cool".to_owned());
+        decorded.insert("power_switch".to_owned(), "power_on".to_owned());
+        decorded.insert("temperature".to_owned(), "26".to_owned());
+        decorded.insert("fan_speed".to_owned(), "auto".to_owned());
+        decorded.insert("swing".to_owned(), "auto".to_owned());
+        decorded.insert("profile".to_owned(), "normal".to_owned());
+        decorded.insert("timer_on_enabled".to_owned(), "disabled".to_owned());
+        decorded.insert("timer_off_enabled".to_owned(), "disabled".to_owned());
+        decorded.insert("checksum".to_owned(), "59".to_owned());
+        decorded.insert("manufacturer".to_owned(), "panasonic".to_owned());
         //
-        // Checksum
+        let markandspaces =
+            panasonic_hvac::encode("hvac_mode_cool", "power_on", 26, "auto", "auto", "normal")
+                .unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let result = panasonic_hvac::decode(&frames);
+        let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn test2() {
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("hvac_mode".to_owned(), "hvac_mode_dry".to_owned());
+        decorded.insert("power_switch".to_owned(), "power_on".to_owned());
+        decorded.insert("temperature".to_owned(), "16".to_owned());
+        decorded.insert("fan_speed".to_owned(), "auto".to_owned());
+        decorded.insert("swing".to_owned(), "auto".to_owned());
+        decorded.insert("profile".to_owned(), "normal".to_owned());
+        decorded.insert("timer_on_enabled".to_owned(), "disabled".to_owned());
+        decorded.insert("timer_off_enabled".to_owned(), "disabled".to_owned());
+        decorded.insert("checksum".to_owned(), "23".to_owned());
+        decorded.insert("manufacturer".to_owned(), "panasonic".to_owned());
         //
-        decorded.insert("checksum".to_owned(), u8::from(octets[0x12]).to_string());
+        let markandspaces =
+            panasonic_hvac::encode("hvac_mode_dry", "power_on", 16, "auto", "auto", "normal")
+                .unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let result = panasonic_hvac::decode(&frames);
+        let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn test3() {
+        let result =
+            panasonic_hvac::encode("hvac_mode_cool", "power_on", 31, "auto", "auto", "normal");
+        assert_eq!(result, Err(EncoderError::TemperatureOutOfRange(16, 30, 31)));
+        let result =
+            panasonic_hvac::encode("hvac_mode_cool", "power_on", 15, "auto", "auto", "normal");
+        assert_eq!(result, Err(EncoderError::TemperatureOutOfRange(16, 30, 15)));
+    }
+
+    #[test]
+    fn test4() {
+        let result =
+            panasonic_hvac::encode("hvac_mode_cool", "power_on", 26, "turbo", "auto", "normal");
+        assert_eq!(
+            result,
+            Err(EncoderError::UnknownValue(
+                "fan_speed".to_owned(),
+                "turbo".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn test5() {
+        let settings: panasonic_hvac::Settings = serde_json::from_str(
+            r#"{"mode":"hvac_mode_cool","power":"power_on","temperature":26,"fan_speed":"auto","swing":"auto","profile":"normal"}"#,
+        )
+        .unwrap();
+        let result = panasonic_hvac::encode_settings(&settings);
+        let expected =
+            panasonic_hvac::encode("hvac_mode_cool", "power_on", 26, "auto", "auto", "normal");
+        assert_eq!(result, expected);
         //
-        decorded.insert("manufacturer".to_owned(), "panasonic".to_owned());
-        Some(InfraredRemoteControlCode(decorded))
-    } else {
-        None
+        let settings = panasonic_hvac::Settings {
+            swing: "sideways".to_owned(),
+            ..settings
+        };
+        let result = panasonic_hvac::encode_settings(&settings);
+        assert_eq!(
+            result,
+            Err(EncoderError::UnknownValue(
+                "swing".to_owned(),
+                "sideways".to_owned()
+            ))
+        );
     }
 }
 
@@ -272,11 +943,13 @@ mod decode_panasonic_tests {
         decorded.insert("temperature".to_owned(), "26".to_owned());
         decorded.insert("fan_speed".to_owned(), "auto".to_owned());
         decorded.insert("swing".to_owned(), "auto".to_owned());
+        decorded.insert("timer_on_enabled".to_owned(), "disabled".to_owned());
+        decorded.insert("timer_off_enabled".to_owned(), "disabled".to_owned());
         decorded.insert("checksum".to_owned(), "107".to_owned());
         decorded.insert("manufacturer".to_owned(), "panasonic".to_owned());
         //
         let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
-        let frames = decord_receiving_data(&markandspaces).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
         let result = panasonic_hvac::decode(&frames);
         let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
         assert_eq!(result, expected)
@@ -291,13 +964,269 @@ mod decode_panasonic_tests {
         decorded.insert("temperature".to_owned(), "16".to_owned());
         decorded.insert("fan_speed".to_owned(), "auto".to_owned());
         decorded.insert("swing".to_owned(), "auto".to_owned());
+        decorded.insert("timer_on_enabled".to_owned(), "disabled".to_owned());
+        decorded.insert("timer_off_enabled".to_owned(), "disabled".to_owned());
         decorded.insert("checksum".to_owned(), "231".to_owned());
         decorded.insert("manufacturer".to_owned(), "panasonic".to_owned());
         //
         let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
-        let frames = decord_receiving_data(&markandspaces).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
         let result = panasonic_hvac::decode(&frames);
         let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
         assert_eq!(result, expected)
     }
+
+    #[test]
+    fn test3() {
+        let markandspaces =
+            panasonic_hvac::encode("hvac_mode_cool", "power_on", 26, "auto", "auto", "normal")
+                .unwrap();
+        let mut frames = decord_receiving_data(&markandspaces, None).unwrap();
+        // 第2フレームの温度(6バイト目)を壊す
+        if let Some(DecordedInfraredRemoteFrame::Aeha(bits)) = frames.get_mut(1) {
+            bits[6 * 8 + 1] = Bit::Hi;
+        }
+        let result = panasonic_hvac::decode(&frames);
+        assert_eq!(result, vec![]);
+    }
+
+    // 第2フレームのoffset番目のバイトを書き換えてチェックサムを合わせる
+    fn mutate_second_frame(offset: usize, value: u8) -> Vec<DecordedInfraredRemoteFrame> {
+        let markandspaces =
+            panasonic_hvac::encode("hvac_mode_cool", "power_on", 26, "auto", "auto", "normal")
+                .unwrap();
+        let mut frames = decord_receiving_data(&markandspaces, None).unwrap();
+        if let Some(DecordedInfraredRemoteFrame::Aeha(bits)) = frames.get_mut(1) {
+            *bits = test_frames::rewrite_with_sum_checksum(bits, |octets| octets[offset] = value);
+        }
+        frames
+    }
+
+    #[test]
+    fn test4() {
+        // 温度 31度
+        let frames = mutate_second_frame(0x6, 0xf << 1);
+        assert_eq!(
+            panasonic_hvac::decode_sub(&frames),
+            Err(HvacDecodeError::TemperatureOutOfRange(16, 30, 31))
+        );
+        assert_eq!(panasonic_hvac::decode(&frames), vec![]);
+        // モード
+        let frames = mutate_second_frame(0x5, 0x19);
+        assert_eq!(
+            panasonic_hvac::decode_sub(&frames),
+            Err(HvacDecodeError::UnknownMode(0x1))
+        );
+        // 風量
+        let frames = mutate_second_frame(0x8, 0x1f);
+        assert_eq!(
+            panasonic_hvac::decode_sub(&frames),
+            Err(HvacDecodeError::UnknownFanSpeed(0x1))
+        );
+        // 風向
+        let frames = mutate_second_frame(0x8, 0xa0);
+        assert_eq!(
+            panasonic_hvac::decode_sub(&frames),
+            Err(HvacDecodeError::UnknownSwing(0x0))
+        );
+    }
+
+    #[test]
+    fn test5() {
+        let markandspaces =
+            panasonic_hvac::encode("hvac_mode_cool", "power_on", 26, "auto", "auto", "normal")
+                .unwrap();
+        let mut frames = decord_receiving_data(&markandspaces, None).unwrap();
+        assert!(matches!(panasonic_hvac::decode_sub(&frames), Ok(Some(_))));
+        // チェックサムを壊す
+        let actual = if let Some(DecordedInfraredRemoteFrame::Aeha(bits)) = frames.get_mut(1) {
+            let checksum = u8::from(LsbFirst::try_from(&bits[0x12 * 8..0x13 * 8]).unwrap());
+            let corrupted = unfolding_from_lsb_first(LsbFirst::from(checksum ^ 0xff));
+            bits[0x12 * 8..0x13 * 8].copy_from_slice(&corrupted);
+            checksum ^ 0xff
+        } else {
+            unreachable!()
+        };
+        assert_eq!(
+            panasonic_hvac::decode_sub(&frames),
+            Err(HvacDecodeError::ChecksumMismatch {
+                expected: actual ^ 0xff,
+                actual
+            })
+        );
+        // パナソニックの信号ではない
+        let frames = decord_receiving_data(&protocol_nec::encode_nec([0, 0, 0, 0]), None).unwrap();
+        assert_eq!(panasonic_hvac::decode_sub(&frames), Ok(None));
+    }
+
+    #[test]
+    fn test6() {
+        // タイマーを設定した実機の受信データは無いので, タイマー無しの受信データで
+        // 0x0a-0x0cバイト目がタイマー無し(0x600分)として読めることを確かめる
+        let rxdata = "8800410014001000130032001300100013001000130010001300100014001000130010001300100013001000130010001400100013001000130032001300100013001000130010001400100013001000130010001300100014003100130032001300320013001000130010001300320013001000130010001400100013001000130010001300100014000F00140010001300100013001000130010001300100014001000130010001300100013001000140010001300100013001000130010001300100014001000130010001300100013001000130010001400100013001000130010001400100013003100140031001300100013001000140010001300100013001000130082018800410013001000140031001300100014001000130010001300100014000F001400100013001000130010001300100014000F00140010001300320013001000130010001300100014000F001400100013001000130010001300320013003200130032001300100013001000130032001300100013001000140010001300100013001000130010001300100014001000130010001300100013001000140010001300100013003200130010001300100014003100130032001300310014001000130010001300100013001000140031001300100014003100130032001300100013000F0013001000140010001300100013001000130010001300100014001000130031001400310013003200130032001300320013001000130032001300100013003200130031001400100013003100140031001300100013001000140010001300100013001000130010001400100013001000130010001300100013001000140010001300100013003200130032001300310014000F0014001000130010001300100013001000130010001400100013001000130010001300320013003200130031001400100013001000130010001300100013001000140010001300320013001000130010001300100014000F001400100013001000130010001300100014001000130010001300320013003200130010001300100013001000130011001300310013001000140010001300100013001000130010001300100014001000130010001300100013001000130011001300310013001000140010001300100013001000130032001300320013001000130032001300100013003200130032001300100013004F03";
+        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let octets = match &frames[1] {
+            DecordedInfraredRemoteFrame::Aeha(bits) => test_frames::octets_from_bits(bits),
+            _ => unreachable!(),
+        };
+        assert_eq!(octets[0xa..0xd], [0x00, 0x0e, 0xe0]);
+        // エンコーダーもタイマー無しで同じバイト列にする
+        let markandspaces =
+            panasonic_hvac::encode("hvac_mode_cool", "power_on", 26, "auto", "auto", "normal")
+                .unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let encoded = match &frames[1] {
+            DecordedInfraredRemoteFrame::Aeha(bits) => test_frames::octets_from_bits(bits),
+            _ => unreachable!(),
+        };
+        assert_eq!(encoded[0xa..0xd], octets[0xa..0xd]);
+    }
+}
+
+#[cfg(test)]
+mod builder_panasonic_tests {
+    use crate::infrared_remote::*;
+    use panasonic_hvac::{FanSpeed, HvacMode, PanasonicHvacBuilder, Profile, Swing};
+
+    #[test]
+    fn test1() {
+        let hvac = PanasonicHvacBuilder::new().build().unwrap();
+        assert_eq!(hvac.mode(), HvacMode::Auto);
+        assert_eq!(hvac.temperature(), 26);
+        assert!(!hvac.switch());
+        assert_eq!(hvac.fan(), FanSpeed::Auto);
+        assert_eq!(hvac.swing(), Swing::Auto);
+        assert_eq!(hvac.profile(), Profile::Normal);
+        assert_eq!(
+            serde_json::to_string(&hvac).unwrap(),
+            r#"{"temperature":26,"mode":"hvac_mode_auto","switch":false,"swing":"auto","fan":"auto","profile":"normal","on_timer":null,"off_timer":null}"#
+        );
+    }
+
+    #[test]
+    fn test2() {
+        let hvac = PanasonicHvacBuilder::new()
+            .mode(HvacMode::Cool)
+            .switch(true)
+            .temperature(26)
+            .build()
+            .unwrap();
+        assert_eq!(
+            hvac.encode(),
+            panasonic_hvac::encode("hvac_mode_cool", "power_on", 26, "auto", "auto", "normal")
+        );
+    }
+
+    #[test]
+    fn test3() {
+        let result = PanasonicHvacBuilder::new().temperature(31).build();
+        assert_eq!(
+            result,
+            Err(ValidationError::TemperatureOutOfRange(16, 30, 31))
+        );
+        let result = PanasonicHvacBuilder::new().temperature(15).build();
+        assert_eq!(
+            result,
+            Err(ValidationError::TemperatureOutOfRange(16, 30, 15))
+        );
+        let result = PanasonicHvacBuilder::new()
+            .mode(HvacMode::Dry)
+            .fan(FanSpeed::Notch3)
+            .build();
+        assert!(matches!(
+            result,
+            Err(ValidationError::InconsistentSettings(_))
+        ));
+    }
+
+    #[test]
+    fn test4() {
+        let hvac = PanasonicHvacBuilder::new()
+            .mode(HvacMode::Heat)
+            .switch(true)
+            .temperature(22)
+            .fan(FanSpeed::Notch4)
+            .swing(Swing::Horizontal)
+            .profile(Profile::Quiet)
+            .build()
+            .unwrap();
+        let code = InfraredRemoteControlCode::from(hvac.clone());
+        assert_eq!(code.hvac_mode(), Some("hvac_mode_heat"));
+        assert_eq!(code.temperature(), Some(22));
+        assert_eq!(panasonic_hvac::PanasonicHvac::try_from(code), Ok(hvac));
+    }
+
+    #[test]
+    fn test5() {
+        // 復号結果から変換する
+        let markandspaces =
+            panasonic_hvac::encode("hvac_mode_cool", "power_on", 26, "auto", "auto", "normal")
+                .unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let code = panasonic_hvac::decode(&frames).pop().unwrap();
+        let hvac = panasonic_hvac::PanasonicHvac::try_from(code).unwrap();
+        assert_eq!(hvac.mode(), HvacMode::Cool);
+        assert!(hvac.switch());
+        assert_eq!(hvac.temperature(), 26);
+        //
+        let mut code = InfraredRemoteControlCode::from(hvac);
+        code.0.remove("swing");
+        assert_eq!(
+            panasonic_hvac::PanasonicHvac::try_from(code.clone()),
+            Err(ValidationError::MissingField("swing".to_owned()))
+        );
+        code.0.insert("swing".to_owned(), "sideways".to_owned());
+        assert_eq!(
+            panasonic_hvac::PanasonicHvac::try_from(code),
+            Err(ValidationError::UnknownValue(
+                "swing".to_owned(),
+                "sideways".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn test6() {
+        // 60分後にオン
+        let hvac = PanasonicHvacBuilder::new()
+            .mode(HvacMode::Cool)
+            .switch(true)
+            .on_timer(60)
+            .build()
+            .unwrap();
+        let frames = decord_receiving_data(&hvac.encode().unwrap(), None).unwrap();
+        let code = panasonic_hvac::decode(&frames).pop().unwrap();
+        assert_eq!(
+            code.0.get("timer_on_enabled").map(|s| s.as_str()),
+            Some("enabled")
+        );
+        assert_eq!(
+            code.0.get("timer_on_duration_minutes").map(|s| s.as_str()),
+            Some("60")
+        );
+        assert_eq!(
+            code.0.get("timer_off_enabled").map(|s| s.as_str()),
+            Some("disabled")
+        );
+        assert_eq!(code.0.get("timer_off_duration_minutes"), None);
+        assert_eq!(code.power_switch(), Some(true));
+        let decoded = panasonic_hvac::PanasonicHvac::try_from(code).unwrap();
+        assert_eq!(decoded.on_timer(), Some(60));
+        assert_eq!(decoded.off_timer(), None);
+        // オフタイマー
+        let hvac = PanasonicHvacBuilder::new().off_timer(90).build().unwrap();
+        let frames = decord_receiving_data(&hvac.encode().unwrap(), None).unwrap();
+        let code = panasonic_hvac::decode(&frames).pop().unwrap();
+        assert_eq!(
+            code.0.get("timer_off_duration_minutes").map(|s| s.as_str()),
+            Some("90")
+        );
+        //
+        let result = PanasonicHvacBuilder::new().on_timer(24 * 60 + 1).build();
+        assert!(matches!(
+            result,
+            Err(ValidationError::InconsistentSettings(_))
+        ));
+    }
 }
@@ -0,0 +1,128 @@
+// Copyright (c) 2023 Akihiro Yamamoto.
+// Licensed under the MIT License <https://spdx.org/licenses/MIT.html>
+// See LICENSE file in the project root for full license information.
+//
+use crate::infrared_remote::*;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+//
+// Panasonic TV (KASEIKYO) OEM code 2bytes value is
+// LSB first                                        -- MSB first
+// 0x02 20                                          -- 0x40 04
+//
+const OEM_CODE: [LsbFirst; 2] = [LsbFirst::new(0x02), LsbFirst::new(0x20)];
+
+//
+static COMMAND: Lazy<HashMap<u8, &'static str>> = Lazy::new(|| {
+    let mut hm = HashMap::new();
+    hm.insert(0x3d, "Power");
+    hm.insert(0x04, "VolumeUp");
+    hm.insert(0x84, "VolumeDown");
+    hm.insert(0x34, "ChannelUp");
+    hm.insert(0xb4, "ChannelDown");
+    hm.insert(0x32, "Mute");
+    hm
+});
+
+/// デコード
+pub fn decode(frames: &[DecordedInfraredRemoteFrame]) -> Vec<InfraredRemoteControlCode> {
+    frames
+        .iter()
+        .flat_map(|f: &DecordedInfraredRemoteFrame| match f {
+            DecordedInfraredRemoteFrame::Kaseikyo(bits)
+            | DecordedInfraredRemoteFrame::Aeha(bits) => {
+                // data required 6bytes
+                if bits.len() != 48 {
+                    return None;
+                }
+                let octets = pack_to_octets(bits)
+                    .iter()
+                    .map(|&x| LsbFirst::from(x))
+                    .collect::<Vec<LsbFirst>>();
+                if octets[0..2] != OEM_CODE {
+                    return None;
+                }
+                let data = [
+                    u8::from(octets[2]),
+                    u8::from(octets[3]),
+                    u8::from(octets[4]),
+                ];
+                let command = data[2];
+                let mut decorded: HashMap<String, String> = HashMap::new();
+                // 6バイト目(パリティ)が一致しない場合は不明
+                let item = if u8::from(octets[5]) == protocol_kaseikyo::parity(data) {
+                    COMMAND.get(&command).copied().unwrap_or("Unknown")
+                } else {
+                    "Unknown"
+                };
+                decorded.insert("command".to_owned(), item.to_owned());
+                //
+                decorded.insert("manufacturer".to_owned(), "panasonic".to_owned());
+                Some(InfraredRemoteControlCode(decorded))
+            }
+            _ => None,
+        })
+        .collect::<Vec<InfraredRemoteControlCode>>()
+}
+
+#[cfg(test)]
+mod decode_tests {
+    use crate::infrared_remote::*;
+    use crate::parsing;
+
+    #[test]
+    fn test1() {
+        let markandspaces = protocol_kaseikyo::encode_frame([0x02, 0x20], [0x80, 0x00, 0x3d]);
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("command".to_owned(), "Power".to_owned());
+        decorded.insert("manufacturer".to_owned(), "panasonic".to_owned());
+        //
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let result = panasonic_tv::decode(&frames);
+        let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
+        assert_eq!(result, expected);
+        assert_eq!(decord_ir_frames(&frames), expected)
+    }
+
+    #[test]
+    fn test2() {
+        // パリティが一致しない
+        let markandspaces = protocol_aeha::encode_frame(&[0x02, 0x20, 0x80, 0x00, 0x04, 0x04]);
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("command".to_owned(), "Unknown".to_owned());
+        decorded.insert("manufacturer".to_owned(), "panasonic".to_owned());
+        //
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let result = panasonic_tv::decode(&frames);
+        let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn test3() {
+        // OEMコードが一致しない
+        let markandspaces = protocol_aeha::encode_frame(&[0x23, 0xcb, 0x80, 0x00, 0x04, 0xfb]);
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        assert_eq!(panasonic_tv::decode(&frames), vec![]);
+    }
+
+    #[test]
+    fn test4() {
+        // 実機の信号
+        let rxdata = "8700410014000F0014002F001400100013001000130010001300100013000F0014000F0014000F001300100014000E00140010001300100013002F001400100013001000130010001300100013000F0015000E0014000F0013001000130010001300300014000F0014000F0014000F0013001000130010001300100013000F0014000F0014002F00140010001300300014002F0015002F00150030001400100013000F0014002F00140010001300300014002F0015002F00140030001400100013002F0015004F03";
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("command".to_owned(), "Power".to_owned());
+        decorded.insert("manufacturer".to_owned(), "panasonic".to_owned());
+        //
+        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        assert!(matches!(
+            frames[..],
+            [DecordedInfraredRemoteFrame::Kaseikyo(_)]
+        ));
+        let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
+        assert_eq!(panasonic_tv::decode(&frames), expected);
+        assert_eq!(decord_ir_frames(&frames), expected)
+    }
+}
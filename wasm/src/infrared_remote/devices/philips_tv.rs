@@ -0,0 +1,104 @@
+// Copyright (c) 2023 Akihiro Yamamoto.
+// Licensed under the MIT License <https://spdx.org/licenses/MIT.html>
+// See LICENSE file in the project root for full license information.
+//
+use crate::infrared_remote::*;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+//
+static ADDRESS_HM: Lazy<HashMap<u8, &'static str>> = Lazy::new(|| {
+    let mut hm = HashMap::new();
+    hm.insert(0u8, "TV");
+    hm.insert(5u8, "VCR");
+    hm.insert(17u8, "Tuner");
+    hm.insert(20u8, "CDPlayer");
+    hm
+});
+
+//
+static COMMAND_HM: Lazy<HashMap<u8, &'static str>> = Lazy::new(|| {
+    let mut hm = HashMap::new();
+    hm.insert(0u8, "DigitKey0");
+    hm.insert(1u8, "DigitKey1");
+    hm.insert(2u8, "DigitKey2");
+    hm.insert(3u8, "DigitKey3");
+    hm.insert(4u8, "DigitKey4");
+    hm.insert(5u8, "DigitKey5");
+    hm.insert(6u8, "DigitKey6");
+    hm.insert(7u8, "DigitKey7");
+    hm.insert(8u8, "DigitKey8");
+    hm.insert(9u8, "DigitKey9");
+    hm.insert(12u8, "Standby");
+    hm.insert(13u8, "Mute");
+    hm.insert(16u8, "VolumePlus");
+    hm.insert(17u8, "VolumeMinus");
+    hm.insert(32u8, "ChannelPlus");
+    hm.insert(33u8, "ChannelMinus");
+    hm.insert(56u8, "AUXInputSelect");
+    hm
+});
+
+/// デコード
+pub fn decode(frames: &[DecordedInfraredRemoteFrame]) -> Vec<InfraredRemoteControlCode> {
+    frames
+        .iter()
+        .flat_map(|f: &DecordedInfraredRemoteFrame| match f {
+            DecordedInfraredRemoteFrame::Rc5(bits) => {
+                let mut decorded: HashMap<String, String> = HashMap::new();
+                //
+                let (address, command, toggle) = protocol_rc5::split_fields(bits)?;
+                ADDRESS_HM
+                    .get(&address)
+                    .map(|&item| decorded.insert("address".to_owned(), item.to_owned()));
+                COMMAND_HM
+                    .get(&command)
+                    .map(|&item| decorded.insert("command".to_owned(), item.to_owned()));
+                decorded.insert("toggle".to_owned(), toggle.to_string());
+                //
+                decorded.insert("manufacturer".to_owned(), "philips".to_owned());
+                Some(InfraredRemoteControlCode(decorded))
+            }
+            _ => None,
+        })
+        .collect::<Vec<InfraredRemoteControlCode>>()
+}
+
+#[cfg(test)]
+mod decode_tests {
+    use crate::infrared_remote::*;
+    use crate::parsing;
+
+    #[test]
+    fn test1() {
+        let rxdata = "[889,889,1778,889,889,889,889,889,889,889,889,889,889,889,889,889,889,1778,889,889,1778,889,889,89000]";
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("address".to_owned(), "TV".to_owned());
+        decorded.insert("command".to_owned(), "Standby".to_owned());
+        decorded.insert("toggle".to_owned(), "false".to_owned());
+        decorded.insert("manufacturer".to_owned(), "philips".to_owned());
+        //
+        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let result = philips_tv::decode(&frames);
+        let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
+        assert_eq!(result, expected);
+        assert_eq!(decord_ir_frames(&frames), expected)
+    }
+
+    #[test]
+    fn test2() {
+        let rxdata = "[905,870,912,861,1790,887,870,901,914,880,889,862,870,903,889,1761,1801,870,900,880,899,861,870,89000]";
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("address".to_owned(), "TV".to_owned());
+        decorded.insert("command".to_owned(), "VolumePlus".to_owned());
+        decorded.insert("toggle".to_owned(), "true".to_owned());
+        decorded.insert("manufacturer".to_owned(), "philips".to_owned());
+        //
+        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let result = philips_tv::decode(&frames);
+        let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
+        assert_eq!(result, expected)
+    }
+}
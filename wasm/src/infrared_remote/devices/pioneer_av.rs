@@ -0,0 +1,104 @@
+// Copyright (c) 2023 Akihiro Yamamoto.
+// Licensed under the MIT License <https://spdx.org/licenses/MIT.html>
+// See LICENSE file in the project root for full license information.
+//
+use crate::infrared_remote::*;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+//
+// Pioneer AV receiver (KASEIKYO) OEM code 2bytes value is
+// LSB first                                        -- MSB first
+// 0x02 20                                          -- 0x40 04
+// パナソニックと同じOEMコードなので, コマンド表にあるものだけをパイオニアとする
+//
+const OEM_CODE: [LsbFirst; 2] = [LsbFirst::new(0x02), LsbFirst::new(0x20)];
+
+//
+static COMMAND: Lazy<HashMap<u8, &'static str>> = Lazy::new(|| {
+    let mut hm = HashMap::new();
+    hm.insert(0x1c, "Power");
+    hm.insert(0x0a, "VolumeUp");
+    hm.insert(0x0b, "VolumeDown");
+    hm.insert(0x12, "Mute");
+    hm.insert(0x4b, "InputPhono");
+    hm.insert(0x4c, "InputCD");
+    hm.insert(0x4d, "InputTuner");
+    hm.insert(0x85, "InputDVD");
+    hm.insert(0x86, "InputTV");
+    hm
+});
+
+/// デコード
+pub fn decode(frames: &[DecordedInfraredRemoteFrame]) -> Vec<InfraredRemoteControlCode> {
+    frames
+        .iter()
+        .flat_map(|f: &DecordedInfraredRemoteFrame| match f {
+            DecordedInfraredRemoteFrame::Kaseikyo(bits) => {
+                // data required 6bytes
+                if bits.len() != protocol_kaseikyo::FRAME_BITS {
+                    return None;
+                }
+                let octets = pack_to_octets(bits)
+                    .iter()
+                    .map(|&x| LsbFirst::from(x))
+                    .collect::<Vec<LsbFirst>>();
+                if octets[0..2] != OEM_CODE {
+                    return None;
+                }
+                // パリティは復調時に確かめてある
+                let command = u8::from(octets[4]);
+                let item = COMMAND.get(&command).copied()?;
+                let mut decorded: HashMap<String, String> = HashMap::new();
+                decorded.insert("command".to_owned(), item.to_owned());
+                //
+                decorded.insert("manufacturer".to_owned(), "pioneer".to_owned());
+                Some(InfraredRemoteControlCode(decorded))
+            }
+            _ => None,
+        })
+        .collect::<Vec<InfraredRemoteControlCode>>()
+}
+
+#[cfg(test)]
+mod decode_tests {
+    use crate::infrared_remote::*;
+
+    #[test]
+    fn test1() {
+        let markandspaces = protocol_kaseikyo::encode_frame([0x02, 0x20], [0xa0, 0x00, 0x1c]);
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("command".to_owned(), "Power".to_owned());
+        decorded.insert("manufacturer".to_owned(), "pioneer".to_owned());
+        //
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let result = pioneer_av::decode(&frames);
+        let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn test2() {
+        // コマンド表にない
+        let markandspaces = protocol_kaseikyo::encode_frame([0x02, 0x20], [0xa0, 0x00, 0x7f]);
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        assert_eq!(pioneer_av::decode(&frames), vec![]);
+        // パナソニックのテレビの実機の受信データ(電源)
+        let rxdata = "8700410014000F0014002F001400100013001000130010001300100013000F0014000F0014000F001300100014000E00140010001300100013002F001400100013001000130010001300100013000F0015000E0014000F0013001000130010001300300014000F0014000F0014000F0013001000130010001300100013000F0014000F0014002F00140010001300300014002F0015002F00150030001400100013000F0014002F00140010001300300014002F0015002F00140030001400100013002F0015004F03";
+        let markandspaces = crate::parsing::parse_infrared_code_text(rxdata).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        assert_eq!(pioneer_av::decode(&frames), vec![]);
+    }
+
+    #[test]
+    fn test3() {
+        // パリティが一致しない(KASEIKYOとして復調されない)
+        let markandspaces = protocol_aeha::encode_frame(&[0x02, 0x20, 0xa0, 0x00, 0x4c, 0x4c]);
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        assert_eq!(pioneer_av::decode(&frames), vec![]);
+        // OEMコードが一致しない
+        let markandspaces = protocol_kaseikyo::encode_frame([0x23, 0xcb], [0xa0, 0x00, 0x4c]);
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        assert_eq!(pioneer_av::decode(&frames), vec![]);
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright (c) 2023 Akihiro Yamamoto.
+// Licensed under the MIT License <https://spdx.org/licenses/MIT.html>
+// See LICENSE file in the project root for full license information.
+//
+use crate::infrared_remote::*;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+//
+// Samsung TV address 2bytes value is
+// LSB first                                        -- MSB first
+// 0x07 07                                          -- 0xe0 e0
+//
+static ADDRESS: Lazy<HashMap<[MsbFirst; 2], &'static str>> = Lazy::new(|| {
+    let mut hm = HashMap::new();
+    hm.insert([MsbFirst::new(0xe0), MsbFirst::new(0xe0)], "tv");
+    hm
+});
+
+//
+static COMMAND: Lazy<HashMap<[MsbFirst; 2], &'static str>> = Lazy::new(|| {
+    let mut hm = HashMap::new();
+    hm.insert([MsbFirst::new(0x40), MsbFirst::new(0xbf)], "Power");
+    hm.insert([MsbFirst::new(0xe0), MsbFirst::new(0x1f)], "VolumeUp");
+    hm.insert([MsbFirst::new(0xd0), MsbFirst::new(0x2f)], "VolumeDown");
+    hm.insert([MsbFirst::new(0x48), MsbFirst::new(0xb7)], "ChannelUp");
+    hm.insert([MsbFirst::new(0x08), MsbFirst::new(0xf7)], "ChannelDown");
+    hm.insert([MsbFirst::new(0xf0), MsbFirst::new(0x0f)], "Mute");
+    hm
+});
+
+/// デコード
+pub fn decode(frames: &[DecordedInfraredRemoteFrame]) -> Vec<InfraredRemoteControlCode> {
+    frames
+        .iter()
+        .flat_map(|f: &DecordedInfraredRemoteFrame| match f {
+            DecordedInfraredRemoteFrame::Samsung(bits) => {
+                let mut decorded: HashMap<String, String> = HashMap::new();
+                // data required 32bits
+                let address = [
+                    bits.get(0..8).and_then(|x| MsbFirst::try_from(x).ok())?,
+                    bits.get(8..16).and_then(|x| MsbFirst::try_from(x).ok())?,
+                ];
+                let command = [
+                    bits.get(16..24).and_then(|x| MsbFirst::try_from(x).ok())?,
+                    bits.get(24..32).and_then(|x| MsbFirst::try_from(x).ok())?,
+                ];
+                // Samsung以外のアドレスは対象外
+                let item = ADDRESS.get(&address).copied()?;
+                decorded.insert("address".to_owned(), item.to_owned());
+                COMMAND
+                    .get(&command)
+                    .map(|&item| decorded.insert("command".to_owned(), item.to_owned()));
+                //
+                decorded.insert("manufacturer".to_owned(), "samsung".to_owned());
+                Some(InfraredRemoteControlCode(decorded))
+            }
+            _ => None,
+        })
+        .collect::<Vec<InfraredRemoteControlCode>>()
+}
+
+#[cfg(test)]
+mod decode_tests {
+    use crate::infrared_remote::*;
+
+    #[test]
+    fn test1() {
+        let markandspaces = protocol_samsung::encode_samsung([0x07, 0x07, 0x02, 0xfd]);
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("address".to_owned(), "tv".to_owned());
+        decorded.insert("command".to_owned(), "Power".to_owned());
+        decorded.insert("manufacturer".to_owned(), "samsung".to_owned());
+        //
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let result = samsung_tv::decode(&frames);
+        let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
+        assert_eq!(result, expected);
+        assert_eq!(decord_ir_frames(&frames), expected)
+    }
+
+    #[test]
+    fn test2() {
+        let markandspaces = protocol_samsung::encode_samsung([0x07, 0x07, 0x0f, 0xf0]);
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("address".to_owned(), "tv".to_owned());
+        decorded.insert("command".to_owned(), "Mute".to_owned());
+        decorded.insert("manufacturer".to_owned(), "samsung".to_owned());
+        //
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let result = samsung_tv::decode(&frames);
+        let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn test3() {
+        // 同じビット列でもNECの信号は一致しない
+        let markandspaces = protocol_nec::encode_nec([0x07, 0x07, 0x02, 0xfd]);
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        assert_eq!(samsung_tv::decode(&frames), vec![]);
+    }
+}
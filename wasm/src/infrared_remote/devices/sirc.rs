@@ -7,7 +7,7 @@ use once_cell::sync::Lazy;
 use std::collections::HashMap;
 
 //
-const ADDRESS_HM: Lazy<HashMap<u8, &'static str>> = Lazy::new(|| {
+static ADDRESS_HM: Lazy<HashMap<u8, &'static str>> = Lazy::new(|| {
     let mut hm = HashMap::new();
     hm.insert(1u8, "TV");
     hm.insert(2u8, "VideoCasetteRecorder1");
@@ -20,8 +20,17 @@ const ADDRESS_HM: Lazy<HashMap<u8, &'static str>> = Lazy::new(|| {
     hm
 });
 
+// 15ビット, 20ビットSIRCのアドレス
+static ADDRESS_EXTENDED_HM: Lazy<HashMap<u8, &'static str>> = Lazy::new(|| {
+    let mut hm = HashMap::new();
+    hm.insert(17u8, "DVDPlayer");
+    hm.insert(0x13u8, "PlayStation");
+    hm.insert(26u8, "BluRayPlayer");
+    hm
+});
+
 //
-const COMMAND_HM: Lazy<HashMap<u8, &'static str>> = Lazy::new(|| {
+static COMMAND_HM: Lazy<HashMap<u8, &'static str>> = Lazy::new(|| {
     let mut hm = HashMap::new();
     hm.insert(0u8, "DigitKey1");
     hm.insert(1u8, "DigitKey2");
@@ -33,6 +42,7 @@ const COMMAND_HM: Lazy<HashMap<u8, &'static str>> = Lazy::new(|| {
     hm.insert(7u8, "DigitKey8");
     hm.insert(8u8, "DigitKey9");
     hm.insert(9u8, "DigitKey0");
+    hm.insert(11u8, "Enter");
     hm.insert(16u8, "ChannelPlus");
     hm.insert(17u8, "ChannelMinus");
     hm.insert(18u8, "VolumePlus");
@@ -51,6 +61,13 @@ const COMMAND_HM: Lazy<HashMap<u8, &'static str>> = Lazy::new(|| {
     hm.insert(38u8, "BalanceLeft");
     hm.insert(39u8, "BalanceRight");
     hm.insert(47u8, "Standby");
+    hm.insert(50u8, "Play");
+    hm.insert(51u8, "Rewind");
+    hm.insert(52u8, "FastForward");
+    hm.insert(56u8, "Stop");
+    hm.insert(57u8, "Pause");
+    hm.insert(96u8, "Menu");
+    hm.insert(104u8, "Eject");
     hm
 });
 
@@ -60,6 +77,11 @@ pub fn decode(frames: &[DecordedInfraredRemoteFrame]) -> Vec<InfraredRemoteContr
         .iter()
         .flat_map(|f: &DecordedInfraredRemoteFrame| match f {
             DecordedInfraredRemoteFrame::Sirc(bits) => {
+                // ビット数でアドレスの長さと拡張の有無が決まる
+                let (address_range, extended_range) = match bits.len() {
+                    20 => (7..12, Some(12..20)),
+                    n => (7..n, None),
+                };
                 let mut decorded: HashMap<String, String> = HashMap::new();
                 //
                 let command = bits
@@ -69,10 +91,26 @@ pub fn decode(frames: &[DecordedInfraredRemoteFrame]) -> Vec<InfraredRemoteContr
                     .get(&command)
                     .map(|&item| decorded.insert("command".to_owned(), item.to_owned()));
                 //
-                let address = bits.get(7..).map(|xs| u8::from(folding_to_lsb_first(xs)))?;
-                ADDRESS_HM
-                    .get(&address)
-                    .map(|&item| decorded.insert("address".to_owned(), item.to_owned()));
+                let address = bits
+                    .get(address_range)
+                    .map(|xs| u8::from(folding_to_lsb_first(xs)))?;
+                // 15ビットは拡張アドレスの表になければ12ビットの表を使う
+                let item = match bits.len() {
+                    15 => ADDRESS_EXTENDED_HM
+                        .get(&address)
+                        .or(ADDRESS_HM.get(&address))
+                        .copied(),
+                    20 => ADDRESS_EXTENDED_HM.get(&address).copied(),
+                    _ => ADDRESS_HM.get(&address).copied(),
+                };
+                item.map(|item| decorded.insert("address".to_owned(), item.to_owned()));
+                //
+                if let Some(range) = extended_range {
+                    let extended = bits
+                        .get(range)
+                        .map(|xs| u8::from(folding_to_lsb_first(xs)))?;
+                    decorded.insert("extended".to_owned(), format!("{:#04x}", extended));
+                }
                 //
                 decorded.insert("manufacturer".to_owned(), "sony".to_owned());
                 Some(InfraredRemoteControlCode(decorded))
@@ -96,9 +134,93 @@ mod decode_tests {
         decorded.insert("manufacturer".to_owned(), "sony".to_owned());
         //
         let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
-        let frames = decord_receiving_data(&markandspaces).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let result = sirc::decode(&frames);
+        let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn test2() {
+        // 15ビットSIRC
+        let markandspaces = protocol_sirc::encode_sirc15(50, 26);
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("address".to_owned(), "BluRayPlayer".to_owned());
+        decorded.insert("command".to_owned(), "Play".to_owned());
+        decorded.insert("manufacturer".to_owned(), "sony".to_owned());
+        //
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let result = sirc::decode(&frames);
+        let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn test3() {
+        // 20ビットSIRC
+        let markandspaces = protocol_sirc::encode_sirc20(104, 17, 0x49);
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("address".to_owned(), "DVDPlayer".to_owned());
+        decorded.insert("command".to_owned(), "Eject".to_owned());
+        decorded.insert("extended".to_owned(), "0x49".to_owned());
+        decorded.insert("manufacturer".to_owned(), "sony".to_owned());
+        //
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
         let result = sirc::decode(&frames);
         let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
         assert_eq!(result, expected)
     }
+
+    #[test]
+    fn test4() {
+        // 12ビットSIRC(同じアドレス値でも12ビットの表を使う)
+        let markandspaces = protocol_sirc::encode_sirc12(57, 17);
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("address".to_owned(), "CDPlayer".to_owned());
+        decorded.insert("command".to_owned(), "Pause".to_owned());
+        decorded.insert("manufacturer".to_owned(), "sony".to_owned());
+        //
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        assert_eq!(
+            sirc::decode(&frames),
+            vec![InfraredRemoteControlCode(decorded)]
+        );
+        // 12, 15, 20ビット以外は12ビットと同じ表を使う
+        let frames = vec![DecordedInfraredRemoteFrame::Sirc(vec![Bit::Lo; 13])];
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("command".to_owned(), "DigitKey1".to_owned());
+        decorded.insert("manufacturer".to_owned(), "sony".to_owned());
+        assert_eq!(
+            sirc::decode(&frames),
+            vec![InfraredRemoteControlCode(decorded)]
+        );
+    }
+
+    #[test]
+    fn test5() {
+        // 拡張アドレスの表にない15ビットSIRCは12ビットの表を使う
+        let markandspaces = protocol_sirc::encode_sirc15(21, 1);
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("address".to_owned(), "TV".to_owned());
+        decorded.insert("command".to_owned(), "Power".to_owned());
+        decorded.insert("manufacturer".to_owned(), "sony".to_owned());
+        //
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        assert_eq!(
+            sirc::decode(&frames),
+            vec![InfraredRemoteControlCode(decorded)]
+        );
+        //
+        let markandspaces = protocol_sirc::encode_sirc15(18, 12);
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("address".to_owned(), "SurroundSound".to_owned());
+        decorded.insert("command".to_owned(), "VolumePlus".to_owned());
+        decorded.insert("manufacturer".to_owned(), "sony".to_owned());
+        //
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        assert_eq!(
+            sirc::decode(&frames),
+            vec![InfraredRemoteControlCode(decorded)]
+        );
+    }
 }
@@ -0,0 +1,81 @@
+// Copyright (c) 2023 Akihiro Yamamoto.
+// Licensed under the MIT License <https://spdx.org/licenses/MIT.html>
+// See LICENSE file in the project root for full license information.
+//
+use crate::infrared_remote::*;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+// AVアンプ(SurroundSound)のアドレス
+const ADDRESS: u8 = 12;
+
+//
+static COMMAND: Lazy<HashMap<u8, &'static str>> = Lazy::new(|| {
+    let mut hm = HashMap::new();
+    hm.insert(21u8, "Power");
+    hm.insert(18u8, "VolumeUp");
+    hm.insert(19u8, "VolumeDown");
+    hm.insert(20u8, "Mute");
+    hm.insert(37u8, "InputCD");
+    hm.insert(15u8, "InputTuner");
+    hm.insert(61u8, "InputDVD");
+    hm.insert(39u8, "SurroundMode");
+    hm
+});
+
+/// デコード
+pub fn decode(frames: &[DecordedInfraredRemoteFrame]) -> Vec<InfraredRemoteControlCode> {
+    frames
+        .iter()
+        .flat_map(|f: &DecordedInfraredRemoteFrame| match f {
+            DecordedInfraredRemoteFrame::Sirc(bits) => {
+                // 12ビットSIRC(コマンド7ビット, アドレス5ビット)
+                if bits.len() != 12 {
+                    return None;
+                }
+                let address = LsbFirst::try_from(&bits[7..12]).map(u8::from).ok()?;
+                if address != ADDRESS {
+                    return None;
+                }
+                let command = LsbFirst::try_from(&bits[0..7]).map(u8::from).ok()?;
+                let mut decorded: HashMap<String, String> = HashMap::new();
+                let item = COMMAND.get(&command).copied().unwrap_or("Unknown");
+                decorded.insert("command".to_owned(), item.to_owned());
+                //
+                decorded.insert("manufacturer".to_owned(), "sony".to_owned());
+                Some(InfraredRemoteControlCode(decorded))
+            }
+            _ => None,
+        })
+        .collect::<Vec<InfraredRemoteControlCode>>()
+}
+
+#[cfg(test)]
+mod decode_tests {
+    use crate::infrared_remote::*;
+
+    #[test]
+    fn test1() {
+        let markandspaces = protocol_sirc::encode_sirc12(18, 12);
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("command".to_owned(), "VolumeUp".to_owned());
+        decorded.insert("manufacturer".to_owned(), "sony".to_owned());
+        //
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let result = sony_av::decode(&frames);
+        let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
+        assert_eq!(result, expected);
+        // TV向けの復号器ではなくAVアンプの復号器で復号される
+        assert_eq!(decord_ir_frames(&frames), expected);
+        assert_ne!(decord_ir_frames(&frames), sirc::decode(&frames));
+    }
+
+    #[test]
+    fn test2() {
+        // アドレスが一致しない(TV)
+        let markandspaces = protocol_sirc::encode_sirc12(21, 1);
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        assert_eq!(sony_av::decode(&frames), vec![]);
+        assert_eq!(decord_ir_frames(&frames), sirc::decode(&frames));
+    }
+}
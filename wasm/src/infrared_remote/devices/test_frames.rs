@@ -0,0 +1,57 @@
+// Copyright (c) 2023 Akihiro Yamamoto.
+// Licensed under the MIT License <https://spdx.org/licenses/MIT.html>
+// See LICENSE file in the project root for full license information.
+//
+// 試験用の信号を組み立てる
+//
+use crate::infrared_remote::*;
+use std::iter;
+
+/// ビット列をオクテット列(LSBファースト)にする
+pub fn octets_from_bits(bits: &[Bit]) -> Vec<u8> {
+    pack_to_octets(bits)
+        .iter()
+        .map(|&x| u8::from(LsbFirst::from(x)))
+        .collect()
+}
+
+/// オクテット列(LSBファースト)をビット列にする
+pub fn bits_from_octets(octets: &[u8]) -> Vec<Bit> {
+    octets
+        .iter()
+        .flat_map(|&x| unfolding_from_lsb_first(LsbFirst::new(x)))
+        .collect()
+}
+
+/// 末尾のオクテットを加算チェックサムにする
+pub fn with_sum_checksum(octets: &[u8]) -> Vec<u8> {
+    let (_, payload) = octets.split_last().unwrap();
+    let checksum = payload.iter().fold(0u8, |acc, &x| acc.wrapping_add(x));
+    payload
+        .iter()
+        .copied()
+        .chain(iter::once(checksum))
+        .collect()
+}
+
+/// オクテットを書き換えて, 末尾の加算チェックサムを付け直す
+pub fn rewrite_with_sum_checksum<F>(bits: &[Bit], f: F) -> Vec<Bit>
+where
+    F: FnOnce(&mut [u8]),
+{
+    let mut octets = octets_from_bits(bits);
+    f(&mut octets);
+    bits_from_octets(&with_sum_checksum(&octets))
+}
+
+/// NECフォーマットで変調する
+pub fn encode_nec_octets(octets: &[u8]) -> Vec<MarkAndSpaceMicros> {
+    iter::once(protocol_nec::LEADER)
+        .chain(
+            bits_from_octets(octets)
+                .into_iter()
+                .map(protocol_nec::modulate),
+        )
+        .chain(iter::once(protocol_nec::STOP_BIT))
+        .collect()
+}
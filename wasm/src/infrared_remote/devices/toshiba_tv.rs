@@ -7,14 +7,14 @@ use once_cell::sync::Lazy;
 use std::collections::HashMap;
 
 //
-const ADDRESS: Lazy<HashMap<[LsbFirst; 2], &'static str>> = Lazy::new(|| {
+static ADDRESS: Lazy<HashMap<[LsbFirst; 2], &'static str>> = Lazy::new(|| {
     let mut hm = HashMap::new();
     hm.insert([LsbFirst::new(0x40), LsbFirst::new(0xbf)], "tv");
     hm
 });
 
 //
-const COMMAND: Lazy<HashMap<[LsbFirst; 2], &'static str>> = Lazy::new(|| {
+static COMMAND: Lazy<HashMap<[LsbFirst; 2], &'static str>> = Lazy::new(|| {
     let mut hm = HashMap::new();
     hm.insert([LsbFirst::new(0x0f), LsbFirst::new(0xf0)], "InputSelect");
     hm.insert([LsbFirst::new(0x10), LsbFirst::new(0xef)], "Mute");
@@ -60,10 +60,9 @@ pub fn decode(frames: &[DecordedInfraredRemoteFrame]) -> Vec<InfraredRemoteContr
                     bits.get(16..24).map(|x| folding_to_lsb_first(x))?,
                     bits.get(24..32).map(|x| folding_to_lsb_first(x))?,
                 ];
-                //
-                ADDRESS
-                    .get(&address)
-                    .map(|&item| decorded.insert("address".to_owned(), item.to_owned()));
+                // 東芝以外のアドレスは対象外
+                let item = ADDRESS.get(&address).copied()?;
+                decorded.insert("address".to_owned(), item.to_owned());
                 COMMAND
                     .get(&command)
                     .map(|&item| decorded.insert("command".to_owned(), item.to_owned()));
@@ -76,6 +75,13 @@ pub fn decode(frames: &[DecordedInfraredRemoteFrame]) -> Vec<InfraredRemoteContr
         .collect::<Vec<InfraredRemoteControlCode>>()
 }
 
+/// アドレスとコマンドの名前からNECフレームの4バイトを得る
+pub fn encode_payload(address: &str, command: &str) -> Result<[u8; 4], EncoderError> {
+    let [a0, a1] = find_key_by_value(&ADDRESS, "address", address)?;
+    let [c0, c1] = find_key_by_value(&COMMAND, "command", command)?;
+    Ok([a0, a1, c0, c1].map(u8::from))
+}
+
 #[cfg(test)]
 mod decode_tests {
     use crate::infrared_remote::*;
@@ -90,7 +96,7 @@ mod decode_tests {
         decorded.insert("manufacturer".to_owned(), "toshiba".to_owned());
         //
         let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
-        let frames = decord_receiving_data(&markandspaces).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
         let result = toshiba_tv::decode(&frames);
         let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
         assert_eq!(result, expected)
@@ -105,9 +111,37 @@ mod decode_tests {
         decorded.insert("manufacturer".to_owned(), "toshiba".to_owned());
         //
         let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
-        let frames = decord_receiving_data(&markandspaces).unwrap();
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
         let result = toshiba_tv::decode(&frames);
         let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
         assert_eq!(result, expected)
     }
+
+    #[test]
+    fn test3() {
+        let rxdata= "5601A900180015001800140018001400190013001900140019001400170040001700150018003F0019003E0018003E0019003F0019003E00170040001800140019003E001800150018003F00180014001800140019003F0018001400170016001700150018003F001800140018003F0018003F001800140019003F0018003F0018003E0019004F03";
+        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        let frames = decord_receiving_data_strict(&markandspaces, None).unwrap();
+        assert_eq!(frames, decord_receiving_data(&markandspaces, None).unwrap());
+    }
+
+    #[test]
+    fn test4() {
+        let rxdata= "5601A900180015001800140018001400190013001900140019001400170040001700150018003F0019003E0018003E0019003F0019003E00170040001800140019003E001800150018003F00180014001800140019003F0018001400170016001700150018003F001800140018003F0018003F001800140019003F0018003F0018003E0019004F03";
+        let mut markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        // 第1バイトの最初のビットを反転する(リーダーパルスの次から8ビット目)
+        let target = &mut markandspaces[1 + 8];
+        target.space = if target.space < Microseconds(1000) {
+            Microseconds(1690)
+        } else {
+            Microseconds(560)
+        };
+        let result = decord_receiving_data_strict(&markandspaces, None);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            InfraredRemoteError::NecComplementMismatch.to_string()
+        );
+        // 厳密でなければ復号できる
+        assert!(decord_receiving_data(&markandspaces, None).is_ok());
+    }
 }
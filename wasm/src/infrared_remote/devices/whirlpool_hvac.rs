@@ -0,0 +1,121 @@
+// Copyright (c) 2023 Akihiro Yamamoto.
+// Licensed under the MIT License <https://spdx.org/licenses/MIT.html>
+// See LICENSE file in the project root for full license information.
+//
+use crate::infrared_remote::*;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+//
+// Whirlpool HVAC first 2bytes value is
+// 0xd6 29 (LSB first)
+//
+const ADDRESS: [LsbFirst; 2] = [LsbFirst::new(0xd6), LsbFirst::new(0x29)];
+
+//
+static HVAC_MODE: Lazy<HashMap<u8, &'static str>> = Lazy::new(|| {
+    let mut hm = HashMap::new();
+    hm.insert(0x1, "hvac_mode_cool");
+    hm.insert(0x4, "hvac_mode_fan");
+    hm.insert(0x6, "hvac_mode_dry");
+    hm
+});
+
+//
+static POWER_SWITCH: Lazy<HashMap<Bit, &'static str>> = Lazy::new(|| {
+    let mut hm = HashMap::new();
+    hm.insert(Bit::Lo, "power_off");
+    hm.insert(Bit::Hi, "power_on");
+    hm
+});
+
+//
+static FAN_SPEED: Lazy<HashMap<u8, &'static str>> = Lazy::new(|| {
+    let mut hm = HashMap::new();
+    hm.insert(0x0, "high");
+    hm.insert(0x2, "med");
+    hm.insert(0x3, "low");
+    hm.insert(0x5, "auto");
+    hm
+});
+
+/// デコード
+pub fn decode(frames: &[DecordedInfraredRemoteFrame]) -> Vec<InfraredRemoteControlCode> {
+    frames
+        .iter()
+        .flat_map(|f: &DecordedInfraredRemoteFrame| match f {
+            DecordedInfraredRemoteFrame::Nec(bits) => {
+                // data required 48bits
+                let octets = bits.get(0..48).map(|x| {
+                    pack_to_octets(x)
+                        .into_iter()
+                        .map(LsbFirst::from)
+                        .collect::<Vec<LsbFirst>>()
+                })?;
+                // Whirlpool以外のアドレスは対象外
+                if octets[0..2] != ADDRESS {
+                    return None;
+                }
+                let octets = octets.into_iter().map(u8::from).collect::<Vec<u8>>();
+                let mut decorded: HashMap<String, String> = HashMap::new();
+                // 電源
+                let power_switch = Bit::try_from(octets[4] >> 3 & 1).ok()?;
+                POWER_SWITCH
+                    .get(&power_switch)
+                    .map(|&item| decorded.insert("power_switch".to_owned(), item.to_owned()));
+                // モード
+                HVAC_MODE
+                    .get(&(octets[4] & 0x7))
+                    .map(|&item| decorded.insert("hvac_mode".to_owned(), item.to_owned()));
+                // 温度
+                decorded.insert(
+                    "temperature".to_owned(),
+                    (16 + (octets[2] & 0xf)).to_string(),
+                );
+                // 風量(autoの5を表すため第4ビットから3ビット読む)
+                FAN_SPEED
+                    .get(&(octets[3] >> 4 & 0x7))
+                    .map(|&item| decorded.insert("fan_speed".to_owned(), item.to_owned()));
+                //
+                decorded.insert("manufacturer".to_owned(), "whirlpool".to_owned());
+                Some(InfraredRemoteControlCode(decorded))
+            }
+            _ => None,
+        })
+        .collect::<Vec<InfraredRemoteControlCode>>()
+}
+
+#[cfg(test)]
+mod decode_tests {
+    use crate::infrared_remote::*;
+
+    #[test]
+    fn test1() {
+        // 冷房 24℃ 風量自動 電源入
+        let markandspaces = test_frames::encode_nec_octets(&[0xd6, 0x29, 0x08, 0x50, 0x09, 0x00]);
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("power_switch".to_owned(), "power_on".to_owned());
+        decorded.insert("hvac_mode".to_owned(), "hvac_mode_cool".to_owned());
+        decorded.insert("temperature".to_owned(), "24".to_owned());
+        decorded.insert("fan_speed".to_owned(), "auto".to_owned());
+        decorded.insert("manufacturer".to_owned(), "whirlpool".to_owned());
+        //
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let result = whirlpool_hvac::decode(&frames);
+        let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
+        assert_eq!(result, expected);
+        // 東芝テレビとして復号されない
+        assert_eq!(toshiba_tv::decode(&frames), vec![]);
+        assert_eq!(decord_ir_frames(&frames), expected);
+    }
+
+    #[test]
+    fn test2() {
+        // 東芝テレビのフレームは対象外
+        let frames =
+            decord_receiving_data(&protocol_nec::encode_nec([0x40, 0xbf, 0x12, 0xed]), None)
+                .unwrap();
+        assert_eq!(whirlpool_hvac::decode(&frames), vec![]);
+        assert!(!toshiba_tv::decode(&frames).is_empty());
+    }
+}
@@ -3,7 +3,11 @@
 // Copyright (c) 2023 Akihiro Yamamoto.
 // Licensed under the MIT License <https://spdx.org/licenses/MIT.html>
 // See LICENSE file in the project root for full license information.
-use crate::infrared_remote::{Bit, MarkAndSpace, MarkAndSpaceMicros, Microseconds};
+use crate::infrared_remote::{
+    unfolding_from_lsb_first, Bit, LsbFirst, MarkAndSpace, MarkAndSpaceMicros, Microseconds,
+};
+use serde::{Deserialize, Serialize};
+use std::iter;
 use std::ops::Range;
 
 /// 基準時間 350us ～ 500us typical 425. T = 440 μ秒(実測)
@@ -33,12 +37,17 @@ pub const TYPICAL_BIT_ONE: MarkAndSpaceMicros = MarkAndSpace {
     space: Microseconds(3 * TIME_BASE.0),
 };
 
+/// ストップビット
+/// H-level width, 1 * T(425us) = typical 425us
+/// L-level width, 次のフレームまでの間隔(8ms以上) typical 10ms
+pub const STOP_BIT: MarkAndSpaceMicros = MarkAndSpace {
+    mark: TIME_BASE,
+    space: Microseconds(10000),
+};
+
 /// pulse distance modulation
 pub fn modulate(bit: Bit) -> MarkAndSpaceMicros {
-    match bit {
-        Bit::Hi => TYPICAL_BIT_ONE,
-        Bit::Lo => TYPICAL_BIT_ZERO,
-    }
+    STANDARD_AEHA.modulate(bit)
 }
 
 /// pulse distance modulation
@@ -53,16 +62,253 @@ pub fn demodulate(x: MarkAndSpaceMicros) -> Bit {
 
 ///
 pub fn compare_leader_pulse(tolerance: Microseconds, test: &MarkAndSpaceMicros) -> bool {
-    let aeha: MarkAndSpace<Range<Microseconds>> = MarkAndSpace {
-        mark: Range {
-            start: LEADER.mark - tolerance,
-            end: LEADER.mark + tolerance,
-        },
-        space: Range {
-            start: LEADER.space - tolerance,
-            end: LEADER.space + tolerance,
-        },
+    STANDARD_AEHA.compare_leader_pulse(tolerance, test)
+}
+
+/// 基準時間を指定できるAEHAプロトコル
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AehaProtocol {
+    pub time_base: Microseconds,
+}
+
+/// 標準のAEHAプロトコル T = 440 μ秒
+pub const STANDARD_AEHA: AehaProtocol = AehaProtocol {
+    time_base: TIME_BASE,
+};
+
+/// パナソニックのAEHAプロトコル T = 425 μ秒
+pub const PANASONIC_AEHA: AehaProtocol = AehaProtocol {
+    time_base: Microseconds(425),
+};
+
+impl AehaProtocol {
+    /// リーダーパルス 8T, 4T
+    pub fn leader(&self) -> MarkAndSpaceMicros {
+        MarkAndSpace {
+            mark: Microseconds(8 * self.time_base.0),
+            space: Microseconds(4 * self.time_base.0),
+        }
+    }
+
+    /// 0を意味する信号 1T, 1T
+    pub fn typical_bit_zero(&self) -> MarkAndSpaceMicros {
+        MarkAndSpace {
+            mark: self.time_base,
+            space: self.time_base,
+        }
+    }
+
+    /// 1を意味する信号 1T, 3T
+    pub fn typical_bit_one(&self) -> MarkAndSpaceMicros {
+        MarkAndSpace {
+            mark: self.time_base,
+            space: Microseconds(3 * self.time_base.0),
+        }
+    }
+
+    /// リーダーパルスか判定する
+    pub fn compare_leader_pulse(&self, tolerance: Microseconds, test: &MarkAndSpaceMicros) -> bool {
+        let leader = self.leader();
+        let aeha: MarkAndSpace<Range<Microseconds>> = MarkAndSpace {
+            mark: Range {
+                start: leader.mark.saturating_sub(tolerance),
+                end: leader.mark.saturating_add(tolerance),
+            },
+            space: Range {
+                start: leader.space.saturating_sub(tolerance),
+                end: leader.space.saturating_add(tolerance),
+            },
+        };
+
+        aeha.mark.contains(&test.mark) && aeha.space.contains(&test.space)
+    }
+
+    /// pulse distance modulation
+    pub fn modulate(&self, bit: Bit) -> MarkAndSpaceMicros {
+        match bit {
+            Bit::Hi => self.typical_bit_one(),
+            Bit::Lo => self.typical_bit_zero(),
+        }
+    }
+
+    /// pulse distance modulation
+    /// マークとスペースの比で判定するので基準時間によらない
+    pub fn demodulate(&self, x: MarkAndSpaceMicros) -> Bit {
+        demodulate(x)
+    }
+}
+
+/// ビット列をリーダーパルスとストップビットを含むAEHAフレームに変調する
+pub fn encode_bits(bits: &[Bit]) -> Vec<MarkAndSpaceMicros> {
+    iter::once(LEADER)
+        .chain(bits.iter().map(|&bit| modulate(bit)))
+        .chain(iter::once(STOP_BIT))
+        .collect()
+}
+
+/// オクテット列をLSBファーストでAEHAフレームに変調する
+pub fn encode_frame(payload: &[u8]) -> Vec<MarkAndSpaceMicros> {
+    let bits = payload
+        .iter()
+        .flat_map(|&octet| unfolding_from_lsb_first(LsbFirst::new(octet)))
+        .collect::<Vec<Bit>>();
+    encode_bits(&bits)
+}
+
+#[cfg(test)]
+mod encode_frame_tests {
+    use crate::infrared_remote::*;
+    use crate::parsing;
+
+    // 復号済みフレームをオクテット列に戻して再変調する
+    fn reencode(frames: &[DecordedInfraredRemoteFrame]) -> Vec<MarkAndSpaceMicros> {
+        frames
+            .iter()
+            .flat_map(|fr| match fr {
+                DecordedInfraredRemoteFrame::Aeha(bits) => {
+                    let payload = pack_to_octets(bits)
+                        .iter()
+                        .map(|x| u8::from(folding_to_lsb_first(x)))
+                        .collect::<Vec<u8>>();
+                    protocol_aeha::encode_frame(&payload)
+                }
+                _ => vec![],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test1() {
+        let result = protocol_aeha::encode_frame(&[]);
+        assert_eq!(result, vec![protocol_aeha::LEADER, protocol_aeha::STOP_BIT]);
+        let frames = decord_receiving_data(&result, None).unwrap();
+        assert_eq!(frames, vec![DecordedInfraredRemoteFrame::Aeha(vec![])])
+    }
+
+    #[test]
+    fn test2() {
+        let result = protocol_aeha::encode_frame(&[0x02]);
+        assert_eq!(result.len(), 1 + 8 + 1);
+        let frames = decord_receiving_data(&result, None).unwrap();
+        assert_eq!(
+            frames,
+            vec![DecordedInfraredRemoteFrame::Aeha(vec_bits!("01000000"))]
+        )
+    }
+
+    #[test]
+    fn test3() {
+        // 8ビット単位でないフレームは復号できない
+        let result = protocol_aeha::encode_bits(&vec_bits!("0100_0000_101"));
+        assert_eq!(
+            decord_receiving_data(&result, None)
+                .unwrap_err()
+                .to_string(),
+            InfraredRemoteError::InvalidFrameLength {
+                protocol: "AEHA",
+                actual: 11
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test4() {
+        // panasonic_hvac test1
+        let rxdata = "8800410014001000130032001300100013001000130010001300100014001000130010001300100013001000130010001400100013001000130032001300100013001000130010001400100013001000130010001300100014003100130032001300320013001000130010001300320013001000130010001400100013001000130010001300100014000F00140010001300100013001000130010001300100014001000130010001300100013001000140010001300100013001000130010001300100014001000130010001300100013001000130010001400100013001000130010001400100013003100140031001300100013001000140010001300100013001000130082018800410013001000140031001300100014001000130010001300100014000F001400100013001000130010001300100014000F00140010001300320013001000130010001300100014000F001400100013001000130010001300320013003200130032001300100013001000130032001300100013001000140010001300100013001000130010001300100014001000130010001300100013001000140010001300100013003200130010001300100014003100130032001300310014001000130010001300100013001000140031001300100014003100130032001300100013000F0013001000140010001300100013001000130010001300100014001000130031001400310013003200130032001300320013001000130032001300100013003200130031001400100013003100140031001300100013001000140010001300100013001000130010001400100013001000130010001300100013001000140010001300100013003200130032001300310014000F0014001000130010001300100013001000130010001400100013001000130010001300320013003200130031001400100013001000130010001300100013001000140010001300320013001000130010001300100014000F001400100013001000130010001300100014001000130010001300320013003200130010001300100013001000130011001300310013001000140010001300100013001000130010001300100014001000130010001300100013001000130011001300310013001000140010001300100013001000130032001300320013001000130032001300100013003200130032001300100013004F03";
+        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        let expected = decord_receiving_data(&markandspaces, None).unwrap();
+        let result = decord_receiving_data(&reencode(&expected), None).unwrap();
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn test5() {
+        // daikin_hvac test1
+        let rxdata= "[417,448,418,450,417,450,417,449,418,448,417,25329,3450,1747,418,1315,419,446,419,449,417,450,417,1315,418,449,417,449,417,449,417,450,417,1314,418,450,417,1315,417,1315,418,448,418,1315,418,1315,417,1315,418,1315,417,1315,418,450,417,448,419,1312,419,449,417,449,417,451,416,449,419,448,417,449,417,450,417,448,419,448,417,449,417,1316,417,450,416,1314,419,448,418,449,417,449,418,1314,418,1314,419,449,417,450,417,448,419,447,418,450,417,448,419,448,417,449,417,449,418,448,418,449,418,449,417,448,419,448,417,449,418,449,417,1315,418,1314,418,1315,418,448,419,1313,419,448,419,1313,419,1313,420,34665,3450,1748,418,1314,419,447,418,450,416,450,417,1316,416,450,418,448,417,449,418,449,417,1315,418,449,418,1315,417,1315,417,451,416,1316,417,1314,418,1314,418,1316,416,1316,417,450,417,450,417,1313,418,451,416,449,417,449,418,449,416,450,417,449,417,450,416,449,417,450,416,451,416,449,419,1314,418,448,417,449,417,451,416,449,418,1317,416,450,415,450,417,449,418,448,417,450,416,450,417,451,416,448,417,450,417,449,417,450,417,450,417,449,418,448,417,453,414,449,417,449,417,450,416,450,416,1316,418,449,417,1315,417,449,418,1315,418,449,417,34670,3449,1750,416,1316,417,451,416,449,416,450,417,1315,418,450,416,450,415,451,417,449,417,1316,416,450,418,1315,416,1316,417,449,418,1315,418,1315,417,1316,417,1315,417,1315,418,450,416,450,417,1316,416,454,412,450,416,451,416,450,416,450,416,450,416,451,416,451,417,448,417,450,416,449,418,450,417,448,417,450,417,450,416,450,416,450,417,450,416,1317,416,1316,416,450,416,1317,417,1315,417,1316,417,449,418,448,417,452,414,451,416,1316,416,1316,417,450,416,1316,417,449,418,450,417,449,416,450,417,450,417,450,416,450,416,451,415,450,419,448,416,1316,417,1316,417,1315,418,1317,416,450,417,449,417,1315,417,450,416,450,420,448,415,450,416,450,417,450,416,450,416,450,417,449,418,1315,417,451,416,449,417,1316,416,451,416,450,416,451,415,1316,417,451,416,1316,416,450,418,450,415,450,416,451,416,451,416,449,417,450,416,450,417,450,416,450,416,450,416,1316,417,1317,417,447,418,450,416,451,416,451,416,449,416,450,417,450,417,449,416,450,416,452,414,451,416,450,416,451,415,451,416,451,415,450,416,451,416,1317,416,451,415,451,416,451,415,452,414,451,415,1317,417,1316,416,451,416,451,416,450,415,453,414,451,415,451,416,451,415,452,414,452,415,450,417,451,416,451,414,451,416,451,416,451,414,451,416,451,415,451,416,1317,416,451,415,1317,416,1316,417,1316,416,451,416]";
+        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        let expected = decord_receiving_data(&markandspaces, None)
+            .unwrap()
+            .into_iter()
+            .filter(|fr| matches!(fr, DecordedInfraredRemoteFrame::Aeha(_)))
+            .collect::<Vec<DecordedInfraredRemoteFrame>>();
+        assert_eq!(expected.len(), 3);
+        let result = decord_receiving_data(&reencode(&expected), None).unwrap();
+        assert_eq!(result, expected)
+    }
+}
+
+#[cfg(test)]
+mod aeha_protocol_tests {
+    use crate::infrared_remote::decord_receiving_data_with_aeha;
+    use crate::infrared_remote::protocol_aeha::*;
+    use crate::infrared_remote::{
+        Bit, DecordedInfraredRemoteFrame, MarkAndSpaceMicros, Microseconds,
     };
+    use std::iter;
+
+    #[test]
+    fn test1() {
+        assert_eq!(STANDARD_AEHA.leader(), LEADER);
+        assert_eq!(STANDARD_AEHA.typical_bit_zero(), TYPICAL_BIT_ZERO);
+        assert_eq!(STANDARD_AEHA.typical_bit_one(), TYPICAL_BIT_ONE);
+        assert_eq!(
+            PANASONIC_AEHA.leader(),
+            MarkAndSpaceMicros::from((Microseconds(3400), Microseconds(1700)))
+        );
+    }
 
-    aeha.mark.contains(&test.mark) && aeha.space.contains(&test.space)
+    #[test]
+    fn test2() {
+        // 350usのリーダーパルスは標準の許容範囲外
+        let custom = AehaProtocol {
+            time_base: Microseconds(350),
+        };
+        let leader = custom.leader();
+        assert!(custom.compare_leader_pulse(Microseconds(100), &leader));
+        assert!(!STANDARD_AEHA.compare_leader_pulse(Microseconds(100), &leader));
+        assert_eq!(custom.demodulate(custom.typical_bit_one()), Bit::Hi);
+        assert_eq!(custom.demodulate(custom.typical_bit_zero()), Bit::Lo);
+    }
+
+    #[test]
+    fn test3() {
+        assert_eq!(STANDARD_AEHA.modulate(Bit::Hi), TYPICAL_BIT_ONE);
+        assert_eq!(STANDARD_AEHA.modulate(Bit::Lo), TYPICAL_BIT_ZERO);
+        // 350usのAEHAフレームは基準時間を指定すれば復号できる
+        let custom = AehaProtocol {
+            time_base: Microseconds(350),
+        };
+        let bits = [
+            Bit::Lo,
+            Bit::Hi,
+            Bit::Lo,
+            Bit::Lo,
+            Bit::Lo,
+            Bit::Lo,
+            Bit::Lo,
+            Bit::Lo,
+        ];
+        let source = iter::once(custom.leader())
+            .chain(bits.iter().map(|&bit| custom.modulate(bit)))
+            .chain(iter::once(STOP_BIT))
+            .collect::<Vec<MarkAndSpaceMicros>>();
+        let frames = decord_receiving_data_with_aeha(&source, None, &custom).unwrap();
+        assert_eq!(
+            frames,
+            vec![DecordedInfraredRemoteFrame::Aeha(bits.to_vec())]
+        );
+        let frames = decord_receiving_data_with_aeha(&source, None, &STANDARD_AEHA).unwrap();
+        assert!(!matches!(frames[0], DecordedInfraredRemoteFrame::Aeha(_)));
+        // パナソニックのAEHAプロトコル
+        let source = iter::once(PANASONIC_AEHA.leader())
+            .chain(bits.iter().map(|&bit| PANASONIC_AEHA.modulate(bit)))
+            .chain(iter::once(STOP_BIT))
+            .collect::<Vec<MarkAndSpaceMicros>>();
+        let frames = decord_receiving_data_with_aeha(&source, None, &PANASONIC_AEHA).unwrap();
+        assert_eq!(
+            frames,
+            vec![DecordedInfraredRemoteFrame::Aeha(bits.to_vec())]
+        );
+    }
 }
@@ -0,0 +1,130 @@
+// KASEIKYO(家電製品協会)プロトコルの定義
+//
+// Copyright (c) 2023 Akihiro Yamamoto.
+// Licensed under the MIT License <https://spdx.org/licenses/MIT.html>
+// See LICENSE file in the project root for full license information.
+//
+// 変調はAEHAと同じで, 48ビットのフレームが次の構造を持つものをKASEIKYOとする
+// メーカーコード(2バイト), メーカーコードのパリティ(4ビット), データ(20ビット), パリティ(1バイト)
+//
+use crate::infrared_remote::{
+    pack_to_octets, protocol_aeha, Bit, LsbFirst, MarkAndSpaceMicros, Microseconds,
+};
+
+/// 基準時間(AEHAと同じ)
+pub const TIME_BASE: Microseconds = protocol_aeha::TIME_BASE;
+
+/// フレームのビット数
+pub const FRAME_BITS: usize = 48;
+
+/// pulse distance modulation
+pub fn demodulate(x: MarkAndSpaceMicros) -> Bit {
+    protocol_aeha::demodulate(x)
+}
+
+/// リーダーパルスはAEHAと同じ
+pub fn compare_leader_pulse(tolerance: Microseconds, test: &MarkAndSpaceMicros) -> bool {
+    protocol_aeha::compare_leader_pulse(tolerance, test)
+}
+
+/// メーカーコードのパリティ
+/// メーカーコード2バイトの各ニブルの排他的論理和
+pub fn vendor_parity(oem: [u8; 2]) -> u8 {
+    oem.iter()
+        .fold(0u8, |acc, &octet| acc ^ (octet >> 4) ^ (octet & 0x0f))
+}
+
+/// パリティ
+/// データ3バイトの排他的論理和
+pub fn parity(data: [u8; 3]) -> u8 {
+    data.iter().fold(0u8, |acc, &octet| acc ^ octet)
+}
+
+/// リーダーパルスを除いたフレームを復調する
+/// KASEIKYOの構造を持たない場合はNone
+pub fn demodulate_bits(trailer: &[MarkAndSpaceMicros]) -> Option<Vec<Bit>> {
+    let mut bits = trailer.iter().map(|&x| demodulate(x)).collect::<Vec<Bit>>();
+    let _ = bits.pop(); // remove stop bit
+    if bits.len() != FRAME_BITS {
+        return None;
+    }
+    let octets = pack_to_octets(&bits)
+        .iter()
+        .map(|&x| u8::from(LsbFirst::from(x)))
+        .collect::<Vec<u8>>();
+    let vendor_ok = octets[2] & 0x0f == vendor_parity([octets[0], octets[1]]);
+    let parity_ok = octets[5] == parity([octets[2], octets[3], octets[4]]);
+    (vendor_ok && parity_ok).then_some(bits)
+}
+
+/// メーカーコード(2バイト)とデータ(3バイト)をKASEIKYOフレームに変調する
+/// データ1バイト目の下位4ビットはメーカーコードのパリティで置き換える
+pub fn encode_frame(oem: [u8; 2], data: [u8; 3]) -> Vec<MarkAndSpaceMicros> {
+    let data = [data[0] & 0xf0 | vendor_parity(oem), data[1], data[2]];
+    protocol_aeha::encode_frame(&[oem[0], oem[1], data[0], data[1], data[2], parity(data)])
+}
+
+#[cfg(test)]
+mod encode_frame_tests {
+    use crate::infrared_remote::*;
+    use crate::parsing;
+
+    #[test]
+    fn test1() {
+        // Panasonic TV 電源
+        let source = protocol_kaseikyo::encode_frame([0x02, 0x20], [0x80, 0x00, 0x3d]);
+        assert_eq!(source.len(), 1 + 48 + 1);
+        let frames = decord_receiving_data(&source, None).unwrap();
+        let expected = vec![DecordedInfraredRemoteFrame::Kaseikyo(vec_bits!(
+            "01000000", "00000100", "00000001", "00000000", "10111100", "10111101"
+        ))];
+        assert_eq!(frames, expected);
+        assert_eq!(
+            detect_protocol(&source).unwrap().protocol,
+            InfraredRemoteProtocol::Kaseikyo
+        )
+    }
+
+    #[test]
+    fn test2() {
+        // Panasonic TV 電源(実機の信号)
+        let rxdata = "8700410014000F0014002F001400100013001000130010001300100013000F0014000F0014000F001300100014000E00140010001300100013002F001400100013001000130010001300100013000F0015000E0014000F0013001000130010001300300014000F0014000F0014000F0013001000130010001300100013000F0014000F0014002F00140010001300300014002F0015002F00150030001400100013000F0014002F00140010001300300014002F0015002F00140030001400100013002F0015004F03";
+        let source = parsing::parse_infrared_code_text(rxdata).unwrap();
+        let frames = decord_receiving_data(&source, None).unwrap();
+        assert_eq!(
+            frames,
+            decord_receiving_data(
+                &protocol_kaseikyo::encode_frame([0x02, 0x20], [0x80, 0x00, 0x3d]),
+                None
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            detect_protocol(&source).unwrap().protocol,
+            InfraredRemoteProtocol::Kaseikyo
+        )
+    }
+
+    #[test]
+    fn test3() {
+        assert_eq!(protocol_kaseikyo::vendor_parity([0x02, 0x20]), 0x0);
+        assert_eq!(protocol_kaseikyo::vendor_parity([0x23, 0xcb]), 0x6);
+        assert_eq!(protocol_kaseikyo::parity([0x80, 0x00, 0x3d]), 0xbd);
+    }
+
+    #[test]
+    fn test4() {
+        // パリティが一致しない48ビットのフレームはAEHAのまま
+        let source = protocol_aeha::encode_frame(&[0x02, 0x20, 0x80, 0x00, 0x3d, 0xc2]);
+        let frames = decord_receiving_data(&source, None).unwrap();
+        assert!(matches!(frames[..], [DecordedInfraredRemoteFrame::Aeha(_)]));
+        assert_eq!(
+            detect_protocol(&source).unwrap().protocol,
+            InfraredRemoteProtocol::Aeha
+        );
+        // 長さが違うフレームもAEHAのまま
+        let source = protocol_aeha::encode_frame(&[0x02, 0x20, 0x80, 0x00, 0x3d, 0xbd, 0x00]);
+        let frames = decord_receiving_data(&source, None).unwrap();
+        assert!(matches!(frames[..], [DecordedInfraredRemoteFrame::Aeha(_)]));
+    }
+}
@@ -3,7 +3,12 @@
 // Copyright (c) 2023 Akihiro Yamamoto.
 // Licensed under the MIT License <https://spdx.org/licenses/MIT.html>
 // See LICENSE file in the project root for full license information.
-use crate::infrared_remote::{Bit, MarkAndSpace, MarkAndSpaceMicros, Microseconds};
+use crate::infrared_remote::{
+    invert_bits, unfolding_from_lsb_first, Bit, LsbFirst, MarkAndSpace, MarkAndSpaceMicros,
+    Microseconds,
+};
+use serde::{Deserialize, Serialize};
+use std::iter;
 use std::ops::Range;
 
 /// 基準時間 T = 562 μ秒
@@ -41,12 +46,17 @@ pub const TYPICAL_BIT_ONE: MarkAndSpaceMicros = MarkAndSpace {
     space: Microseconds(3 * TIME_BASE.0),
 };
 
+/// ストップビット
+/// H-level width, 1 * T(562us) = typical 562us
+/// L-level width, 次のフレームまでの間隔 typical 40ms
+pub const STOP_BIT: MarkAndSpaceMicros = MarkAndSpace {
+    mark: TIME_BASE,
+    space: Microseconds(40000),
+};
+
 /// pulse distance modulation
 pub fn modulate(bit: Bit) -> MarkAndSpaceMicros {
-    match bit {
-        Bit::Hi => TYPICAL_BIT_ONE,
-        Bit::Lo => TYPICAL_BIT_ZERO,
-    }
+    STANDARD_NEC.modulate(bit)
 }
 
 /// pulse distance modulation
@@ -61,30 +71,210 @@ pub fn demodulate(x: MarkAndSpaceMicros) -> Bit {
 
 ///
 pub fn compare_leader_pulse(tolerance: Microseconds, test: &MarkAndSpaceMicros) -> bool {
-    let nec: MarkAndSpace<Range<Microseconds>> = MarkAndSpace {
-        mark: Range {
-            start: LEADER.mark - tolerance,
-            end: LEADER.mark + tolerance,
-        },
-        space: Range {
-            start: LEADER.space - tolerance,
-            end: LEADER.space + tolerance,
-        },
-    };
-    nec.mark.contains(&test.mark) && nec.space.contains(&test.space)
+    STANDARD_NEC.compare_leader_pulse(tolerance, test)
+}
+
+/// 基準時間とリーダーパルスの長さを指定できるNECプロトコル
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NecProtocol {
+    pub time_base: Microseconds,
+    pub leader_mark_multiplier: u32,
+    pub leader_space_multiplier: u32,
+}
+
+/// 標準のNECプロトコル T = 562 μ秒, リーダーパルス 16T, 8T
+pub const STANDARD_NEC: NecProtocol = NecProtocol {
+    time_base: TIME_BASE,
+    leader_mark_multiplier: 16,
+    leader_space_multiplier: 8,
+};
+
+/// サムスンのNECプロトコル T = 560 μ秒, リーダーパルス 8T, 8T
+pub const SAMSUNG_NEC: NecProtocol = NecProtocol {
+    time_base: Microseconds(560),
+    leader_mark_multiplier: 8,
+    leader_space_multiplier: 8,
+};
+
+impl NecProtocol {
+    /// リーダーパルス
+    pub fn leader(&self) -> MarkAndSpaceMicros {
+        MarkAndSpace {
+            mark: Microseconds(self.leader_mark_multiplier * self.time_base.0),
+            space: Microseconds(self.leader_space_multiplier * self.time_base.0),
+        }
+    }
+
+    /// 0を意味する信号 1T, 1T
+    pub fn typical_bit_zero(&self) -> MarkAndSpaceMicros {
+        MarkAndSpace {
+            mark: self.time_base,
+            space: self.time_base,
+        }
+    }
+
+    /// 1を意味する信号 1T, 3T
+    pub fn typical_bit_one(&self) -> MarkAndSpaceMicros {
+        MarkAndSpace {
+            mark: self.time_base,
+            space: Microseconds(3 * self.time_base.0),
+        }
+    }
+
+    /// リーダーパルスか判定する
+    pub fn compare_leader_pulse(&self, tolerance: Microseconds, test: &MarkAndSpaceMicros) -> bool {
+        let leader = self.leader();
+        let nec: MarkAndSpace<Range<Microseconds>> = MarkAndSpace {
+            mark: Range {
+                start: leader.mark.saturating_sub(tolerance),
+                end: leader.mark.saturating_add(tolerance),
+            },
+            space: Range {
+                start: leader.space.saturating_sub(tolerance),
+                end: leader.space.saturating_add(tolerance),
+            },
+        };
+        nec.mark.contains(&test.mark) && nec.space.contains(&test.space)
+    }
+
+    /// pulse distance modulation
+    pub fn modulate(&self, bit: Bit) -> MarkAndSpaceMicros {
+        match bit {
+            Bit::Hi => self.typical_bit_one(),
+            Bit::Lo => self.typical_bit_zero(),
+        }
+    }
+
+    /// pulse distance modulation
+    /// マークとスペースの比で判定するので基準時間によらない
+    pub fn demodulate(&self, x: MarkAndSpaceMicros) -> Bit {
+        demodulate(x)
+    }
 }
 
 ///
 pub fn compare_repeat_pulse(tolerance: Microseconds, test: &MarkAndSpaceMicros) -> bool {
     let nec_repeat: MarkAndSpace<Range<Microseconds>> = MarkAndSpace {
         mark: Range {
-            start: REPEAT.mark - tolerance,
-            end: REPEAT.mark + tolerance,
+            start: REPEAT.mark.saturating_sub(tolerance),
+            end: REPEAT.mark.saturating_add(tolerance),
         },
         space: Range {
-            start: REPEAT.space - tolerance,
-            end: REPEAT.space + tolerance,
+            start: REPEAT.space.saturating_sub(tolerance),
+            end: REPEAT.space.saturating_add(tolerance),
         },
     };
     nec_repeat.mark.contains(&test.mark) && nec_repeat.space.contains(&test.space)
 }
+
+/// 32ビット(カスタマーコード, カスタマーコード反転, データ, データ反転)の
+/// 第1, 第3バイトが第0, 第2バイトの反転になっているか確かめる
+pub fn verify_complement(bits: &[Bit]) -> bool {
+    match (
+        bits.get(0..8),
+        bits.get(8..16),
+        bits.get(16..24),
+        bits.get(24..32),
+    ) {
+        (Some(custom), Some(inv_custom), Some(data), Some(inv_data)) => {
+            invert_bits(custom) == inv_custom && invert_bits(data) == inv_data
+        }
+        _ => false,
+    }
+}
+
+/// 32ビット(カスタマーコード, カスタマーコード反転, データ, データ反転)を
+/// リーダーパルスとストップビットを含むNECフレームに変調する
+pub fn encode_nec(payload: [u8; 4]) -> Vec<MarkAndSpaceMicros> {
+    let bits = payload
+        .into_iter()
+        .flat_map(|octet| unfolding_from_lsb_first(LsbFirst::new(octet)));
+    iter::once(LEADER)
+        .chain(bits.map(modulate))
+        .chain(iter::once(STOP_BIT))
+        .collect()
+}
+
+/// リピートパルスとストップビットからなるNECリピートフレームを返す
+pub fn encode_nec_repeat() -> Vec<MarkAndSpaceMicros> {
+    vec![REPEAT, STOP_BIT]
+}
+
+#[cfg(test)]
+mod encode_nec_tests {
+    use crate::infrared_remote::*;
+
+    #[test]
+    fn test1() {
+        let source = protocol_nec::encode_nec([0x40, 0xbf, 0x12, 0xed]);
+        assert_eq!(source.len(), 1 + 32 + 1);
+        let frames = decord_receiving_data(&source, None).unwrap();
+        let mut decorded: HashMap<String, String> = HashMap::new();
+        decorded.insert("address".to_owned(), "tv".to_owned());
+        decorded.insert("command".to_owned(), "Power".to_owned());
+        decorded.insert("manufacturer".to_owned(), "toshiba".to_owned());
+        let expected: Vec<InfraredRemoteControlCode> = vec![InfraredRemoteControlCode(decorded)];
+        assert_eq!(toshiba_tv::decode(&frames), expected)
+    }
+
+    #[test]
+    fn test2() {
+        let source = crate::parsing::parse_infrared_code_text("5601A900180015001800140018001400190013001900140019001400170040001700150018003F0019003E0018003E0019003F0019003E00170040001800140019003E001800150018003F00180014001800140019003F0018001400170016001700150018003F001800140018003F0018003F001800140019003F0018003F0018003E0019004F03").unwrap();
+        let expected = decord_receiving_data(&source, None).unwrap();
+        let result =
+            decord_receiving_data(&protocol_nec::encode_nec([0x40, 0xbf, 0x12, 0xed]), None);
+        assert_eq!(result.unwrap(), expected)
+    }
+
+    #[test]
+    fn test3() {
+        let mut source = protocol_nec::encode_nec([0x40, 0xbf, 0x12, 0xed]);
+        source.extend(protocol_nec::encode_nec_repeat());
+        let frames = decord_receiving_data(&source, None).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1], DecordedInfraredRemoteFrame::NecRepeat(()))
+    }
+
+    #[test]
+    fn test4() {
+        let bits = vec_bits!("00000010", "11111101", "01001000", "10110111");
+        assert!(protocol_nec::verify_complement(&bits));
+        let bits = vec_bits!("00000010", "11111100", "01001000", "10110111");
+        assert!(!protocol_nec::verify_complement(&bits));
+        let bits = vec_bits!("00000010", "11111101", "01001000");
+        assert!(!protocol_nec::verify_complement(&bits));
+    }
+}
+
+#[cfg(test)]
+mod nec_protocol_tests {
+    use crate::infrared_remote::protocol_nec::*;
+    use crate::infrared_remote::{decord_receiving_data_with_nec, protocol_samsung};
+    use crate::infrared_remote::{Bit, DecordedInfraredRemoteFrame, Microseconds};
+
+    #[test]
+    fn test1() {
+        assert_eq!(STANDARD_NEC.leader(), LEADER);
+        assert_eq!(STANDARD_NEC.typical_bit_zero(), TYPICAL_BIT_ZERO);
+        assert_eq!(STANDARD_NEC.typical_bit_one(), TYPICAL_BIT_ONE);
+        assert_eq!(SAMSUNG_NEC.leader(), protocol_samsung::LEADER);
+        assert_eq!(
+            SAMSUNG_NEC.demodulate(SAMSUNG_NEC.typical_bit_one()),
+            Bit::Hi
+        );
+        assert!(!STANDARD_NEC.compare_leader_pulse(Microseconds(300), &SAMSUNG_NEC.leader()));
+        assert_eq!(STANDARD_NEC.modulate(Bit::Hi), TYPICAL_BIT_ONE);
+        assert_eq!(STANDARD_NEC.modulate(Bit::Lo), TYPICAL_BIT_ZERO);
+    }
+
+    #[test]
+    fn test2() {
+        // サムスンのリーダーパルスのNECプロトコルとして復号する
+        let source = protocol_samsung::encode_samsung([0x07, 0x07, 0x02, 0xfd]);
+        let frames = decord_receiving_data_with_nec(&source, None, &SAMSUNG_NEC).unwrap();
+        assert!(matches!(frames[0], DecordedInfraredRemoteFrame::Nec(_)));
+        // 既定ではSAMSUNG
+        let frames = decord_receiving_data_with_nec(&source, None, &STANDARD_NEC).unwrap();
+        assert!(matches!(frames[0], DecordedInfraredRemoteFrame::Samsung(_)));
+    }
+}
@@ -0,0 +1,171 @@
+// RC5プロトコルの定義
+//
+// Copyright (c) 2023 Akihiro Yamamoto.
+// Licensed under the MIT License <https://spdx.org/licenses/MIT.html>
+// See LICENSE file in the project root for full license information.
+use crate::infrared_remote::{
+    folding_to_msb_first, Bit, MarkAndSpace, MarkAndSpaceMicros, Microseconds, TOLERANCE,
+};
+use std::iter;
+use std::ops::Range;
+
+/// 基準時間(半ビット時間) T = 889 μ秒
+pub const TIME_BASE: Microseconds = Microseconds(889);
+
+/// フレームのビット数
+/// スタートビット(2), トグルビット(1), アドレス(5), コマンド(6)
+pub const FRAME_BITS: usize = 14;
+
+/// リーダーパルス
+/// スタートビットは "1" なので前半のL-levelは見えず
+/// H-level width, 1 * T(889us) = typical 889us
+/// L-level width, 1 * T(889us) = typical 889us (次のビットが "0" なら 2 * T)
+pub const LEADER: MarkAndSpaceMicros = MarkAndSpace {
+    mark: TIME_BASE,
+    space: TIME_BASE,
+};
+
+/// RC5拡張(第2スタートビットが "0")ではリーダーパルスのH-levelが 2 * T になる
+pub fn compare_leader_pulse(tolerance: Microseconds, test: &MarkAndSpaceMicros) -> bool {
+    let single: Range<Microseconds> = Range {
+        start: LEADER.mark.saturating_sub(tolerance),
+        end: LEADER.mark.saturating_add(tolerance),
+    };
+    let double: Range<Microseconds> = Range {
+        start: (LEADER.mark + LEADER.mark).saturating_sub(tolerance),
+        end: (LEADER.mark + LEADER.mark).saturating_add(tolerance),
+    };
+    let mark_ok = single.contains(&test.mark) || double.contains(&test.mark);
+    let space_ok = single.contains(&test.space) || double.contains(&test.space);
+    mark_ok && space_ok
+}
+
+// 半ビット時間が何個分か数える
+fn count_half_bits(duration: Microseconds) -> Option<usize> {
+    match duration.0.saturating_add(TIME_BASE.0 / 2) / TIME_BASE.0 {
+        n @ 1..=2 if duration.0.abs_diff(n * TIME_BASE.0) <= TOLERANCE.0 => Some(n as usize),
+        _ => None,
+    }
+}
+
+/// bi-phase modulation: RC5
+/// H-level → L-level が "0", L-level → H-level が "1"
+// iter::repeat_n は Rust 1.82 からなので使わない
+#[allow(clippy::manual_repeat_n)]
+pub fn demodulate_bits(frame: &[MarkAndSpaceMicros]) -> Option<Vec<Bit>> {
+    // スタートビットの前半(L-level)
+    let mut half_bits: Vec<Bit> = vec![Bit::Lo];
+    for (index, ms) in frame.iter().enumerate() {
+        half_bits.extend(iter::repeat(Bit::Hi).take(count_half_bits(ms.mark)?));
+        if index + 1 < frame.len() {
+            half_bits.extend(iter::repeat(Bit::Lo).take(count_half_bits(ms.space)?));
+        }
+    }
+    // 最後のスペースはフレーム間隔と区別がつかないので残りを埋める
+    if half_bits.len() < 2 * FRAME_BITS {
+        half_bits.resize(2 * FRAME_BITS, Bit::Lo);
+    }
+    if half_bits.len() != 2 * FRAME_BITS {
+        return None;
+    }
+    half_bits
+        .chunks(2)
+        .map(|pair| match pair {
+            [Bit::Lo, Bit::Hi] => Some(Bit::Hi),
+            [Bit::Hi, Bit::Lo] => Some(Bit::Lo),
+            _ => None,
+        })
+        .collect()
+}
+
+/// 14ビットのRC5フレームを(アドレス, コマンド, トグルビット)に分ける
+/// 第2スタートビットの反転はコマンドの第6ビット(RC5拡張)
+pub fn split_fields(bits: &[Bit]) -> Option<(u8, u8, bool)> {
+    match bits {
+        [Bit::Hi, field, toggle, address @ .., c5, c4, c3, c2, c1, c0] if address.len() == 5 => {
+            let command = u8::from(folding_to_msb_first(&[*c5, *c4, *c3, *c2, *c1, *c0]));
+            let extended = if *field == Bit::Lo { 1 << 6 } else { 0 };
+            Some((
+                u8::from(folding_to_msb_first(address)),
+                extended | command,
+                *toggle == Bit::Hi,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// RC5フレームを(アドレス, コマンド, トグルビット)に復調する
+pub fn demodulate(frame: &[MarkAndSpaceMicros]) -> Option<(u8, u8, bool)> {
+    demodulate_bits(frame).and_then(|bits| split_fields(&bits))
+}
+
+#[cfg(test)]
+mod demodulate_rc5_tests {
+    use crate::infrared_remote::*;
+    use crate::parsing;
+
+    #[test]
+    fn test1() {
+        let rxdata = "[889,889,1778,889,889,889,889,889,889,889,889,889,889,889,889,889,889,1778,889,889,1778,889,889,89000]";
+        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        assert!(protocol_rc5::compare_leader_pulse(
+            TOLERANCE,
+            &markandspaces[0]
+        ));
+        assert_eq!(
+            protocol_rc5::demodulate(&markandspaces),
+            Some((0, 12, false))
+        )
+    }
+
+    #[test]
+    fn test2() {
+        let rxdata = "[905,870,912,861,1790,887,870,901,914,880,889,862,870,903,889,1761,1801,870,900,880,899,861,870,89000]";
+        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        assert_eq!(
+            protocol_rc5::demodulate(&markandspaces),
+            Some((0, 16, true))
+        )
+    }
+
+    #[test]
+    fn test3() {
+        // 拡張コマンド(第2スタートビットが "0")
+        let bits = vec_bits!("10", "0", "00101", "110101");
+        assert_eq!(protocol_rc5::split_fields(&bits), Some((5, 64 + 53, false)))
+    }
+
+    #[test]
+    fn test4() {
+        // NECのリーダーパルスは一致しない
+        assert!(!protocol_rc5::compare_leader_pulse(
+            TOLERANCE,
+            &protocol_nec::LEADER
+        ));
+        // 途中が崩れている
+        let rxdata = "[889,889,889,2667,889,89000]";
+        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        assert_eq!(protocol_rc5::demodulate(&markandspaces), None);
+        // 桁あふれする長さ
+        let rxdata = "[889,889,4294967295,889]";
+        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        assert_eq!(protocol_rc5::demodulate(&markandspaces), None)
+    }
+
+    #[test]
+    fn test5() {
+        // 拡張コマンド 76 (第2スタートビットが "0" なので最初のH-levelが 2 * T)
+        let rxdata = "[1778,889,889,889,889,889,889,889,889,889,889,889,889,889,889,889,889,1778,889,889,1778,889,889,89000]";
+        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        assert_eq!(
+            protocol_rc5::demodulate(&markandspaces),
+            Some((0, 64 + 12, false))
+        );
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        let expected = vec![DecordedInfraredRemoteFrame::Rc5(vec_bits!(
+            "10", "0", "00000", "001100"
+        ))];
+        assert_eq!(frames, expected)
+    }
+}
@@ -0,0 +1,184 @@
+// RC6プロトコルの定義
+//
+// Copyright (c) 2023 Akihiro Yamamoto.
+// Licensed under the MIT License <https://spdx.org/licenses/MIT.html>
+// See LICENSE file in the project root for full license information.
+use crate::infrared_remote::{
+    folding_to_msb_first, Bit, MarkAndSpace, MarkAndSpaceMicros, Microseconds, TOLERANCE,
+};
+use std::iter;
+use std::ops::Range;
+
+/// 基準時間(半ビット時間) T = 444 μ秒
+pub const TIME_BASE: Microseconds = Microseconds(444);
+
+/// リーダーパルス
+/// H-level width, 6 * T(444us) = typical 2664us
+/// L-level width, 2 * T(444us) = typical 888us
+pub const LEADER: MarkAndSpaceMicros = MarkAndSpace {
+    mark: Microseconds(6 * TIME_BASE.0),
+    space: Microseconds(2 * TIME_BASE.0),
+};
+
+/// モード0フレームの半ビット時間の数
+/// スタートビット(2), モード(3 * 2), トレーラービット(4), アドレス(8 * 2), コマンド(8 * 2)
+const MODE0_FRAME_UNITS: usize = 2 + 3 * 2 + 4 + 8 * 2 + 8 * 2;
+
+/// リーダーパルスか判定する
+pub fn compare_leader_pulse(tolerance: Microseconds, test: &MarkAndSpaceMicros) -> bool {
+    let rc6: MarkAndSpace<Range<Microseconds>> = MarkAndSpace {
+        mark: Range {
+            start: LEADER.mark.saturating_sub(tolerance),
+            end: LEADER.mark.saturating_add(tolerance),
+        },
+        space: Range {
+            start: LEADER.space.saturating_sub(tolerance),
+            end: LEADER.space.saturating_add(tolerance),
+        },
+    };
+    rc6.mark.contains(&test.mark) && rc6.space.contains(&test.space)
+}
+
+// 半ビット時間が何個分か数える
+fn count_half_bits(duration: Microseconds) -> Option<usize> {
+    match (duration.0 + TIME_BASE.0 / 2) / TIME_BASE.0 {
+        n @ 1..=3 => Some(n as usize),
+        _ => None,
+    }
+}
+
+/// bi-phase modulation: RC6
+/// リーダーパルスに続くマークアンドスペース列を
+/// スタートビット, モード(3ビット), トグルビット, アドレス(8ビット), コマンド(8ビット)に復調する
+/// H-level → L-level が "1", L-level → H-level が "0"
+// iter::repeat_n は Rust 1.82 からなので使わない
+#[allow(clippy::manual_repeat_n)]
+pub fn demodulate_bits(trailer: &[MarkAndSpaceMicros]) -> Option<Vec<Bit>> {
+    let mut units: Vec<Bit> = vec![];
+    for (index, ms) in trailer.iter().enumerate() {
+        units.extend(iter::repeat(Bit::Hi).take(count_half_bits(ms.mark)?));
+        if index + 1 < trailer.len() {
+            units.extend(iter::repeat(Bit::Lo).take(count_half_bits(ms.space)?));
+        }
+    }
+    // 最後のスペースはフレーム間隔と区別がつかないので残りを埋める
+    if units.len() < MODE0_FRAME_UNITS {
+        units.resize(MODE0_FRAME_UNITS, Bit::Lo);
+    }
+    if units.len() != MODE0_FRAME_UNITS {
+        return None;
+    }
+    let biphase = |pair: &[Bit]| match pair {
+        [Bit::Hi, Bit::Lo] => Some(Bit::Hi),
+        [Bit::Lo, Bit::Hi] => Some(Bit::Lo),
+        _ => None,
+    };
+    // トレーラービットは2倍の幅
+    let trailer_bit = match units[8..12] {
+        [Bit::Hi, Bit::Hi, Bit::Lo, Bit::Lo] => Some(Bit::Hi),
+        [Bit::Lo, Bit::Lo, Bit::Hi, Bit::Hi] => Some(Bit::Lo),
+        _ => None,
+    }?;
+    let mut bits = units[0..8]
+        .chunks(2)
+        .map(biphase)
+        .collect::<Option<Vec<Bit>>>()?;
+    bits.push(trailer_bit);
+    bits.extend(
+        units[12..]
+            .chunks(2)
+            .map(biphase)
+            .collect::<Option<Vec<Bit>>>()?,
+    );
+    // スタートビットは常に "1"
+    if bits[0] == Bit::Hi {
+        Some(bits)
+    } else {
+        None
+    }
+}
+
+/// 21ビットのRC6フレームを(モード, トグルビット, アドレス, コマンド)に分ける
+/// モード0以外は扱わない
+pub fn split_fields(bits: &[Bit]) -> Option<(u8, bool, u8, u8)> {
+    match bits {
+        [Bit::Hi, m2, m1, m0, toggle, rest @ ..] if rest.len() == 16 => {
+            let mode = u8::from(folding_to_msb_first(&[*m2, *m1, *m0]));
+            if mode == 0 {
+                Some((
+                    mode,
+                    *toggle == Bit::Hi,
+                    u8::from(folding_to_msb_first(&rest[0..8])),
+                    u8::from(folding_to_msb_first(&rest[8..16])),
+                ))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// RC6フレームを(モード, トグルビット, アドレス, コマンド)に復調する
+pub fn demodulate(frame: &[MarkAndSpaceMicros]) -> Option<(u8, bool, u8, u8)> {
+    let (leader, trailer) = frame.split_first()?;
+    if compare_leader_pulse(TOLERANCE, leader) {
+        demodulate_bits(trailer).and_then(|bits| split_fields(&bits))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod demodulate_rc6_tests {
+    use crate::infrared_remote::*;
+    use crate::parsing;
+
+    #[test]
+    fn test1() {
+        let rxdata = "[2664,888,444,888,444,444,444,444,444,888,888,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,444,888,444,444,888,444,444,444,83000]";
+        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        assert!(protocol_rc6::compare_leader_pulse(
+            TOLERANCE,
+            &markandspaces[0]
+        ));
+        assert_eq!(
+            protocol_rc6::demodulate(&markandspaces),
+            Some((0, false, 0, 12))
+        )
+    }
+
+    #[test]
+    fn test2() {
+        let rxdata = "[2665,867,454,854,413,472,416,450,1366,1299,468,431,408,415,459,457,412,434,415,474,458,411,476,419,432,484,484,478,411,477,922,898,410,432,409,475,421,441,457,83000]";
+        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        assert_eq!(
+            protocol_rc6::demodulate(&markandspaces),
+            Some((0, true, 0, 16))
+        )
+    }
+
+    #[test]
+    fn test3() {
+        // モード6(MCE)は扱わない
+        let rxdata = "[2664,888,444,444,444,444,444,888,444,888,888,444,444,444,444,444,444,444,444,444,888,888,444,444,444,444,444,444,444,444,444,444,888,444,444,888,444,444,444,83000]";
+        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        assert!(protocol_rc6::demodulate_bits(&markandspaces[1..]).is_some());
+        assert_eq!(protocol_rc6::demodulate(&markandspaces), None)
+    }
+
+    #[test]
+    fn test4() {
+        // RC5のタイミングはRC6ではない
+        let rxdata = "[889,889,1778,889,889,889,889,889,889,889,889,889,889,889,889,889,889,1778,889,889,1778,889,889,89000]";
+        let markandspaces = parsing::parse_infrared_code_text(rxdata).unwrap();
+        assert!(!protocol_rc6::compare_leader_pulse(
+            TOLERANCE,
+            &markandspaces[0]
+        ));
+        assert_eq!(protocol_rc6::demodulate(&markandspaces), None);
+        assert_eq!(protocol_rc6::demodulate_bits(&markandspaces), None);
+        let frames = decord_receiving_data(&markandspaces, None).unwrap();
+        assert!(matches!(frames[0], DecordedInfraredRemoteFrame::Rc5(_)))
+    }
+}
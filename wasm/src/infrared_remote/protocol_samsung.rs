@@ -0,0 +1,119 @@
+// Samsungプロトコルの定義
+//
+// Copyright (c) 2023 Akihiro Yamamoto.
+// Licensed under the MIT License <https://spdx.org/licenses/MIT.html>
+// See LICENSE file in the project root for full license information.
+use crate::infrared_remote::{
+    unfolding_from_lsb_first, Bit, LsbFirst, MarkAndSpace, MarkAndSpaceMicros, Microseconds,
+};
+use std::iter;
+use std::ops::Range;
+
+/// 基準時間 T = 560 μ秒
+pub const TIME_BASE: Microseconds = Microseconds(560);
+
+/// リーダーパルス
+/// H-level width, 8 * T(560us) = typical 4480us
+/// L-level width, 8 * T(560us) = typical 4480us
+pub const LEADER: MarkAndSpaceMicros = MarkAndSpace {
+    mark: Microseconds(8 * TIME_BASE.0),
+    space: Microseconds(8 * TIME_BASE.0),
+};
+
+/// 0を意味する信号
+/// H-level width, 1 * T(560us) = typical 560us
+/// L-level width, 1 * T(560us) = typical 560us
+pub const TYPICAL_BIT_ZERO: MarkAndSpaceMicros = MarkAndSpace {
+    mark: TIME_BASE,
+    space: TIME_BASE,
+};
+
+/// 1を意味する信号
+/// H-level width, 1 * T(560us) = typical 560us
+/// L-level width, 3 * T(560us) = typical 1680us
+pub const TYPICAL_BIT_ONE: MarkAndSpaceMicros = MarkAndSpace {
+    mark: TIME_BASE,
+    space: Microseconds(3 * TIME_BASE.0),
+};
+
+/// ストップビット
+/// H-level width, 1 * T(560us) = typical 560us
+/// L-level width, 次のフレームまでの間隔 typical 40ms
+pub const STOP_BIT: MarkAndSpaceMicros = MarkAndSpace {
+    mark: TIME_BASE,
+    space: Microseconds(40000),
+};
+
+/// pulse distance modulation
+pub fn modulate(bit: Bit) -> MarkAndSpaceMicros {
+    match bit {
+        Bit::Hi => TYPICAL_BIT_ONE,
+        Bit::Lo => TYPICAL_BIT_ZERO,
+    }
+}
+
+/// pulse distance modulation
+pub fn demodulate(x: MarkAndSpaceMicros) -> Bit {
+    if x.mark + x.mark <= x.space {
+        // マーク時間の２倍以上スペース時間があれば
+        Bit::Hi
+    } else {
+        Bit::Lo
+    }
+}
+
+/// リーダーパルスか判定する
+pub fn compare_leader_pulse(tolerance: Microseconds, test: &MarkAndSpaceMicros) -> bool {
+    let samsung: MarkAndSpace<Range<Microseconds>> = MarkAndSpace {
+        mark: Range {
+            start: LEADER.mark.saturating_sub(tolerance),
+            end: LEADER.mark.saturating_add(tolerance),
+        },
+        space: Range {
+            start: LEADER.space.saturating_sub(tolerance),
+            end: LEADER.space.saturating_add(tolerance),
+        },
+    };
+    samsung.mark.contains(&test.mark) && samsung.space.contains(&test.space)
+}
+
+/// 32ビット(カスタマーコード 2バイト, データ, データ反転)を
+/// リーダーパルスとストップビットを含むSamsungフレームに変調する
+pub fn encode_samsung(payload: [u8; 4]) -> Vec<MarkAndSpaceMicros> {
+    let bits = payload
+        .into_iter()
+        .flat_map(|octet| unfolding_from_lsb_first(LsbFirst::new(octet)));
+    iter::once(LEADER)
+        .chain(bits.map(modulate))
+        .chain(iter::once(STOP_BIT))
+        .collect()
+}
+
+#[cfg(test)]
+mod encode_samsung_tests {
+    use crate::infrared_remote::*;
+
+    #[test]
+    fn test1() {
+        let source = protocol_samsung::encode_samsung([0x07, 0x07, 0x02, 0xfd]);
+        assert_eq!(source.len(), 1 + 32 + 1);
+        let frames = decord_receiving_data(&source, None).unwrap();
+        let expected = vec![DecordedInfraredRemoteFrame::Samsung(vec_bits!(
+            "11100000", "11100000", "01000000", "10111111"
+        ))];
+        assert_eq!(frames, expected)
+    }
+
+    #[test]
+    fn test2() {
+        // NECのリーダーパルスとは一致しない
+        assert!(!protocol_samsung::compare_leader_pulse(
+            TOLERANCE,
+            &protocol_nec::LEADER
+        ));
+        assert!(!protocol_nec::compare_leader_pulse(
+            TOLERANCE,
+            &protocol_samsung::LEADER
+        ));
+    }
+}
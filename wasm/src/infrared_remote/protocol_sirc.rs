@@ -3,7 +3,10 @@
 // Copyright (c) 2023 Akihiro Yamamoto.
 // Licensed under the MIT License <https://spdx.org/licenses/MIT.html>
 // See LICENSE file in the project root for full license information.
-use crate::infrared_remote::{Bit, MarkAndSpace, MarkAndSpaceMicros, Microseconds};
+use crate::infrared_remote::{
+    unfolding_from_lsb_first, Bit, LsbFirst, MarkAndSpace, MarkAndSpaceMicros, Microseconds,
+};
+use std::iter;
 use std::ops::Range;
 
 /// 基準時間 T = 600 μ秒
@@ -33,6 +36,10 @@ pub const TYPICAL_BIT_ONE: MarkAndSpaceMicros = MarkAndSpace {
     space: TIME_BASE,
 };
 
+/// フレーム間隔
+/// SIRCにはストップビットが無いので最後のビットのL-level widthをこの時間に延ばして区切る
+pub const FRAME_GAP: Microseconds = Microseconds(20000);
+
 /// pulse width modulation: SIRC
 pub fn modulate(bit: Bit) -> MarkAndSpaceMicros {
     match bit {
@@ -60,14 +67,101 @@ pub fn demodulate(x: MarkAndSpaceMicros) -> Bit {
 pub fn compare_leader_pulse(tolerance: Microseconds, test: &MarkAndSpaceMicros) -> bool {
     let sirc: MarkAndSpace<Range<Microseconds>> = MarkAndSpace {
         mark: Range {
-            start: LEADER.mark - tolerance,
-            end: LEADER.mark + tolerance,
+            start: LEADER.mark.saturating_sub(tolerance),
+            end: LEADER.mark.saturating_add(tolerance),
         },
         space: Range {
-            start: LEADER.space - tolerance,
-            end: LEADER.space + tolerance,
+            start: LEADER.space.saturating_sub(tolerance),
+            end: LEADER.space.saturating_add(tolerance),
         },
     };
 
     sirc.mark.contains(&test.mark) && sirc.space.contains(&test.space)
 }
+
+// 下位から n ビットをLSBファーストで取り出す
+fn lsb_first_bits(value: u8, n: usize) -> impl Iterator<Item = Bit> {
+    unfolding_from_lsb_first(LsbFirst::new(value))
+        .into_iter()
+        .take(n)
+}
+
+// ビット列をリーダーパルスを含むSIRCフレームに変調する
+fn encode_bits(bits: impl Iterator<Item = Bit>) -> Vec<MarkAndSpaceMicros> {
+    let mut sequence = iter::once(LEADER)
+        .chain(bits.map(modulate))
+        .collect::<Vec<MarkAndSpaceMicros>>();
+    if let Some(last) = sequence.last_mut() {
+        // マーク時間で値が決まるのでスペース時間を延ばしても値は変わらない
+        last.space = FRAME_GAP;
+    }
+    sequence
+}
+
+/// 12ビットSIRC(コマンド7ビット, アドレス5ビット)
+pub fn encode_sirc12(command: u8, address: u8) -> Vec<MarkAndSpaceMicros> {
+    encode_bits(lsb_first_bits(command, 7).chain(lsb_first_bits(address, 5)))
+}
+
+/// 15ビットSIRC(コマンド7ビット, アドレス8ビット)
+pub fn encode_sirc15(command: u8, address: u8) -> Vec<MarkAndSpaceMicros> {
+    encode_bits(lsb_first_bits(command, 7).chain(lsb_first_bits(address, 8)))
+}
+
+/// 20ビットSIRC(コマンド7ビット, アドレス5ビット, 拡張8ビット)
+pub fn encode_sirc20(command: u8, address: u8, extended: u8) -> Vec<MarkAndSpaceMicros> {
+    encode_bits(
+        lsb_first_bits(command, 7)
+            .chain(lsb_first_bits(address, 5))
+            .chain(lsb_first_bits(extended, 8)),
+    )
+}
+
+#[cfg(test)]
+mod encode_sirc_tests {
+    use crate::infrared_remote::*;
+
+    #[test]
+    fn test1() {
+        let source = protocol_sirc::encode_sirc12(21, 1);
+        let result = decord_receiving_data(&source, None).unwrap();
+        let expected = vec![DecordedInfraredRemoteFrame::Sirc(vec_bits!(
+            "1010100", "10000"
+        ))];
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn test2() {
+        let source = crate::parsing::parse_infrared_code_text("5B0018002E001800180018002E001800170018002E00190017001800170018002E00180018001800170018001700180017004F03").unwrap();
+        let expected = sirc::decode(&decord_receiving_data(&source, None).unwrap());
+        let result = sirc::decode(
+            &decord_receiving_data(&protocol_sirc::encode_sirc12(21, 1), None).unwrap(),
+        );
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn test3() {
+        let source = protocol_sirc::encode_sirc15(0x7f, 0x9a);
+        assert_eq!(source.len(), 1 + 15);
+        assert!(source[1..15]
+            .iter()
+            .all(|ms| *ms == protocol_sirc::TYPICAL_BIT_ZERO
+                || *ms == protocol_sirc::TYPICAL_BIT_ONE));
+        let result = decord_receiving_data(&source, None).unwrap();
+        let expected = vec![DecordedInfraredRemoteFrame::Sirc(vec_bits!(
+            "1111111", "01011001"
+        ))];
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn test4() {
+        let mut source = protocol_sirc::encode_sirc20(0x01, 0x1a, 0xa5);
+        source.extend(protocol_sirc::encode_sirc20(0x01, 0x1a, 0xa5));
+        let result = decord_receiving_data(&source, None).unwrap();
+        let frame = DecordedInfraredRemoteFrame::Sirc(vec_bits!("1000000", "01011", "10100101"));
+        assert_eq!(result, vec![frame.clone(), frame])
+    }
+}
@@ -2,17 +2,51 @@
 // Licensed under the MIT License <https://spdx.org/licenses/MIT.html>
 // See LICENSE file in the project root for full license information.
 //
+mod format;
 mod infrared_remote;
 mod parsing;
 
+pub use infrared_remote::MAX_SIGNAL_PAIRS;
+pub use parsing::MAX_INPUT_BYTES;
+
+use format::{
+    to_broadlink_base64, to_carrier_hex, to_home_assistant_format, to_pronto_code, to_pronto_hex,
+};
 use infrared_remote::{
-    decord_ir_frames, decord_receiving_data, DecordedInfraredRemoteFrame,
-    InfraredRemoteControlCode, MarkAndSpaceMicros,
+    and_bits, batch_parse_and_decode, bits_from_hex_string, bits_try_from_string,
+    calibrate_tolerance, check_signal_pairs, collapse_nec_repeats, collapse_sirc_repeats,
+    compare_signals, count_frames, daikin_hvac, decode_raw_bits, decord_ir_frames,
+    decord_ir_frames_all, decord_ir_frames_with_fallback, decord_receiving_data,
+    decord_receiving_data_inverted, decord_receiving_data_strict, decord_receiving_data_with_aeha,
+    decord_receiving_data_with_nec, decord_receiving_data_with_priority,
+    decord_receiving_data_with_quality, detect_protocol, diff, folding_to_msb_first_u16,
+    folding_to_msb_first_u32, frame_durations, hitachi_hvac, identify_manufacturer,
+    json_schema_for_control_code, mitsubishi_electric_hvac, normalize_to_protocol, or_bits,
+    panasonic_hvac, parse_and_decode, protocol_aeha, protocol_kaseikyo, protocol_nec, protocol_rc5,
+    protocol_rc6, protocol_samsung, protocol_sirc, rle_decode, rle_encode, signal_fingerprint,
+    supported_devices, total_duration_micros, try_vec_bits, validate_frames, xor_bits, Bit,
+    BitParseError, DecodedFrameWithQuality, DecordedInfraredRemoteFrame, DetectedProtocol,
+    FrameValidation, InfraredRemoteControlCode, InfraredRemoteFrame, InfraredRemoteProtocol,
+    IrCarrier, IrDecodeStream, MarkAndSpace, MarkAndSpaceDemodulator, MarkAndSpaceHuman,
+    MarkAndSpaceMicros, Microseconds, ProtocolTolerances, SignalComparison, AEHA_CARRIER,
+    NEC_CARRIER, SIRC_CARRIER, SUPPORTED_PROTOCOLS,
 };
-use parsing::parse_infrared_code_text;
+use nom::{error::convert_error, Finish};
+use parsing::{
+    check_input_bytes, parse_infrared_code_text, parse_infrared_code_text_with_format,
+    parse_pigpio_irrp_multi_format, IrCodeFormat,
+};
+use serde::{Serialize as _, Serializer as _};
 use serde_wasm_bindgen::Error;
 use wasm_bindgen::prelude::*;
 
+// モジュールの読み込み時にパニックの内容をコンソールに出力するようにする
+#[wasm_bindgen(start)]
+pub fn init() {
+    #[cfg(feature = "console_error_panic_hook")]
+    console_error_panic_hook::set_once();
+}
+
 #[wasm_bindgen]
 extern "C" {
     fn alert(s: &str);
@@ -46,7 +80,13 @@ export type DecordedInfraredRemoteFrame =
 	| { Aeha: Uint8Array }
 	| { Nec: Uint8Array }
 	| { NecRepeat: undefined }
+	| { NecRepeatSequence: { count: number } }
 	| { Sirc: Uint8Array }
+	| { SircRepeat: { frame: Uint8Array, count: number } }
+	| { Rc5: Uint8Array }
+	| { Rc6: Uint8Array }
+	| { Samsung: Uint8Array }
+	| { Kaseikyo: Uint8Array }
 "#;
 
 #[wasm_bindgen(typescript_custom_section)]
@@ -55,12 +95,42 @@ const TS_APPEND_CONTENT: &'static str = r#"
 export type InfraredRemoteControlCode = Map<string, string>;
 "#;
 
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// 入力の上限(事前の確認用)
+export function wasm_max_signal_pairs(): number;
+export function wasm_max_input_bytes(): number;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_max_signal_pairs() -> usize {
+    MAX_SIGNAL_PAIRS
+}
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_max_input_bytes() -> usize {
+    MAX_INPUT_BYTES
+}
+
+// 変換する前に入力配列の長さを確かめる
+fn check_input_length(input: &JsValue) -> Result<(), Error> {
+    let length = input
+        .dyn_ref::<js_sys::Array>()
+        .map_or(0, |array| array.length() as usize);
+    check_signal_pairs(length).map_err(|e| Error::new(e.to_string()))
+}
+
+// 入力配列の長さを確かめてから信号に変換する
+fn mark_and_spaces_from_value(input: JsValue) -> Result<Vec<MarkAndSpaceMicros>, Error> {
+    check_input_length(&input)?;
+    serde_wasm_bindgen::from_value(input)
+}
+
 #[wasm_bindgen(typescript_custom_section)]
 const TS_APPEND_CONTENT: &'static str = r#"
 export function wasm_parse_infrared_code(ircode: string): MarkAndSpaceMicros[];
 "#;
 #[wasm_bindgen(skip_typescript)]
 pub fn wasm_parse_infrared_code(input: &str) -> Result<JsValue, Error> {
+    check_input_bytes(input).map_err(|e| Error::new(e.to_string()))?;
     parse_infrared_code_text(input)
         .map_err(|e| Error::new(e.to_string()))
         .and_then(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
@@ -70,19 +140,379 @@ pub fn wasm_parse_infrared_code(input: &str) -> Result<JsValue, Error> {
 
 #[wasm_bindgen(typescript_custom_section)]
 const TS_APPEND_CONTENT: &'static str = r#"
-export function wasm_decord_receiving_data(input: MarkAndSpaceMicros[]): DecordedInfraredRemoteFrame[];
+export function wasm_parse_pigpio_irrp_multi(input: string): { [name: string]: MarkAndSpaceMicros[] };
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_parse_pigpio_irrp_multi(input: &str) -> Result<JsValue, Error> {
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+    parse_pigpio_irrp_multi_format(input)
+        .finish()
+        .map_err(|e| Error::new(convert_error(input, e)))
+        .and_then(
+            |(_, entries): (&str, Vec<(String, Vec<MarkAndSpaceMicros>)>)| {
+                serializer.collect_map(entries)
+            },
+        )
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// 赤外線リモコンコードの書式
+export type IrCodeFormat =
+	| "OnoffPairHex"
+	| "JsonArray"
+	| "PigpioIrrp"
+	| "ClangArray"
+	| "Pronto"
+	| "GlobalCache"
+	| "Broadlink";
+export function wasm_detect_format(ircode: string): IrCodeFormat;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_detect_format(input: &str) -> Result<JsValue, Error> {
+    parse_infrared_code_text_with_format(input)
+        .map_err(|e| Error::new(e.to_string()))
+        .and_then(|(_, format): (Vec<MarkAndSpaceMicros>, IrCodeFormat)| {
+            serde_wasm_bindgen::to_value(&format)
+        })
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// 赤外線リモコンプロトコルの判定結果
+export interface DetectedProtocol {
+	protocol: "NEC" | "AEHA" | "SIRC" | "RC5" | "RC6" | "SAMSUNG" | "KASEIKYO" | "Unknown",
+	frame_count: number,
+};
+export function wasm_detect_protocol(input: MarkAndSpaceMicros[]): DetectedProtocol;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_detect_protocol(input: JsValue) -> Result<JsValue, Error> {
+    mark_and_spaces_from_value(input)
+        .and_then(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+            detect_protocol(&mark_and_spaces).map_err(|e| Error::new(e.to_string()))
+        })
+        .and_then(|detected: DetectedProtocol| serde_wasm_bindgen::to_value(&detected))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// inverted: マークとスペースが反転した(負論理の)信号の場合はtrue
+export function wasm_decord_receiving_data(input: MarkAndSpaceMicros[], inverted?: boolean): DecordedInfraredRemoteFrame[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_decord_receiving_data(
+    input: JsValue,
+    inverted: Option<bool>,
+) -> Result<JsValue, Error> {
+    mark_and_spaces_from_value(input)
+        .and_then(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+            if inverted.unwrap_or(false) {
+                decord_receiving_data_inverted(&mark_and_spaces)
+            } else {
+                decord_receiving_data(&mark_and_spaces, None)
+            }
+            .map_err(|e| Error::new(e.to_string()))
+        })
+        .and_then(|ir_frames: Vec<DecordedInfraredRemoteFrame>| {
+            serde_wasm_bindgen::to_value(&ir_frames)
+        })
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// NECフレームの反転バイトが合わなければエラーにする
+export function wasm_decord_receiving_data_strict(input: MarkAndSpaceMicros[]): DecordedInfraredRemoteFrame[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_decord_receiving_data_strict(input: JsValue) -> Result<JsValue, Error> {
+    mark_and_spaces_from_value(input)
+        .and_then(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+            decord_receiving_data_strict(&mark_and_spaces, None)
+                .map_err(|e| Error::new(e.to_string()))
+        })
+        .and_then(|ir_frames: Vec<DecordedInfraredRemoteFrame>| {
+            serde_wasm_bindgen::to_value(&ir_frames)
+        })
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// priority: 判定するプロトコルの順序
+export function wasm_decord_receiving_data_prioritized(input: MarkAndSpaceMicros[], priority: ("NEC" | "AEHA" | "SIRC" | "RC5" | "RC6" | "SAMSUNG" | "KASEIKYO")[]): DecordedInfraredRemoteFrame[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_decord_receiving_data_prioritized(
+    input: JsValue,
+    priority: Vec<String>,
+) -> Result<JsValue, Error> {
+    let priority = priority
+        .into_iter()
+        .map(|s| serde_wasm_bindgen::from_value(JsValue::from_str(&s)))
+        .collect::<Result<Vec<InfraredRemoteProtocol>, Error>>()?;
+    mark_and_spaces_from_value(input)
+        .and_then(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+            decord_receiving_data_with_priority(&mark_and_spaces, &priority)
+                .map_err(|e| Error::new(e.to_string()))
+        })
+        .and_then(|ir_frames: Vec<DecordedInfraredRemoteFrame>| {
+            serde_wasm_bindgen::to_value(&ir_frames)
+        })
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_collapse_nec_repeats(input: DecordedInfraredRemoteFrame[]): DecordedInfraredRemoteFrame[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_collapse_nec_repeats(input: JsValue) -> Result<JsValue, Error> {
+    serde_wasm_bindgen::from_value(input)
+        .map(|frames: Vec<DecordedInfraredRemoteFrame>| collapse_nec_repeats(&frames))
+        .and_then(|frames: Vec<DecordedInfraredRemoteFrame>| serde_wasm_bindgen::to_value(&frames))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export interface FrameValidation {
+	valid: boolean,
+	errors: string[],
+};
+export function wasm_validate_frames(input: DecordedInfraredRemoteFrame[]): FrameValidation;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_validate_frames(input: JsValue) -> Result<JsValue, Error> {
+    serde_wasm_bindgen::from_value(input)
+        .map(|frames: Vec<DecordedInfraredRemoteFrame>| validate_frames(&frames))
+        .and_then(|result: FrameValidation| serde_wasm_bindgen::to_value(&result))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_collapse_sirc_repeats(input: DecordedInfraredRemoteFrame[]): DecordedInfraredRemoteFrame[];
 "#;
 #[wasm_bindgen(skip_typescript)]
-pub fn wasm_decord_receiving_data(input: JsValue) -> Result<JsValue, Error> {
+pub fn wasm_collapse_sirc_repeats(input: JsValue) -> Result<JsValue, Error> {
     serde_wasm_bindgen::from_value(input)
+        .map(|frames: Vec<DecordedInfraredRemoteFrame>| collapse_sirc_repeats(&frames))
+        .and_then(|frames: Vec<DecordedInfraredRemoteFrame>| serde_wasm_bindgen::to_value(&frames))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// プロトコル毎のずれ時間の許容範囲(マイクロ秒)
+export interface ProtocolTolerances {
+	nec: number,
+	aeha: number,
+	sirc: number,
+};
+export function wasm_decord_receiving_data_with_tolerances(input: MarkAndSpaceMicros[], tolerances: ProtocolTolerances): DecordedInfraredRemoteFrame[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_decord_receiving_data_with_tolerances(
+    input: JsValue,
+    tolerances: JsValue,
+) -> Result<JsValue, Error> {
+    let tolerances: ProtocolTolerances = serde_wasm_bindgen::from_value(tolerances)?;
+    mark_and_spaces_from_value(input)
         .and_then(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
-            decord_receiving_data(&mark_and_spaces).map_err(|e| Error::new(e.to_string()))
+            decord_receiving_data(&mark_and_spaces, Some(&tolerances))
+                .map_err(|e| Error::new(e.to_string()))
         })
         .and_then(|ir_frames: Vec<DecordedInfraredRemoteFrame>| {
             serde_wasm_bindgen::to_value(&ir_frames)
         })
 }
 
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// NECプロトコルのタイミング(基準時間はマイクロ秒)
+export interface NecProtocol {
+	time_base: number,
+	leader_mark_multiplier: number,
+	leader_space_multiplier: number,
+};
+export function wasm_decord_receiving_data_with_nec(input: MarkAndSpaceMicros[], nec: NecProtocol): DecordedInfraredRemoteFrame[];
+// サムスンのNECプロトコル T = 560 μ秒, リーダーパルス 8T, 8T
+export function wasm_samsung_nec(): NecProtocol;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_decord_receiving_data_with_nec(input: JsValue, nec: JsValue) -> Result<JsValue, Error> {
+    let nec: protocol_nec::NecProtocol = serde_wasm_bindgen::from_value(nec)?;
+    mark_and_spaces_from_value(input)
+        .and_then(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+            decord_receiving_data_with_nec(&mark_and_spaces, None, &nec)
+                .map_err(|e| Error::new(e.to_string()))
+        })
+        .and_then(|ir_frames: Vec<DecordedInfraredRemoteFrame>| {
+            serde_wasm_bindgen::to_value(&ir_frames)
+        })
+}
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_samsung_nec() -> JsValue {
+    serde_wasm_bindgen::to_value(&protocol_nec::SAMSUNG_NEC).unwrap_or(JsValue::UNDEFINED)
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// AEHAプロトコルの基準時間(マイクロ秒)
+export interface AehaProtocol {
+	time_base: number,
+};
+export function wasm_decord_receiving_data_with_aeha(input: MarkAndSpaceMicros[], aeha: AehaProtocol): DecordedInfraredRemoteFrame[];
+// パナソニックのAEHAプロトコル T = 425 μ秒
+export function wasm_panasonic_aeha(): AehaProtocol;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_decord_receiving_data_with_aeha(
+    input: JsValue,
+    aeha: JsValue,
+) -> Result<JsValue, Error> {
+    let aeha: protocol_aeha::AehaProtocol = serde_wasm_bindgen::from_value(aeha)?;
+    mark_and_spaces_from_value(input)
+        .and_then(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+            decord_receiving_data_with_aeha(&mark_and_spaces, None, &aeha)
+                .map_err(|e| Error::new(e.to_string()))
+        })
+        .and_then(|ir_frames: Vec<DecordedInfraredRemoteFrame>| {
+            serde_wasm_bindgen::to_value(&ir_frames)
+        })
+}
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_panasonic_aeha() -> JsValue {
+    serde_wasm_bindgen::to_value(&protocol_aeha::PANASONIC_AEHA).unwrap_or(JsValue::UNDEFINED)
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// 見本の信号からプロトコル毎のずれ時間の許容範囲を求める
+export function wasm_calibrate_tolerance(samples: MarkAndSpaceMicros[][]): ProtocolTolerances;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_calibrate_tolerance(samples: JsValue) -> Result<JsValue, Error> {
+    serde_wasm_bindgen::from_value(samples)
+        .map(|samples: Vec<Vec<MarkAndSpaceMicros>>| calibrate_tolerance(&samples))
+        .and_then(|tolerances: ProtocolTolerances| serde_wasm_bindgen::to_value(&tolerances))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// 信号品質付きの復号後の赤外線リモコン信号フレーム
+export interface DecodedFrameWithQuality {
+	frame: DecordedInfraredRemoteFrame,
+	quality: number,
+};
+export function wasm_decord_receiving_data_with_quality(input: MarkAndSpaceMicros[]): DecodedFrameWithQuality[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_decord_receiving_data_with_quality(input: JsValue) -> Result<JsValue, Error> {
+    mark_and_spaces_from_value(input)
+        .and_then(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+            decord_receiving_data_with_quality(&mark_and_spaces)
+                .map_err(|e| Error::new(e.to_string()))
+        })
+        .and_then(|ir_frames: Vec<DecodedFrameWithQuality>| {
+            serde_wasm_bindgen::to_value(&ir_frames)
+        })
+}
+
+/// 逐次復号器
+#[wasm_bindgen]
+pub struct WasmIrDecodeStream {
+    stream: IrDecodeStream,
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_ir_decode_stream_new(): WasmIrDecodeStream;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_ir_decode_stream_new() -> WasmIrDecodeStream {
+    WasmIrDecodeStream {
+        stream: IrDecodeStream::new(None),
+    }
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_ir_decode_stream_push(stream: WasmIrDecodeStream, input: MarkAndSpaceMicros[]): void;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_ir_decode_stream_push(
+    stream: &mut WasmIrDecodeStream,
+    input: JsValue,
+) -> Result<(), Error> {
+    mark_and_spaces_from_value(input).map(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+        mark_and_spaces
+            .into_iter()
+            .for_each(|ms| stream.stream.push(ms))
+    })
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_ir_decode_stream_collect(stream: WasmIrDecodeStream): DecordedInfraredRemoteFrame[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_ir_decode_stream_collect(stream: &mut WasmIrDecodeStream) -> Result<JsValue, Error> {
+    let ir_frames = stream
+        .stream
+        .by_ref()
+        .collect::<Vec<DecordedInfraredRemoteFrame>>();
+    serde_wasm_bindgen::to_value(&ir_frames)
+}
+
+/// 逐次復調器
+#[wasm_bindgen]
+pub struct WasmMarkAndSpaceDemodulator {
+    demodulator: MarkAndSpaceDemodulator,
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// 1つのマークアンドスペースで1ビットを表すプロトコルのみ
+export function wasm_demodulator_new(protocol: "NEC" | "AEHA" | "SIRC" | "SAMSUNG"): WasmMarkAndSpaceDemodulator;
+export function wasm_demodulator_feed(demodulator: WasmMarkAndSpaceDemodulator, input: MarkAndSpaceMicros[]): void;
+export function wasm_demodulator_collect(demodulator: WasmMarkAndSpaceDemodulator): Uint8Array;
+export function wasm_demodulator_protocol(demodulator: WasmMarkAndSpaceDemodulator): "NEC" | "AEHA" | "SIRC" | "SAMSUNG";
+export function wasm_demodulator_repeat_count(demodulator: WasmMarkAndSpaceDemodulator): number;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_demodulator_new(protocol: &str) -> Result<WasmMarkAndSpaceDemodulator, Error> {
+    let protocol: InfraredRemoteProtocol =
+        serde_wasm_bindgen::from_value(JsValue::from_str(protocol))?;
+    MarkAndSpaceDemodulator::new(protocol)
+        .map(|demodulator| WasmMarkAndSpaceDemodulator { demodulator })
+        .ok_or_else(|| Error::new("unsupported protocol"))
+}
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_demodulator_feed(
+    demodulator: &mut WasmMarkAndSpaceDemodulator,
+    input: JsValue,
+) -> Result<(), Error> {
+    mark_and_spaces_from_value(input).map(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+        mark_and_spaces
+            .into_iter()
+            .for_each(|ms| demodulator.demodulator.feed(ms))
+    })
+}
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_demodulator_collect(
+    demodulator: &mut WasmMarkAndSpaceDemodulator,
+) -> Result<JsValue, Error> {
+    let bits = demodulator.demodulator.by_ref().collect::<Vec<Bit>>();
+    serde_wasm_bindgen::to_value(&bits)
+}
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_demodulator_protocol(
+    demodulator: &WasmMarkAndSpaceDemodulator,
+) -> Result<JsValue, Error> {
+    serde_wasm_bindgen::to_value(&demodulator.demodulator.protocol())
+}
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_demodulator_repeat_count(demodulator: &WasmMarkAndSpaceDemodulator) -> usize {
+    demodulator.demodulator.repeat_count()
+}
+
 #[wasm_bindgen(typescript_custom_section)]
 const TS_APPEND_CONTENT: &'static str = r#"
 export function wasm_decord_ir_frames(input: DecordedInfraredRemoteFrame[]): any;
@@ -93,3 +523,762 @@ pub fn wasm_decord_ir_frames(input: JsValue) -> Result<JsValue, Error> {
         .and_then(|frames: Vec<DecordedInfraredRemoteFrame>| Ok(decord_ir_frames(&frames)))
         .and_then(|codes: Vec<InfraredRemoteControlCode>| serde_wasm_bindgen::to_value(&codes))
 }
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_decord_ir_frames_with_fallback(input: DecordedInfraredRemoteFrame[]): InfraredRemoteControlCode[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_decord_ir_frames_with_fallback(input: JsValue) -> Result<JsValue, Error> {
+    serde_wasm_bindgen::from_value(input)
+        .map(|frames: Vec<DecordedInfraredRemoteFrame>| decord_ir_frames_with_fallback(&frames))
+        .and_then(|codes: Vec<InfraredRemoteControlCode>| serde_wasm_bindgen::to_value(&codes))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_parse_and_decode(ircode: string): Map<string, string>[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_parse_and_decode(input: &str) -> Result<JsValue, Error> {
+    parse_and_decode(input)
+        .map_err(|e| Error::new(e.to_string()))
+        .and_then(|codes: Vec<InfraredRemoteControlCode>| serde_wasm_bindgen::to_value(&codes))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_batch_parse_and_decode(inputs: string[]): (Map<string, string>[] | null)[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_batch_parse_and_decode(inputs: JsValue) -> Result<JsValue, Error> {
+    // 失敗したものは null にする
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_missing_as_null(true);
+    serde_wasm_bindgen::from_value(inputs)
+        .map(|inputs: Vec<String>| batch_parse_and_decode(&inputs))
+        .and_then(|results: Vec<Option<Vec<InfraredRemoteControlCode>>>| {
+            results.serialize(&serializer)
+        })
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// 判定できなければundefined
+export function wasm_identify_manufacturer(input: DecordedInfraredRemoteFrame[]): string | undefined;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_identify_manufacturer(input: JsValue) -> Result<JsValue, Error> {
+    serde_wasm_bindgen::from_value(input)
+        .map(|frames: Vec<DecordedInfraredRemoteFrame>| identify_manufacturer(&frames))
+        .and_then(|manufacturer: Option<&str>| serde_wasm_bindgen::to_value(&manufacturer))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_decode_raw_bits(protocol: "NEC" | "AEHA" | "SIRC" | "RC5" | "RC6" | "SAMSUNG" | "KASEIKYO", bits: Uint8Array): Map<string, string>[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_decode_raw_bits(protocol: &str, bits: JsValue) -> Result<JsValue, Error> {
+    let protocol: InfraredRemoteProtocol =
+        serde_wasm_bindgen::from_value(JsValue::from_str(protocol))?;
+    serde_wasm_bindgen::from_value(bits)
+        .map(|bits: Vec<Bit>| decode_raw_bits(protocol, bits))
+        .and_then(|codes: Vec<InfraredRemoteControlCode>| serde_wasm_bindgen::to_value(&codes))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_decord_ir_frames_fahrenheit(input: DecordedInfraredRemoteFrame[]): any;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_decord_ir_frames_fahrenheit(input: JsValue) -> Result<JsValue, Error> {
+    serde_wasm_bindgen::from_value(input)
+        .map(|frames: Vec<DecordedInfraredRemoteFrame>| {
+            decord_ir_frames(&frames)
+                .into_iter()
+                .map(InfraredRemoteControlCode::into_fahrenheit)
+                .collect::<Vec<InfraredRemoteControlCode>>()
+        })
+        .and_then(|codes: Vec<InfraredRemoteControlCode>| serde_wasm_bindgen::to_value(&codes))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_decord_ir_frames_all(input: DecordedInfraredRemoteFrame[]): any[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_decord_ir_frames_all(input: JsValue) -> Result<JsValue, Error> {
+    serde_wasm_bindgen::from_value(input)
+        .map(|frames: Vec<DecordedInfraredRemoteFrame>| decord_ir_frames_all(&frames))
+        .and_then(|codes: Vec<Vec<InfraredRemoteControlCode>>| serde_wasm_bindgen::to_value(&codes))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_diff_control_codes(a: InfraredRemoteControlCode, b: InfraredRemoteControlCode): Map<string, [string | undefined, string | undefined]>;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_diff_control_codes(a: JsValue, b: JsValue) -> Result<JsValue, Error> {
+    let a: InfraredRemoteControlCode = serde_wasm_bindgen::from_value(a)?;
+    let b: InfraredRemoteControlCode = serde_wasm_bindgen::from_value(b)?;
+    serde_wasm_bindgen::to_value(&diff(&a, &b))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_control_code_to_string(input: InfraredRemoteControlCode): string;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_control_code_to_string(input: JsValue) -> Result<String, Error> {
+    serde_wasm_bindgen::from_value(input).map(|code: InfraredRemoteControlCode| code.to_string())
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// 復号結果の型付きの項目
+export interface ControlCodeFields {
+	manufacturer: string | null,
+	hvac_mode: string | null,
+	temperature: number | null,
+	power_switch: boolean | null,
+	fan_speed: string | null,
+	checksum: number | null,
+	field_names: string[],
+};
+export function wasm_control_code_fields(input: InfraredRemoteControlCode): ControlCodeFields;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_control_code_fields(input: JsValue) -> Result<JsValue, Error> {
+    serde_wasm_bindgen::from_value(input)
+        .and_then(|code: InfraredRemoteControlCode| serde_wasm_bindgen::to_value(&code.fields()))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_control_code_to_json(input: InfraredRemoteControlCode): string;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_control_code_to_json(input: JsValue) -> Result<String, Error> {
+    serde_wasm_bindgen::from_value(input)
+        .map(|code: InfraredRemoteControlCode| code.to_json_string())
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// 信号の比較結果
+export interface SignalComparison {
+	match: boolean,
+	similarity: number,
+	mismatched_at: number | null,
+};
+export function wasm_compare_signals(a: MarkAndSpaceMicros[], b: MarkAndSpaceMicros[], tolerance_micros: number): SignalComparison;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_compare_signals(
+    a: JsValue,
+    b: JsValue,
+    tolerance_micros: u32,
+) -> Result<JsValue, Error> {
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_missing_as_null(true);
+    let a = mark_and_spaces_from_value(a)?;
+    mark_and_spaces_from_value(b)
+        .map(|b: Vec<MarkAndSpaceMicros>| compare_signals(&a, &b, Microseconds(tolerance_micros)))
+        .and_then(|result: SignalComparison| result.serialize(&serializer))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_signal_fingerprint(input: MarkAndSpaceMicros[]): string;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_signal_fingerprint(input: JsValue) -> Result<String, Error> {
+    mark_and_spaces_from_value(input).map(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+        signal_fingerprint(&mark_and_spaces)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    })
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_to_pronto_hex(input: MarkAndSpaceMicros[], carrier_hz: number): string;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_to_pronto_hex(input: JsValue, carrier_hz: u16) -> Result<String, Error> {
+    mark_and_spaces_from_value(input)
+        .map(|mark_and_spaces: Vec<MarkAndSpaceMicros>| to_pronto_hex(&mark_and_spaces, carrier_hz))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// 全てのペアを繰り返し部分に置いたPronto形式
+export function wasm_to_pronto_code(input: MarkAndSpaceMicros[], carrier_hz: number): string;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_to_pronto_code(input: JsValue, carrier_hz: u32) -> Result<String, Error> {
+    mark_and_spaces_from_value(input).map(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+        to_pronto_code(&mark_and_spaces, carrier_hz)
+    })
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export interface HomeAssistantIrCode {
+	protocol: "nec" | "aeha",
+	data: number | number[][],
+};
+export function wasm_to_home_assistant_format(input: InfraredRemoteControlCode): HomeAssistantIrCode | null;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_to_home_assistant_format(input: JsValue) -> Result<JsValue, Error> {
+    let code: InfraredRemoteControlCode = serde_wasm_bindgen::from_value(input)?;
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    to_home_assistant_format(&code).serialize(&serializer)
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_signal_to_carrier_hex(input: MarkAndSpaceMicros[]): string;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_signal_to_carrier_hex(input: JsValue) -> Result<String, Error> {
+    mark_and_spaces_from_value(input)
+        .map(|mark_and_spaces: Vec<MarkAndSpaceMicros>| to_carrier_hex(&mark_and_spaces))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_to_broadlink_base64(input: MarkAndSpaceMicros[]): string;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_to_broadlink_base64(input: JsValue) -> Result<String, Error> {
+    mark_and_spaces_from_value(input)
+        .map(|mark_and_spaces: Vec<MarkAndSpaceMicros>| to_broadlink_base64(&mark_and_spaces))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// Panasonic エアコンの送信設定
+export interface PanasonicHvacSettings {
+	mode: "hvac_mode_auto" | "hvac_mode_dry" | "hvac_mode_cool" | "hvac_mode_heat" | "hvac_mode_fan",
+	power: "power_on" | "power_off",
+	temperature: number,
+	fan_speed: "auto" | "slowest" | "notch2" | "notch3" | "notch4" | "notch5",
+	swing: "auto" | "horizontal" | "notch2" | "notch3" | "notch4" | "notch5",
+	profile: "normal" | "boost" | "quiet",
+};
+export function wasm_encode_panasonic_hvac(settings: PanasonicHvacSettings): MarkAndSpaceMicros[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_encode_panasonic_hvac(settings: JsValue) -> Result<JsValue, Error> {
+    serde_wasm_bindgen::from_value(settings)
+        .and_then(|settings: panasonic_hvac::Settings| {
+            panasonic_hvac::encode_settings(&settings).map_err(|e| Error::new(e.to_string()))
+        })
+        .and_then(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+            serde_wasm_bindgen::to_value(&mark_and_spaces)
+        })
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// Panasonic エアコンの状態
+export interface PanasonicHvacState {
+	temperature: number,
+	mode: "hvac_mode_auto" | "hvac_mode_dry" | "hvac_mode_cool" | "hvac_mode_heat" | "hvac_mode_fan",
+	switch: boolean,
+	swing: "auto" | "horizontal" | "notch2" | "notch3" | "notch4" | "notch5",
+	fan: "auto" | "slowest" | "notch2" | "notch3" | "notch4" | "notch5",
+	profile: "normal" | "boost" | "quiet",
+	on_timer: number | null,
+	off_timer: number | null,
+};
+// 復号結果を確かめて Panasonic エアコンの状態にする
+export function wasm_panasonic_hvac_state(input: InfraredRemoteControlCode): PanasonicHvacState;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_panasonic_hvac_state(input: JsValue) -> Result<JsValue, Error> {
+    serde_wasm_bindgen::from_value(input)
+        .and_then(|code: InfraredRemoteControlCode| {
+            panasonic_hvac::PanasonicHvac::try_from(code).map_err(|e| Error::new(e.to_string()))
+        })
+        .and_then(|hvac| serde_wasm_bindgen::to_value(&hvac))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// Daikin エアコンの送信設定
+export interface DaikinHvacSettings {
+	comfort_mode: boolean,
+	mode: "hvac_mode_auto" | "hvac_mode_dry" | "hvac_mode_cool" | "hvac_mode_heat" | "hvac_mode_fan",
+	power: boolean,
+	temperature: number,
+	fan_speed: "auto" | "silent" | "notch1" | "notch2" | "notch3" | "notch4" | "notch5",
+	swing: boolean,
+	powerful: boolean,
+	econo: boolean,
+	timer_on: boolean,
+	timer_on_minutes: number,
+	timer_off: boolean,
+	timer_off_minutes: number,
+	horizontal_swing?: "stop" | "position1" | "position2" | "position3" | "position4" | "position5" | "swing",
+};
+export function wasm_encode_daikin_hvac(settings: DaikinHvacSettings): MarkAndSpaceMicros[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_encode_daikin_hvac(settings: JsValue) -> Result<JsValue, Error> {
+    serde_wasm_bindgen::from_value(settings)
+        .and_then(|settings: daikin_hvac::Settings| {
+            daikin_hvac::encode_settings(&settings).map_err(|e| Error::new(e.to_string()))
+        })
+        .and_then(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+            serde_wasm_bindgen::to_value(&mark_and_spaces)
+        })
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// Daikin エアコンの状態(タイマーは分)
+export interface DaikinHvacState {
+	comfort_mode: boolean,
+	temperature: number,
+	mode: "hvac_mode_auto" | "hvac_mode_dry" | "hvac_mode_cool" | "hvac_mode_heat" | "hvac_mode_fan",
+	switch: boolean,
+	fan: "auto" | "silent" | "notch1" | "notch2" | "notch3" | "notch4" | "notch5",
+	swing: boolean,
+	horizontal_swing: "stop" | "position1" | "position2" | "position3" | "position4" | "position5" | "swing",
+	powerful: boolean,
+	econo: boolean,
+	on_timer: number | null,
+	off_timer: number | null,
+};
+// 復号結果を確かめて Daikin エアコンの状態にする
+export function wasm_daikin_hvac_state(input: InfraredRemoteControlCode): DaikinHvacState;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_daikin_hvac_state(input: JsValue) -> Result<JsValue, Error> {
+    serde_wasm_bindgen::from_value(input)
+        .and_then(|code: InfraredRemoteControlCode| {
+            daikin_hvac::DaikinHvac::try_from(code).map_err(|e| Error::new(e.to_string()))
+        })
+        .and_then(|hvac| serde_wasm_bindgen::to_value(&hvac))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// Hitachi エアコン(風向, タイマーは実機の受信データと同じにする)
+export function wasm_encode_hitachi_hvac(temperature: number, mode: "hvac_mode_cool" | "hvac_mode_dry_cool" | "hvac_mode_dehumidify" | "hvac_mode_heat" | "hvac_mode_auto" | "hvac_mode_auto_dehumidifying" | "hvac_mode_quick_laundry" | "hvac_mode_condensation_control", fan_speed: "silent" | "low" | "med" | "high" | "auto", power: boolean): MarkAndSpaceMicros[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_encode_hitachi_hvac(
+    temperature: u8,
+    mode: &str,
+    fan_speed: &str,
+    power: bool,
+) -> Result<JsValue, Error> {
+    hitachi_hvac::encode(temperature, mode, fan_speed, power)
+        .map_err(|e| Error::new(e.to_string()))
+        .and_then(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+            serde_wasm_bindgen::to_value(&mark_and_spaces)
+        })
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// 三菱電機エアコンの設定
+// mode1: 1 暖房, 2 除湿, 3 冷房, 4 自動
+// checksumは計算し直す, unmodelled_bits(5バイト)とunmodelled_octets(7バイト)は省略すると0
+export interface MitsubishiElectricHvac {
+	temperature: number,
+	mode1: number,
+	switch: boolean,
+	vane: "auto" | "up" | "notch2" | "notch3" | "notch4" | "notch5" | "down",
+	fan: "auto" | "silent" | "low" | "med" | "high" | "max",
+	checksum?: number,
+	unmodelled_bits?: number[],
+	unmodelled_octets?: number[],
+};
+export function wasm_encode_mitsubishi_electric_hvac(state: MitsubishiElectricHvac): MarkAndSpaceMicros[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_encode_mitsubishi_electric_hvac(state: JsValue) -> Result<JsValue, Error> {
+    serde_wasm_bindgen::from_value(state)
+        .map(|state: mitsubishi_electric_hvac::MitsubishiElectricHvac| {
+            mitsubishi_electric_hvac::encode_mitsubishi_electric_hvac(&state)
+        })
+        .and_then(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+            serde_wasm_bindgen::to_value(&mark_and_spaces)
+        })
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// 対応機器
+export interface SupportedDevice {
+	manufacturer: string,
+	device_type: "tv" | "av" | "hvac",
+};
+export function wasm_get_supported_devices(): SupportedDevice[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_get_supported_devices() -> JsValue {
+    serde_wasm_bindgen::to_value(&supported_devices()).unwrap_or(JsValue::UNDEFINED)
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_get_control_code_schema(): object;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_get_control_code_schema() -> JsValue {
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    json_schema_for_control_code()
+        .serialize(&serializer)
+        .unwrap_or(JsValue::UNDEFINED)
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_get_supported_protocols(): string[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_get_supported_protocols() -> JsValue {
+    serde_wasm_bindgen::to_value(&SUPPORTED_PROTOCOLS).unwrap_or(JsValue::UNDEFINED)
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_total_duration_micros(input: MarkAndSpaceMicros[]): number;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_total_duration_micros(input: JsValue) -> Result<u32, Error> {
+    mark_and_spaces_from_value(input)
+        .map(|mark_and_spaces: Vec<MarkAndSpaceMicros>| total_duration_micros(&mark_and_spaces).0)
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// 時間を "9000µs" の文字列にしたマークアンドスペース
+export interface MarkAndSpaceHuman {
+	mark: string,
+	space: string,
+};
+export function wasm_signal_to_human(input: MarkAndSpaceMicros[]): MarkAndSpaceHuman[];
+// 数値, "9000µs", "9000us", "9ms" を受け付ける
+export function wasm_signal_from_human(input: MarkAndSpaceHuman[]): MarkAndSpaceMicros[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_signal_to_human(input: JsValue) -> Result<JsValue, Error> {
+    mark_and_spaces_from_value(input)
+        .map(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+            mark_and_spaces
+                .into_iter()
+                .map(MarkAndSpaceHuman::from)
+                .collect::<Vec<MarkAndSpaceHuman>>()
+        })
+        .and_then(|human| serde_wasm_bindgen::to_value(&human))
+}
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_signal_from_human(input: JsValue) -> Result<JsValue, Error> {
+    check_input_length(&input)?;
+    serde_wasm_bindgen::from_value(input)
+        .map(|human: Vec<MarkAndSpaceHuman>| {
+            human
+                .into_iter()
+                .map(MarkAndSpaceMicros::from)
+                .collect::<Vec<MarkAndSpaceMicros>>()
+        })
+        .and_then(|mark_and_spaces| serde_wasm_bindgen::to_value(&mark_and_spaces))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_count_frames(input: MarkAndSpaceMicros[]): number;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_count_frames(input: JsValue) -> Result<usize, Error> {
+    mark_and_spaces_from_value(input)
+        .map(|mark_and_spaces: Vec<MarkAndSpaceMicros>| count_frames(&mark_and_spaces))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_frame_durations(input: MarkAndSpaceMicros[]): number[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_frame_durations(input: JsValue) -> Result<JsValue, Error> {
+    mark_and_spaces_from_value(input)
+        .map(|mark_and_spaces: Vec<MarkAndSpaceMicros>| frame_durations(&mark_and_spaces))
+        .and_then(|durations: Vec<Microseconds>| serde_wasm_bindgen::to_value(&durations))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// データビット数の推定値(リーダーパルスとストップビットを除いた数)
+export function wasm_frame_bit_count_estimate(input: InfraredRemoteFrame): number;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_frame_bit_count_estimate(input: JsValue) -> Result<usize, Error> {
+    mark_and_spaces_from_value(input).map(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+        mark_and_spaces
+            .into_iter()
+            .collect::<InfraredRemoteFrame>()
+            .bit_count_estimate()
+    })
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_normalize_signal(input: MarkAndSpaceMicros[], protocol: "NEC" | "AEHA" | "SIRC"): MarkAndSpaceMicros[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_normalize_signal(input: JsValue, protocol: &str) -> Result<JsValue, Error> {
+    let protocol: InfraredRemoteProtocol =
+        serde_wasm_bindgen::from_value(JsValue::from_str(protocol))?;
+    mark_and_spaces_from_value(input)
+        .map(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+            normalize_to_protocol(&mark_and_spaces, protocol)
+        })
+        .and_then(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+            serde_wasm_bindgen::to_value(&mark_and_spaces)
+        })
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// 連続する同じマークアンドスペースを [値, 個数] にまとめる
+export function wasm_rle_encode(input: MarkAndSpaceMicros[]): [MarkAndSpaceMicros, number][];
+export function wasm_rle_decode(input: [MarkAndSpaceMicros, number][]): MarkAndSpaceMicros[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_rle_encode(input: JsValue) -> Result<JsValue, Error> {
+    mark_and_spaces_from_value(input)
+        .map(|mark_and_spaces: Vec<MarkAndSpaceMicros>| rle_encode(&mark_and_spaces))
+        .and_then(|rle| serde_wasm_bindgen::to_value(&rle))
+}
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_rle_decode(input: JsValue) -> Result<JsValue, Error> {
+    serde_wasm_bindgen::from_value(input)
+        .map(|rle: Vec<(MarkAndSpaceMicros, u32)>| rle_decode(&rle))
+        .and_then(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+            serde_wasm_bindgen::to_value(&mark_and_spaces)
+        })
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// 2つのフレームを確かめてから, aの最後のスペースをgap(マイクロ秒)にしてつなげる
+export function wasm_merge_frames(a: MarkAndSpaceMicros[], gap: number, b: MarkAndSpaceMicros[]): MarkAndSpaceMicros[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_merge_frames(a: JsValue, gap: u32, b: JsValue) -> Result<JsValue, Error> {
+    let frame = |input: JsValue| {
+        mark_and_spaces_from_value(input).and_then(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+            InfraredRemoteFrame::try_from_iter(mark_and_spaces)
+                .map_err(|e| Error::new(e.to_string()))
+        })
+    };
+    let merged = InfraredRemoteFrame::merge(&frame(a)?, Microseconds(gap), &frame(b)?);
+    serde_wasm_bindgen::to_value(&merged.0)
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_carrier_frequency(protocol: "NEC" | "AEHA" | "SIRC"): number;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_carrier_frequency(protocol: &str) -> Result<u32, Error> {
+    let protocol: InfraredRemoteProtocol =
+        serde_wasm_bindgen::from_value(JsValue::from_str(protocol))?;
+    match protocol {
+        InfraredRemoteProtocol::Nec => Ok(NEC_CARRIER.freq_hz),
+        InfraredRemoteProtocol::Aeha => Ok(AEHA_CARRIER.freq_hz),
+        InfraredRemoteProtocol::Sirc => Ok(SIRC_CARRIER.freq_hz),
+        _ => Err(Error::new("unsupported protocol")),
+    }
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_convert_to_carrier_counts(input: MarkAndSpaceMicros[], freq_hz: number): { mark: number; space: number }[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_convert_to_carrier_counts(input: JsValue, freq_hz: u32) -> Result<JsValue, Error> {
+    let carrier = IrCarrier { freq_hz };
+    mark_and_spaces_from_value(input)
+        .map(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+            mark_and_spaces
+                .iter()
+                .map(|ms| MarkAndSpace {
+                    mark: carrier.micros_to_count(ms.mark),
+                    space: carrier.micros_to_count(ms.space),
+                })
+                .collect::<Vec<MarkAndSpace<u32>>>()
+        })
+        .and_then(|counts| serde_wasm_bindgen::to_value(&counts))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_from_binary_string(input: string): Uint8Array;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_from_binary_string(input: &str) -> Result<JsValue, Error> {
+    try_vec_bits!(input)
+        .map_err(|e| Error::new(e.to_string()))
+        .and_then(|bits: Vec<Bit>| serde_wasm_bindgen::to_value(&bits))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// 各文字を4ビット(LSBファースト)にする
+export function wasm_from_hex_string(input: string): Uint8Array;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_from_hex_string(input: &str) -> Result<JsValue, Error> {
+    bits_from_hex_string(input)
+        .map_err(|e| Error::new(e.to_string()))
+        .and_then(|bits: Vec<Bit>| serde_wasm_bindgen::to_value(&bits))
+}
+
+// 0か1の配列をビット型の配列にする
+fn bits_from_u8_array(input: &[u8]) -> Result<Vec<Bit>, Error> {
+    input
+        .iter()
+        .map(|&n| Bit::try_from(n).map_err(Error::new))
+        .collect()
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// 同じ長さのビット列同士のビット毎の演算
+export function wasm_xor_bits(a: Uint8Array, b: Uint8Array): Uint8Array;
+export function wasm_and_bits(a: Uint8Array, b: Uint8Array): Uint8Array;
+export function wasm_or_bits(a: Uint8Array, b: Uint8Array): Uint8Array;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_xor_bits(a: &[u8], b: &[u8]) -> Result<JsValue, Error> {
+    xor_bits(&bits_from_u8_array(a)?, &bits_from_u8_array(b)?)
+        .map_err(Error::new)
+        .and_then(|bits: Vec<Bit>| serde_wasm_bindgen::to_value(&bits))
+}
+
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_and_bits(a: &[u8], b: &[u8]) -> Result<JsValue, Error> {
+    and_bits(&bits_from_u8_array(a)?, &bits_from_u8_array(b)?)
+        .map_err(Error::new)
+        .and_then(|bits: Vec<Bit>| serde_wasm_bindgen::to_value(&bits))
+}
+
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_or_bits(a: &[u8], b: &[u8]) -> Result<JsValue, Error> {
+    or_bits(&bits_from_u8_array(a)?, &bits_from_u8_array(b)?)
+        .map_err(Error::new)
+        .and_then(|bits: Vec<Bit>| serde_wasm_bindgen::to_value(&bits))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// 右端ビットが最下位になるように16ビット/32ビットまで畳み込む
+export function wasm_folding_to_msb_first_u16(input: Uint8Array): number;
+export function wasm_folding_to_msb_first_u32(input: Uint8Array): number;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_folding_to_msb_first_u16(input: &[u8]) -> Result<u16, Error> {
+    bits_from_u8_array(input).map(|bits| folding_to_msb_first_u16(&bits))
+}
+
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_folding_to_msb_first_u32(input: &[u8]) -> Result<u32, Error> {
+    bits_from_u8_array(input).map(|bits| folding_to_msb_first_u32(&bits))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_encode_nec(payload: Uint8Array): MarkAndSpaceMicros[];
+export function wasm_encode_nec_repeat(): MarkAndSpaceMicros[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_encode_nec(payload: &[u8]) -> Result<JsValue, Error> {
+    <[u8; 4]>::try_from(payload)
+        .map_err(|_| Error::new("NEC payload must be 4 octets"))
+        .map(protocol_nec::encode_nec)
+        .and_then(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+            serde_wasm_bindgen::to_value(&mark_and_spaces)
+        })
+}
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_encode_nec_repeat() -> Result<JsValue, Error> {
+    serde_wasm_bindgen::to_value(&protocol_nec::encode_nec_repeat())
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_encode_samsung(payload: Uint8Array): MarkAndSpaceMicros[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_encode_samsung(payload: &[u8]) -> Result<JsValue, Error> {
+    <[u8; 4]>::try_from(payload)
+        .map_err(|_| Error::new("Samsung payload must be 4 octets"))
+        .map(protocol_samsung::encode_samsung)
+        .and_then(|mark_and_spaces: Vec<MarkAndSpaceMicros>| {
+            serde_wasm_bindgen::to_value(&mark_and_spaces)
+        })
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// oem: メーカーコード(2バイト), data: データ(3バイト)
+export function wasm_encode_kaseikyo(oem: Uint8Array, data: Uint8Array): MarkAndSpaceMicros[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_encode_kaseikyo(oem: &[u8], data: &[u8]) -> Result<JsValue, Error> {
+    let oem = <[u8; 2]>::try_from(oem).map_err(|_| Error::new("OEM code must be 2 octets"))?;
+    let data = <[u8; 3]>::try_from(data).map_err(|_| Error::new("data must be 3 octets"))?;
+    serde_wasm_bindgen::to_value(&protocol_kaseikyo::encode_frame(oem, data))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+export function wasm_encode_sirc(bits: 12 | 15 | 20, command: number, address: number, extended: number): MarkAndSpaceMicros[];
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_encode_sirc(
+    bits: u8,
+    command: u8,
+    address: u8,
+    extended: u8,
+) -> Result<JsValue, Error> {
+    let mark_and_spaces = match bits {
+        12 => protocol_sirc::encode_sirc12(command, address),
+        15 => protocol_sirc::encode_sirc15(command, address),
+        20 => protocol_sirc::encode_sirc20(command, address, extended),
+        _ => return Err(Error::new("SIRC frame must be 12, 15 or 20 bits")),
+    };
+    serde_wasm_bindgen::to_value(&mark_and_spaces)
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// [アドレス, コマンド, トグルビット]
+export function wasm_demodulate_rc5(input: MarkAndSpaceMicros[]): [number, number, boolean] | undefined;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_demodulate_rc5(input: JsValue) -> Result<JsValue, Error> {
+    mark_and_spaces_from_value(input)
+        .map(|mark_and_spaces: Vec<MarkAndSpaceMicros>| protocol_rc5::demodulate(&mark_and_spaces))
+        .and_then(|fields| serde_wasm_bindgen::to_value(&fields))
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+// [モード, トグルビット, アドレス, コマンド]
+export function wasm_demodulate_rc6(input: MarkAndSpaceMicros[]): [number, boolean, number, number] | undefined;
+"#;
+#[wasm_bindgen(skip_typescript)]
+pub fn wasm_demodulate_rc6(input: JsValue) -> Result<JsValue, Error> {
+    mark_and_spaces_from_value(input)
+        .map(|mark_and_spaces: Vec<MarkAndSpaceMicros>| protocol_rc6::demodulate(&mark_and_spaces))
+        .and_then(|fields| serde_wasm_bindgen::to_value(&fields))
+}
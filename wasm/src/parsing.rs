@@ -2,19 +2,53 @@
 // Licensed under the MIT License <https://spdx.org/licenses/MIT.html>
 // See LICENSE file in the project root for full license information.
 //
+use crate::format::PRONTO_CLOCK_PERIOD_MICROS;
 use crate::infrared_remote::{IrCarrierCounter, MarkAndSpaceMicros, Microseconds};
+use base64::{engine::general_purpose, Engine as _};
 use nom::{
     branch::{alt, permutation},
-    bytes::complete::{escaped_transform, take_while_m_n},
-    character::complete::{char, digit1, multispace0, none_of},
-    combinator::{map, map_res, opt, value},
+    bytes::complete::{escaped_transform, tag, take_while_m_n},
+    character::complete::{char, digit1, multispace0, multispace1, none_of},
+    combinator::{all_consuming, map, map_res, opt, value, verify},
     error::{convert_error, VerboseError},
-    multi::many1,
-    sequence::{delimited, tuple},
+    multi::{count, many1, separated_list1},
+    sequence::{delimited, pair, preceded, terminated, tuple},
     Finish, IResult,
 };
+use serde::Serialize;
 use std::char::{decode_utf16, REPLACEMENT_CHARACTER};
 use std::error::Error;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum BroadlinkFormatError {
+    #[error("invalid base64 string.")]
+    InvalidBase64,
+    #[error("not an infrared packet. (header {0:#04x})")]
+    NotInfraredPacket(u8),
+    #[error("insufficient input data. (expected {0} bytes, actual {1} bytes)")]
+    InsufficientInputData(usize, usize),
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum InputSizeError {
+    #[error("input too long. (limit {limit} bytes, actual {actual} bytes)")]
+    TooLongInput { limit: usize, actual: usize },
+}
+
+/// 入力文字列の最大バイト数
+pub const MAX_INPUT_BYTES: usize = 65_536;
+
+/// 入力文字列が上限以下であることを確かめる
+pub fn check_input_bytes(input: &str) -> Result<(), InputSizeError> {
+    if MAX_INPUT_BYTES < input.len() {
+        return Err(InputSizeError::TooLongInput {
+            limit: MAX_INPUT_BYTES,
+            actual: input.len(),
+        });
+    }
+    Ok(())
+}
 
 // 入力文字列を16進数として解釈する。
 fn from_hexadecimal_str(s: &str) -> Result<u8, std::num::ParseIntError> {
@@ -164,6 +198,37 @@ fn parse_pigpio_irrp_format<'a>(
     )(s)
 }
 
+// 名前付きの赤外線リモコンコード
+pub type NamedMarkAndSpaces = (String, Vec<MarkAndSpaceMicros>);
+
+// 複数の名前付きコードを含むpigpioのirrp形式の文字列を解析する
+pub fn parse_pigpio_irrp_multi_format(
+    s: &str,
+) -> IResult<&str, Vec<NamedMarkAndSpaces>, VerboseError<&str>> {
+    fn json_object_entry(s: &str) -> IResult<&str, NamedMarkAndSpaces, VerboseError<&str>> {
+        let (s, _) = multispace0(s)?;
+        let (s, name) = string_literal(s)?;
+        let (s, _) = multispace0(s)?;
+        let (s, _) = char(':')(s)?;
+        let (s, _) = multispace0(s)?;
+        let (s, vs) = parse_json_array_format(s)?;
+        let (s, _) = multispace0(s)?;
+        Ok((s, (name, vs)))
+    }
+    let (s, _) = multispace0(s)?;
+    let (s, entries) = delimited(
+        char('{'),
+        terminated(
+            separated_list1(char(','), json_object_entry),
+            opt(pair(char(','), multispace0)),
+        ),
+        char('}'),
+    )(s)?;
+    // 後ろに余計な文字があればエラー
+    let (s, _) = all_consuming(multispace0)(s)?;
+    Ok((s, entries))
+}
+
 // C配列の文字列を解析する
 fn parse_clang_array_format<'a>(
     s: &'a str,
@@ -180,19 +245,175 @@ fn parse_clang_array_format<'a>(
     )(s)
 }
 
+// Pronto形式の16進数文字列を解析する
+//
+// 0000 <周波数> <1回目のペア数> <繰り返しのペア数> <ON> <OFF>...
+fn parse_pronto_hex_format(s: &str) -> IResult<&str, Vec<MarkAndSpaceMicros>, VerboseError<&str>> {
+    // 4桁の16進数(16ビット)
+    fn word(s: &str) -> IResult<&str, u16, VerboseError<&str>> {
+        let hexadecimal_16bits_str = take_while_m_n(4, 4, |c: char| c.is_ascii_hexdigit());
+        map_res(hexadecimal_16bits_str, |s| u16::from_str_radix(s, 16))(s)
+    }
+    let (s, _) = multispace0(s)?;
+    let (s, _) = tag("0000")(s)?;
+    let (s, frequency) = preceded(multispace1, verify(word, |&w| w > 0))(s)?;
+    let (s, once) = preceded(multispace1, word)(s)?;
+    let (s, repeat) = preceded(multispace1, word)(s)?;
+    let pairs = once as usize + repeat as usize;
+    let (s, words) = verify(
+        count(preceded(multispace1, word), 2 * pairs),
+        |words: &Vec<u16>| !words.is_empty(),
+    )(s)?;
+    let (s, _) = multispace0(s)?;
+    // 搬送波の周期数をマイクロ秒にする
+    let carrier_period = frequency as f64 * PRONTO_CLOCK_PERIOD_MICROS;
+    let to_micros = |w: u16| Microseconds((w as f64 * carrier_period).round() as u32);
+    let mark_and_spaces = words
+        .chunks(2)
+        .map(|pair| MarkAndSpaceMicros {
+            mark: to_micros(pair[0]),
+            space: to_micros(pair[1]),
+        })
+        .collect();
+    Ok((s, mark_and_spaces))
+}
+
+// Global Caché形式(sendir)の文字列を解析する
+//
+// sendir,<モジュール>:<ポート>,<ID>,<搬送波周波数>,<リピート回数>,<オフセット>,<ON>,<OFF>,...
+// ON/OFFの時間は搬送波の周期数で表す
+pub fn parse_global_cache_format(
+    s: &str,
+) -> IResult<&str, Vec<MarkAndSpaceMicros>, VerboseError<&str>> {
+    fn decimal(s: &str) -> IResult<&str, u32, VerboseError<&str>> {
+        map_res(digit1, str::parse::<u32>)(s)
+    }
+    fn comma(s: &str) -> IResult<&str, char, VerboseError<&str>> {
+        delimited(multispace0, char(','), multispace0)(s)
+    }
+    let (s, _) = multispace0(s)?;
+    let (s, _) = tag("sendir")(s)?;
+    let (s, _) = comma(s)?;
+    let (s, _module_port) = tuple((decimal, char(':'), decimal))(s)?;
+    let (s, _id) = preceded(comma, decimal)(s)?;
+    let (s, frequency) = preceded(comma, verify(decimal, |&hz| hz > 0))(s)?;
+    let (s, _repeat) = preceded(comma, decimal)(s)?;
+    let (s, _offset) = preceded(comma, decimal)(s)?;
+    let (s, counts) = many1(preceded(comma, decimal))(s)?;
+    let (s, _) = multispace0(s)?;
+    // 搬送波の周期数をマイクロ秒にする
+    let to_micros = |count: &u32| {
+        let micros = (*count as u64 * 1_000_000 + frequency as u64 / 2) / frequency as u64;
+        Microseconds(micros as u32)
+    };
+    let pairs = counts
+        .chunks(2)
+        .map(|pair| MarkAndSpaceMicros {
+            mark: to_micros(&pair[0]),
+            //
+            // 最後のoffが存在しなかった場合はなにか適当な値を入れる
+            //
+            space: pair.get(1).map_or(Microseconds(35000u32), to_micros),
+        })
+        .collect();
+    Ok((s, pairs))
+}
+
+// Broadlinkの時間単位 269/8192 ms = 約32.84us をマイクロ秒にする
+fn from_broadlink_ticks(ticks: u32) -> Microseconds {
+    let micros = ticks as u64 * 269_000 / 8192;
+    Microseconds(u32::try_from(micros).unwrap_or(u32::MAX))
+}
+
+// Broadlink形式(base64)の文字列を解析する
+//
+// 0x26(赤外線) 0x00(リピート回数) <長さ下位> <長さ上位> <時間>...
+// 時間は1バイトで表せる場合はそのまま, 256以上は 0x00 <上位> <下位> の3バイトで表す
+pub fn parse_broadlink_format(s: &str) -> Result<Vec<MarkAndSpaceMicros>, Box<dyn Error>> {
+    let packet = general_purpose::STANDARD
+        .decode(s.split_whitespace().collect::<String>())
+        .map_err(|_| BroadlinkFormatError::InvalidBase64)?;
+    let (header, body) = packet
+        .split_at_checked(4)
+        .ok_or(BroadlinkFormatError::InsufficientInputData(4, packet.len()))?;
+    if header[0] != 0x26 {
+        return Err(BroadlinkFormatError::NotInfraredPacket(header[0]).into());
+    }
+    let length = (header[3] as usize) << 8 | header[2] as usize;
+    let body = body
+        .get(..length)
+        .ok_or(BroadlinkFormatError::InsufficientInputData(
+            length,
+            body.len(),
+        ))?;
+    //
+    let mut durations: Vec<Microseconds> = vec![];
+    let mut octets = body.iter();
+    while let Some(&octet) = octets.next() {
+        let ticks = match octet {
+            0x00 => match (octets.next(), octets.next()) {
+                (Some(&higher), Some(&lower)) => (higher as u32) << 8 | lower as u32,
+                _ => return Err(BroadlinkFormatError::InsufficientInputData(3, 1).into()),
+            },
+            n => n as u32,
+        };
+        durations.push(from_broadlink_ticks(ticks));
+    }
+    if durations.is_empty() {
+        return Err(BroadlinkFormatError::InsufficientInputData(1, 0).into());
+    }
+    Ok(durations
+        .chunks(2)
+        .map(|pair| MarkAndSpaceMicros {
+            mark: pair[0],
+            //
+            // 最後のoffが存在しなかった場合はなにか適当な値を入れる
+            //
+            space: pair.get(1).copied().unwrap_or(Microseconds(35000u32)),
+        })
+        .collect())
+}
+
 // 入力文字列のパーサー
-pub fn parse_infrared_code_text<'a>(
-    input: &'a str,
-) -> Result<Vec<MarkAndSpaceMicros>, Box<dyn Error>> {
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+/// 赤外線リモコンコードの書式
+pub enum IrCodeFormat {
+    OnoffPairHex,
+    JsonArray,
+    PigpioIrrp,
+    ClangArray,
+    Pronto,
+    GlobalCache,
+    Broadlink,
+}
+
+/// 赤外線リモコンコードを読み込んで, 判定した書式と共に返す
+pub fn parse_infrared_code_text_with_format(
+    input: &str,
+) -> Result<(Vec<MarkAndSpaceMicros>, IrCodeFormat), Box<dyn Error>> {
     alt((
-        parse_onoff_pair_format,
-        parse_json_array_format,
-        parse_pigpio_irrp_format,
-        parse_clang_array_format,
+        map(parse_onoff_pair_format, |v| (v, IrCodeFormat::OnoffPairHex)),
+        map(parse_json_array_format, |v| (v, IrCodeFormat::JsonArray)),
+        map(parse_pigpio_irrp_format, |v| (v, IrCodeFormat::PigpioIrrp)),
+        map(parse_clang_array_format, |v| (v, IrCodeFormat::ClangArray)),
+        map(parse_pronto_hex_format, |v| (v, IrCodeFormat::Pronto)),
+        map(parse_global_cache_format, |v| {
+            (v, IrCodeFormat::GlobalCache)
+        }),
     ))(input)
     .finish()
     .map(|(_, v)| v)
     .map_err(|e| convert_error(input, e).into())
+    .or_else(|e: Box<dyn Error>| {
+        parse_broadlink_format(input)
+            .map(|v| (v, IrCodeFormat::Broadlink))
+            .map_err(|_| e)
+    })
+}
+
+/// 赤外線リモコンコードを読み込む
+pub fn parse_infrared_code_text(input: &str) -> Result<Vec<MarkAndSpaceMicros>, Box<dyn Error>> {
+    parse_infrared_code_text_with_format(input).map(|(v, _)| v)
 }
 
 #[cfg(test)]
@@ -311,4 +532,171 @@ mod parsing_tests {
         ];
         assert_eq!(x, y);
     }
+
+    #[test]
+    fn test11_parse_infrared_code_text() {
+        // 0x26 0x00 0x05 0x00 | 0x11 0x22 0x00 0x01 0x00
+        let x = parse_infrared_code_text("JgAFABEiAAEA").unwrap();
+        let y = vec![
+            MarkAndSpaceMicros::from((Microseconds(558), Microseconds(1116))),
+            MarkAndSpaceMicros::from((Microseconds(8406), Microseconds(35000))),
+        ];
+        assert_eq!(x, y);
+        // 0x26 0x00 0x04 0x00 | 0x11 0x00 0xff 0xff
+        let x = parse_infrared_code_text("JgAEABEA//8=").unwrap();
+        let y = vec![MarkAndSpaceMicros::from((
+            Microseconds(558),
+            Microseconds(2151967),
+        ))];
+        assert_eq!(x, y);
+    }
+
+    #[test]
+    fn test12_parse_infrared_code_text() {
+        use crate::infrared_remote::decord_receiving_data;
+        let onoff_pair = parse_infrared_code_text("5601A900180015001800140018001400190013001900140019001400170040001700150018003F0019003E0018003E0019003F0019003E00170040001800140019003E001800150018003F00180014001800140019003F0018001400170016001700150018003F001800140018003F0018003F001800140019003F0018003F0018003E0019004F03").unwrap();
+        let broadlink = parse_infrared_code_text("JgBIAAABEocTERMQExAUDxQQFBASMxIREzIUMhMyFDIUMhIzExAUMhMREzITEBMQFDITEBISEhETMhMQEzITMhMQFDITMhMyFAANBQAAAAA=").unwrap();
+        assert_eq!(broadlink.len(), onoff_pair.len());
+        // 量子化誤差(1カウント 約33us)の範囲で一致する
+        for (x, y) in broadlink
+            .iter()
+            .zip(onoff_pair.iter())
+            .take(onoff_pair.len() - 1)
+        {
+            assert!(x.mark.0.abs_diff(y.mark.0) <= 33);
+            assert!(x.space.0.abs_diff(y.space.0) <= 33);
+        }
+        assert_eq!(
+            decord_receiving_data(&broadlink, None).unwrap(),
+            decord_receiving_data(&onoff_pair, None).unwrap()
+        );
+    }
+
+    #[test]
+    fn test13_parse_infrared_code_text() {
+        // 0x26ではない
+        let x = parse_broadlink_format("sgAFABEiAAEA");
+        assert_eq!(
+            x.unwrap_err().to_string(),
+            BroadlinkFormatError::NotInfraredPacket(0xb2).to_string()
+        );
+        // 長さが足りない
+        let x = parse_broadlink_format("JgAIABEiAAEA");
+        assert_eq!(
+            x.unwrap_err().to_string(),
+            BroadlinkFormatError::InsufficientInputData(8, 5).to_string()
+        );
+    }
+
+    #[test]
+    fn test14_parse_infrared_code_text() {
+        let x = parse_infrared_code_text("sendir,1:1,1,38000,1,1,341,171,21,21,21,64,21").unwrap();
+        let y = vec![
+            MarkAndSpaceMicros::from((Microseconds(8974), Microseconds(4500))),
+            MarkAndSpaceMicros::from((Microseconds(553), Microseconds(553))),
+            MarkAndSpaceMicros::from((Microseconds(553), Microseconds(1684))),
+            MarkAndSpaceMicros::from((Microseconds(553), Microseconds(35000))),
+        ];
+        assert_eq!(x, y);
+    }
+
+    #[test]
+    fn test15_parse_infrared_code_text() {
+        // 搬送波周波数が0
+        let x = parse_global_cache_format("sendir,1:1,1,0,1,1,341,171");
+        assert!(x.is_err());
+        // ON/OFFの時間が無い
+        let x = parse_global_cache_format("sendir,1:1,1,38000,1,1");
+        assert!(x.is_err());
+    }
+
+    #[test]
+    fn test16_parse_infrared_code_text_with_format() {
+        let testcases = [
+            ("5601AA00", IrCodeFormat::OnoffPairHex),
+            ("5601AA00 17001500", IrCodeFormat::OnoffPairHex),
+            ("[ 9000, 4473 , 605, 552, ]", IrCodeFormat::JsonArray),
+            (
+                r#" { "data": [9000, 4473, 605, 552, ] } "#,
+                IrCodeFormat::PigpioIrrp,
+            ),
+            (r#"{"name":[417,448,418,]}"#, IrCodeFormat::PigpioIrrp),
+            (
+                r#" { "name:name" : [ 417  , 448 , 418 , 450,  ] } "#,
+                IrCodeFormat::PigpioIrrp,
+            ),
+            ("{ 9000, 4473, 605, 552 }", IrCodeFormat::ClangArray),
+            ("0000 006D 0001 0000 0156 00AB", IrCodeFormat::Pronto),
+            (
+                "sendir,1:1,1,38000,1,1,341,171,21,21,21,64,21",
+                IrCodeFormat::GlobalCache,
+            ),
+            ("JgAFABEiAAEA", IrCodeFormat::Broadlink),
+        ];
+        for (input, format) in testcases {
+            let (x, detected) = parse_infrared_code_text_with_format(input).unwrap();
+            assert_eq!(detected, format, "{}", input);
+            assert_eq!(x, parse_infrared_code_text(input).unwrap());
+        }
+        assert!(parse_infrared_code_text_with_format("xyz").is_err());
+    }
+
+    #[test]
+    fn test17_parse_pigpio_irrp_multi_format() {
+        let input = r#"
+            {
+                "power": [9000, 4500, 560, 560, 560],
+                "volume_up": [ 417 , 448 ],
+                "volume:down": [417, 448, 418, 450, ],
+            }
+        "#;
+        let (rest, x) = parse_pigpio_irrp_multi_format(input).unwrap();
+        assert_eq!(rest, "");
+        let y = vec![
+            (
+                "power".to_owned(),
+                vec![
+                    MarkAndSpaceMicros::from((Microseconds(9000), Microseconds(4500))),
+                    MarkAndSpaceMicros::from((Microseconds(560), Microseconds(560))),
+                    MarkAndSpaceMicros::from((Microseconds(560), Microseconds(35000))),
+                ],
+            ),
+            (
+                "volume_up".to_owned(),
+                vec![MarkAndSpaceMicros::from((
+                    Microseconds(417),
+                    Microseconds(448),
+                ))],
+            ),
+            (
+                "volume:down".to_owned(),
+                vec![
+                    MarkAndSpaceMicros::from((Microseconds(417), Microseconds(448))),
+                    MarkAndSpaceMicros::from((Microseconds(418), Microseconds(450))),
+                ],
+            ),
+        ];
+        assert_eq!(x, y);
+        // 1つだけの場合
+        let (_, x) = parse_pigpio_irrp_multi_format(r#"{"name":[417,448]}"#).unwrap();
+        assert_eq!(x.len(), 1);
+        assert!(parse_pigpio_irrp_multi_format("{}").is_err());
+        // 後ろに余計な文字がある
+        assert!(parse_pigpio_irrp_multi_format(r#"{"name":[417,448]} xyz"#).is_err());
+        assert!(parse_pigpio_irrp_multi_format(r#"{"name":[417,448]}}"#).is_err());
+    }
+
+    #[test]
+    fn test18_check_input_bytes() {
+        let input = "0".repeat(MAX_INPUT_BYTES);
+        assert_eq!(check_input_bytes(&input), Ok(()));
+        let input = "0".repeat(MAX_INPUT_BYTES + 1);
+        assert_eq!(
+            check_input_bytes(&input),
+            Err(InputSizeError::TooLongInput {
+                limit: MAX_INPUT_BYTES,
+                actual: MAX_INPUT_BYTES + 1
+            })
+        );
+    }
 }
@@ -0,0 +1,24 @@
+// Copyright (c) 2023 Akihiro Yamamoto.
+// Licensed under the MIT License <https://spdx.org/licenses/MIT.html>
+// See LICENSE file in the project root for full license information.
+//
+// ブラウザ上でのテスト
+// wasm-pack test --headless --firefox
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn parse_infrared_code() {
+    wasm::init();
+    let result = wasm::wasm_parse_infrared_code("5601AA00");
+    assert!(result.is_ok());
+}
+
+#[wasm_bindgen_test]
+fn parse_infrared_code_error() {
+    let result = wasm::wasm_parse_infrared_code("XYZ");
+    assert!(result.is_err());
+}